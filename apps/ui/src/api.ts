@@ -12,6 +12,184 @@ export type Bookmark = {
   notes?: string | null;
   created_at: number;
   updated_at: number;
+  added_at: number;
+  citation: Citation;
+  metadata: BookmarkMetadata;
+  reading: Reading;
+  review?: ReviewSchedule | null;
+  link_status?: LinkStatus | null;
+};
+
+export type LinkStatus = "ok" | "moved" | "unreachable" | "broken";
+
+export type BookmarkFilter = {
+  workspace_id?: string | null;
+  tag?: string | null;
+  query?: string | null;
+  language?: string | null;
+  site_name?: string | null;
+  author?: string | null;
+  max_reading_time?: number | null;
+  read_state?: ReadState | null;
+  link_status?: LinkStatus | null;
+  sort?: BookmarkSort;
+};
+
+export type SavedSearch = {
+  id: string;
+  name: string;
+  workspace_id?: string | null;
+  query?: string | null;
+  filter: BookmarkFilter;
+  created_at: number;
+  last_viewed_at?: number | null;
+};
+
+export type SavedSearchResults = {
+  search: SavedSearch;
+  total: number;
+  new_count: number;
+  new_bookmark_ids: string[];
+  bookmarks: Bookmark[];
+};
+
+export type TagRuleInput = {
+  name: string;
+  url_pattern?: string | null;
+  domain?: string | null;
+  title_regex?: string | null;
+  has_tags?: string[];
+  tags: string[];
+  confidence?: number;
+  enabled?: boolean;
+};
+
+export type TagRule = Required<TagRuleInput> & {
+  id: string;
+  created_at: number;
+  updated_at: number;
+};
+
+export type Profile = {
+  name: string;
+  db_path: string;
+  active: boolean;
+  settings: Record<string, unknown>;
+};
+
+export type RelationKind = "cites" | "related" | "supersedes" | "contradicts";
+
+export type BookmarkLink = {
+  id: string;
+  source_id: string;
+  target_id: string;
+  kind: RelationKind;
+  notes?: string | null;
+  created_at: number;
+};
+
+export type Neighbour = {
+  link: BookmarkLink;
+  direction: "outgoing" | "incoming";
+  bookmark: Bookmark;
+};
+
+export type BookmarkGraph = {
+  nodes: {
+    id: string;
+    url: string;
+    title: string;
+    site_name?: string | null;
+    degree: number;
+  }[];
+  edges: {
+    id: string;
+    source: string;
+    target: string;
+    kind: RelationKind;
+    notes?: string | null;
+  }[];
+};
+
+export type Tab = {
+  id: string;
+  tab_set_id: string;
+  url: string;
+  title: string;
+  position: number;
+  pinned: boolean;
+  last_active_at?: number | null;
+};
+
+export type TabSet = {
+  id: string;
+  workspace_id: string;
+  name: string;
+  created_at: number;
+  updated_at: number;
+  tabs: Tab[];
+};
+
+export type TabInput = {
+  url: string;
+  title?: string;
+  pinned?: boolean;
+  last_active_at?: number;
+};
+
+export type PromotedTab = {
+  bookmark: Bookmark;
+  job?: TagJob | null;
+  created: boolean;
+};
+
+export type LinkCheck = {
+  id: string;
+  bookmark_id: string;
+  status: LinkStatus;
+  status_code?: number | null;
+  final_url?: string | null;
+  error?: string | null;
+  checked_at: number;
+};
+
+export type ReviewSchedule = {
+  due_at: number;
+  interval_days: number;
+  ease: number;
+  repetitions: number;
+  last_reviewed_at?: number | null;
+};
+
+export type ReviewOutcome = "again" | "hard" | "good" | "easy" | "done";
+
+export type ReadState = "unread" | "reading" | "read" | "archived";
+
+export type Reading = {
+  state: ReadState;
+  progress: number;
+  visit_count: number;
+  last_visited_at?: number | null;
+};
+
+export type BookmarkSort = "updated" | "frecency" | "last_visited";
+
+export type BookmarkMetadata = {
+  description?: string | null;
+  favicon_url?: string | null;
+  site_name?: string | null;
+  author?: string | null;
+  published_at?: number | null;
+  language?: string | null;
+  word_count?: number | null;
+  reading_time_minutes?: number | null;
+};
+
+export type Citation = {
+  authors: string[];
+  venue?: string | null;
+  doi?: string | null;
+  year?: number | null;
 };
 
 export type Tag = {
@@ -20,9 +198,26 @@ export type Tag = {
   created_at: number;
 };
 
+export type TagSource = "Rules" | "Llm" | "User";
+
+export type TagEvidence = {
+  source: TagSource;
+  confidence: number;
+  created_at: number;
+};
+
+export type BookmarkTag = {
+  name: string;
+  confidence: number;
+  source: TagSource;
+  created_at: number;
+  evidence: TagEvidence[];
+};
+
 export type TagJob = {
   id: string;
   bookmark_id: string;
+  kind: "tag" | "link_check";
   status: string;
   attempts: number;
   created_at: number;
@@ -39,6 +234,27 @@ export type CreateBookmarkResponse = {
   job: TagJob;
 };
 
+export type LibraryStats = {
+  workspaces: number;
+  bookmarks: number;
+  tags: number;
+  bookmarks_per_workspace: { workspace_id: string; name: string; bookmarks: number }[];
+  tag_sources: { rules: number; llm: number; user: number };
+  jobs: {
+    pending: number;
+    running: number;
+    done: number;
+    failed: number;
+    oldest_pending_age_secs: number | null;
+  };
+  database: {
+    file_size_bytes: number;
+    page_size: number;
+    page_count: number;
+    free_pages: number;
+  } | null;
+};
+
 const API_URL = import.meta.env.VITE_API_URL ?? "http://127.0.0.1:7316";
 
 async function request<T>(path: string, options?: RequestInit): Promise<T> {
@@ -58,6 +274,31 @@ async function request<T>(path: string, options?: RequestInit): Promise<T> {
   return (await response.json()) as T;
 }
 
+export function listProfiles(): Promise<Profile[]> {
+  return request("/profiles");
+}
+
+export function createProfile(name: string): Promise<Profile> {
+  return request("/profiles", {
+    method: "POST",
+    body: JSON.stringify({ name }),
+  });
+}
+
+export function switchProfile(name: string): Promise<Profile> {
+  return request(`/profiles/${name}/switch`, { method: "POST" });
+}
+
+export function updateProfileSettings(
+  name: string,
+  settings: Record<string, unknown>,
+): Promise<Profile> {
+  return request(`/profiles/${name}/settings`, {
+    method: "PUT",
+    body: JSON.stringify(settings),
+  });
+}
+
 export function listWorkspaces(): Promise<Workspace[]> {
   return request("/workspaces");
 }
@@ -73,11 +314,27 @@ export function listBookmarks(params: {
   workspace_id?: string;
   tag?: string;
   q?: string;
+  language?: string;
+  site_name?: string;
+  author?: string;
+  max_reading_time?: number;
+  read_state?: ReadState;
+  link_status?: LinkStatus;
+  sort?: BookmarkSort;
 } = {}): Promise<Bookmark[]> {
   const query = new URLSearchParams();
   if (params.workspace_id) query.set("workspace_id", params.workspace_id);
   if (params.tag) query.set("tag", params.tag);
   if (params.q) query.set("q", params.q);
+  if (params.language) query.set("language", params.language);
+  if (params.site_name) query.set("site_name", params.site_name);
+  if (params.author) query.set("author", params.author);
+  if (params.max_reading_time !== undefined) {
+    query.set("max_reading_time", String(params.max_reading_time));
+  }
+  if (params.read_state) query.set("read_state", params.read_state);
+  if (params.link_status) query.set("link_status", params.link_status);
+  if (params.sort) query.set("sort", params.sort);
   const suffix = query.toString() ? `?${query.toString()}` : "";
   return request(`/bookmarks${suffix}`);
 }
@@ -87,6 +344,7 @@ export function createBookmark(input: {
   url: string;
   title: string;
   notes?: string;
+  metadata?: BookmarkMetadata;
 }): Promise<CreateBookmarkResponse> {
   return request("/bookmarks", {
     method: "POST",
@@ -94,6 +352,181 @@ export function createBookmark(input: {
   });
 }
 
+export function recordVisit(id: string, progress?: number): Promise<Bookmark> {
+  return request(`/bookmarks/${id}/visits`, {
+    method: "POST",
+    body: JSON.stringify({ progress }),
+  });
+}
+
+export function setReadState(
+  id: string,
+  state: ReadState,
+  progress?: number,
+): Promise<Bookmark> {
+  return request(`/bookmarks/${id}/read-state`, {
+    method: "PUT",
+    body: JSON.stringify({ state, progress }),
+  });
+}
+
+export function scheduleReview(id: string, due_at: number): Promise<Bookmark> {
+  return request(`/bookmarks/${id}/review`, {
+    method: "PUT",
+    body: JSON.stringify({ due_at }),
+  });
+}
+
+export function recordReview(id: string, outcome: ReviewOutcome): Promise<Bookmark> {
+  return request(`/bookmarks/${id}/review`, {
+    method: "POST",
+    body: JSON.stringify({ outcome }),
+  });
+}
+
+export function listDueReviews(params: {
+  workspace_id?: string;
+  tz_offset_minutes?: number;
+  limit?: number;
+} = {}): Promise<Bookmark[]> {
+  const query = new URLSearchParams();
+  if (params.workspace_id) query.set("workspace_id", params.workspace_id);
+  // "Today" is the browser's day unless the caller says otherwise.
+  const offset = params.tz_offset_minutes ?? -new Date().getTimezoneOffset();
+  query.set("tz_offset_minutes", String(offset));
+  if (params.limit !== undefined) query.set("limit", String(params.limit));
+  return request(`/review/due?${query.toString()}`);
+}
+
+export function listLinkChecks(id: string, limit = 20): Promise<LinkCheck[]> {
+  return request(`/bookmarks/${id}/link-checks?limit=${limit}`);
+}
+
+export function listBookmarkTags(id: string): Promise<BookmarkTag[]> {
+  return request(`/bookmarks/${id}/tags`);
+}
+
+export function queueLinkChecks(workspace_id?: string): Promise<TagJob[]> {
+  const suffix = workspace_id ? `?workspace_id=${workspace_id}` : "";
+  return request(`/link-checks${suffix}`, { method: "POST" });
+}
+
+export function listSavedSearches(workspace_id?: string): Promise<SavedSearch[]> {
+  const suffix = workspace_id ? `?workspace_id=${workspace_id}` : "";
+  return request(`/saved-searches${suffix}`);
+}
+
+export function createSavedSearch(input: {
+  name: string;
+  workspace_id?: string;
+  query?: string;
+  filter?: BookmarkFilter;
+}): Promise<SavedSearch> {
+  return request("/saved-searches", {
+    method: "POST",
+    body: JSON.stringify(input),
+  });
+}
+
+export async function deleteSavedSearch(id: string): Promise<void> {
+  const response = await fetch(`${API_URL}/saved-searches/${id}`, { method: "DELETE" });
+  if (!response.ok) {
+    throw new Error(`Request failed: ${response.status}`);
+  }
+}
+
+export function runSavedSearch(id: string): Promise<SavedSearchResults> {
+  return request(`/saved-searches/${id}/bookmarks`);
+}
+
+export function linkBookmarks(
+  id: string,
+  input: { target_id: string; kind: RelationKind; notes?: string },
+): Promise<BookmarkLink> {
+  return request(`/bookmarks/${id}/links`, {
+    method: "POST",
+    body: JSON.stringify(input),
+  });
+}
+
+export function listNeighbours(id: string): Promise<Neighbour[]> {
+  return request(`/bookmarks/${id}/links`);
+}
+
+export async function deleteBookmarkLink(id: string): Promise<void> {
+  const response = await fetch(`${API_URL}/bookmark-links/${id}`, { method: "DELETE" });
+  if (!response.ok) {
+    throw new Error(`Request failed: ${response.status}`);
+  }
+}
+
+export function exportGraph(workspace_id: string): Promise<BookmarkGraph> {
+  return request(`/export/graph?workspace_id=${workspace_id}`);
+}
+
+export function listTabSets(workspace_id?: string): Promise<TabSet[]> {
+  const suffix = workspace_id ? `?workspace_id=${workspace_id}` : "";
+  return request(`/tab-sets${suffix}`);
+}
+
+export function saveTabSet(input: {
+  workspace_id: string;
+  name: string;
+  tabs: TabInput[];
+}): Promise<TabSet> {
+  return request("/tab-sets", {
+    method: "POST",
+    body: JSON.stringify(input),
+  });
+}
+
+export function getTabSet(id: string): Promise<TabSet> {
+  return request(`/tab-sets/${id}`);
+}
+
+export function replaceTabs(id: string, tabs: TabInput[]): Promise<TabSet> {
+  return request(`/tab-sets/${id}`, {
+    method: "PUT",
+    body: JSON.stringify({ tabs }),
+  });
+}
+
+export async function deleteTabSet(id: string): Promise<void> {
+  const response = await fetch(`${API_URL}/tab-sets/${id}`, { method: "DELETE" });
+  if (!response.ok) {
+    throw new Error(`Request failed: ${response.status}`);
+  }
+}
+
+export function promoteTab(id: string): Promise<PromotedTab> {
+  return request(`/tabs/${id}/promote`, { method: "POST" });
+}
+
+export function listTagRules(): Promise<TagRule[]> {
+  return request("/tag-rules");
+}
+
+export function createTagRule(input: TagRuleInput): Promise<TagRule> {
+  return request("/tag-rules", {
+    method: "POST",
+    body: JSON.stringify(input),
+  });
+}
+
+export function updateTagRule(id: string, input: TagRuleInput): Promise<TagRule> {
+  return request(`/tag-rules/${id}`, {
+    method: "PUT",
+    body: JSON.stringify(input),
+  });
+}
+
+export async function deleteTagRule(id: string): Promise<void> {
+  const response = await fetch(`${API_URL}/tag-rules/${id}`, { method: "DELETE" });
+  if (!response.ok) {
+    throw new Error(`Request failed: ${response.status}`);
+  }
+}
+
 export function listTags(): Promise<Tag[]> {
   return request("/tags");
 }
@@ -101,3 +534,7 @@ export function listTags(): Promise<Tag[]> {
 export function listTagCloud(limit = 40): Promise<TagCloudEntry[]> {
   return request(`/tag-cloud?limit=${limit}`);
 }
+
+export function getStats(): Promise<LibraryStats> {
+  return request("/stats");
+}
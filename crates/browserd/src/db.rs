@@ -0,0 +1,80 @@
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use lester_core::Store;
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+
+use crate::AppError;
+
+const SLOW_QUERY: Duration = Duration::from_millis(250);
+
+#[derive(Clone)]
+pub struct Db<S> {
+    // Swapped out when the active profile changes; queries already running
+    // finish against the store they started with.
+    store: Arc<RwLock<S>>,
+    queue: Arc<Semaphore>,
+    workers: Arc<Semaphore>,
+}
+
+impl<S: Store> Db<S> {
+    pub fn new(store: S, workers: usize, queue_depth: usize) -> Self {
+        let workers = workers.max(1);
+        Self {
+            store: Arc::new(RwLock::new(store)),
+            queue: Arc::new(Semaphore::new(workers + queue_depth)),
+            workers: Arc::new(Semaphore::new(workers)),
+        }
+    }
+
+    pub fn replace_store(&self, store: S) {
+        *self.store.write().unwrap_or_else(|err| err.into_inner()) = store;
+    }
+
+    pub async fn run<T, F>(&self, query: &'static str, f: F) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&S) -> lester_core::Result<T> + Send + 'static,
+    {
+        let Ok(_slot) = self.queue.clone().try_acquire_owned() else {
+            warn!(query, "storage queue full, rejecting request");
+            return Err(AppError::Busy);
+        };
+        let queued_at = Instant::now();
+        let worker = self
+            .workers
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|err| AppError::Other(err.to_string()))?;
+        let waited = queued_at.elapsed();
+
+        let store = self.store.read().unwrap_or_else(|err| err.into_inner()).clone();
+        let started = Instant::now();
+        let result = tokio::task::spawn_blocking(move || {
+            let _worker = worker;
+            f(&store)
+        })
+        .await
+        .map_err(|err| AppError::Other(format!("storage task failed: {err}")))?;
+        let elapsed = started.elapsed();
+
+        if elapsed >= SLOW_QUERY {
+            warn!(
+                query,
+                wait_ms = waited.as_millis() as u64,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "slow storage query"
+            );
+        } else {
+            debug!(
+                query,
+                wait_ms = waited.as_millis() as u64,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "storage query"
+            );
+        }
+        Ok(result?)
+    }
+}
@@ -1,39 +1,108 @@
+mod db;
+
 use std::net::SocketAddr;
 
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, patch, post, put},
     Json, Router,
 };
-use lester_core::{BookmarkFilter, BookmarkInput, SqliteStore, TagCloudEntry, TagJob, WorkspaceInput};
+use lester_core::{
+    parse_calibration, BookmarkFilter, BookmarkGraph, BookmarkInput, BookmarkLink,
+    BookmarkLinkInput, BookmarkPatch, BookmarkSort, BookmarkTagDetail, Browser, FolderMapping,
+    HistoryEntry, HistoryFilter, HistoryImportOptions, HistoryTarget, ImportFormat, ImportOptions,
+    ImportReport, JobKind, LibraryStats, LinkCheck, LinkStatus, MaintenanceReport,
+    MaintenanceTask, Neighbour, Profile, ProfileRegistry, ProfileSettings, PromotedTab, ReadState,
+    RelationKind, ReviewOutcome, SavedSearch, SavedSearchInput, SavedSearchResults, SqliteStore,
+    Store, TabInput, TabSet, TabSetInput, TagCloudEntry, TagFusion, TagJob, TagRule,
+    TagRuleInput, VaultReport, WorkspaceInput,
+};
+use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 use uuid::Uuid;
 
+use crate::db::Db;
+
 #[derive(Clone)]
-struct AppState {
-    store: SqliteStore,
+struct AppState<S> {
+    db: Db<S>,
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve the HTTP API (default).
+    Serve,
+    /// Run database maintenance and print a JSON report.
+    Maintenance {
+        /// Task to run; repeat to run several. Runs every task when omitted.
+        #[arg(long = "task", value_parser = parse_maintenance_task)]
+        tasks: Vec<MaintenanceTask>,
+    },
+}
+
+fn parse_maintenance_task(value: &str) -> Result<MaintenanceTask, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string())).map_err(|_| {
+        let names: Vec<_> = MaintenanceTask::ALL.iter().map(|task| task.as_str()).collect();
+        format!("expected one of: {}", names.join(", "))
+    })
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
+    let args = Args::parse();
+
+    let fusion = tag_fusion_from_env()?;
+    // LESTER_DB_PATH pins a single database and turns profiles off.
+    let (store, registry) = match std::env::var("LESTER_DB_PATH") {
+        Ok(db_path) => {
+            let store = SqliteStore::new(db_path).with_tag_fusion(fusion);
+            store.migrate()?;
+            (store, None)
+        }
+        Err(_) => {
+            let data_dir =
+                std::env::var("LESTER_DATA_DIR").unwrap_or_else(|_| "lester-data".to_string());
+            let registry = ProfileRegistry::open(data_dir)?.with_tag_fusion(fusion);
+            let profile = match std::env::var("LESTER_PROFILE") {
+                Ok(name) => registry.switch(&name)?,
+                Err(_) => registry.active()?,
+            };
+            info!("using profile {}", profile.name);
+            (registry.store(&profile.name)?, Some(registry))
+        }
+    };
+
+    if let Some(Command::Maintenance { tasks }) = args.command {
+        let tasks = if tasks.is_empty() { MaintenanceTask::ALL.to_vec() } else { tasks };
+        let report = tokio::task::spawn_blocking(move || store.run_maintenance(&tasks)).await??;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.ok() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
-    let db_path = std::env::var("LESTER_DB_PATH").unwrap_or_else(|_| "lester.db".to_string());
     let addr = std::env::var("LESTER_ADDR").unwrap_or_else(|_| "127.0.0.1:7316".to_string());
 
-    let store = SqliteStore::new(db_path);
-    store.migrate()?;
+    let db_workers = env_usize("LESTER_DB_WORKERS", 4);
+    let db_queue = env_usize("LESTER_DB_QUEUE", 64);
 
-    let app = Router::new()
-        .route("/health", get(health))
-        .route("/workspaces", get(list_workspaces).post(create_workspace))
-        .route("/bookmarks", get(list_bookmarks).post(create_bookmark))
-        .route("/tags", get(list_tags))
-        .route("/tag-cloud", get(tag_cloud))
-        .with_state(AppState { store });
+    let db = Db::new(store, db_workers, db_queue);
+    let mut app = router(db.clone()).merge(admin_router(db.clone()));
+    if let Some(registry) = registry {
+        app = app.merge(profile_router(db, registry));
+    }
 
     let addr: SocketAddr = addr
         .parse()
@@ -44,61 +113,722 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn router<S: Store>(db: Db<S>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/stats", get(stats::<S>))
+        .route("/workspaces", get(list_workspaces::<S>).post(create_workspace::<S>))
+        .route("/bookmarks", get(list_bookmarks::<S>).post(create_bookmark::<S>))
+        .route("/bookmarks/:id", patch(update_bookmark::<S>))
+        .route("/bookmarks/:id/visits", post(record_visit::<S>))
+        .route("/bookmarks/:id/read-state", put(set_read_state::<S>))
+        .route(
+            "/bookmarks/:id/review",
+            put(schedule_review::<S>).post(record_review::<S>),
+        )
+        .route(
+            "/bookmarks/:id/links",
+            get(list_neighbours::<S>).post(link_bookmarks::<S>),
+        )
+        .route("/bookmark-links/:id", delete(delete_bookmark_link::<S>))
+        .route("/review/due", get(list_due_reviews::<S>))
+        .route("/bookmarks/:id/link-checks", get(list_link_checks::<S>))
+        .route("/bookmarks/:id/tags", get(list_bookmark_tags::<S>))
+        .route("/link-checks", post(enqueue_link_checks::<S>))
+        .route(
+            "/saved-searches",
+            get(list_saved_searches::<S>).post(create_saved_search::<S>),
+        )
+        .route("/saved-searches/:id", delete(delete_saved_search::<S>))
+        .route("/saved-searches/:id/bookmarks", get(run_saved_search::<S>))
+        .route("/tab-sets", get(list_tab_sets::<S>).post(save_tab_set::<S>))
+        .route(
+            "/tab-sets/:id",
+            get(get_tab_set::<S>)
+                .put(replace_tabs::<S>)
+                .delete(delete_tab_set::<S>),
+        )
+        .route("/tabs/:id/promote", post(promote_tab::<S>))
+        .route("/tag-rules", get(list_tag_rules::<S>).post(create_tag_rule::<S>))
+        .route(
+            "/tag-rules/:id",
+            put(update_tag_rule::<S>).delete(delete_tag_rule::<S>),
+        )
+        .route("/tags", get(list_tags::<S>))
+        .route("/tag-cloud", get(tag_cloud::<S>))
+        .route("/import/history", post(import_history::<S>))
+        .route("/import/:format", post(import_bookmarks::<S>))
+        .route("/history", get(list_history::<S>))
+        .route("/export/netscape", get(export_netscape::<S>))
+        .route("/export/markdown", post(export_markdown::<S>))
+        .route("/export/bibtex", get(export_bibtex::<S>))
+        .route("/export/csl-json", get(export_csl_json::<S>))
+        .route("/export/graph", get(export_graph::<S>))
+        .with_state(AppState { db })
+}
+
+fn admin_router(db: Db<SqliteStore>) -> Router {
+    Router::new()
+        .route("/admin/maintenance", post(run_all_maintenance))
+        .route("/admin/maintenance/:task", post(run_maintenance_task))
+        .with_state(AppState { db })
+}
+
+#[derive(Clone)]
+struct ProfileState {
+    db: Db<SqliteStore>,
+    registry: ProfileRegistry,
+}
+
+fn profile_router(db: Db<SqliteStore>, registry: ProfileRegistry) -> Router {
+    Router::new()
+        .route("/profiles", get(list_profiles).post(create_profile))
+        .route("/profiles/:name/switch", post(switch_profile))
+        .route("/profiles/:name/settings", put(update_profile_settings))
+        .with_state(ProfileState { db, registry })
+}
+
+fn env_usize(key: &str, default: usize) -> usize {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+// LESTER_TAG_FUSION (`noisy-or` or `weighted`) and LESTER_TAG_CALIBRATION
+// (`rules=0.8,llm=0.9`) mirror the worker's `--fusion` and `--calibration`.
+fn tag_fusion_from_env() -> lester_core::Result<TagFusion> {
+    let mut fusion = TagFusion::default();
+    if let Ok(method) = std::env::var("LESTER_TAG_FUSION") {
+        fusion = fusion.with_method(serde_json::from_value(serde_json::Value::String(method))?);
+    }
+    if let Ok(calibration) = std::env::var("LESTER_TAG_CALIBRATION") {
+        for setting in calibration.split(',').filter(|setting| !setting.trim().is_empty()) {
+            let (source, weight) = parse_calibration(setting)?;
+            fusion = fusion.with_weight(source, weight);
+        }
+    }
+    Ok(fusion)
+}
+
 async fn health() -> Json<HealthResponse> {
     Json(HealthResponse { status: "ok" })
 }
 
-async fn list_workspaces(State(state): State<AppState>) -> Result<Json<Vec<lester_core::Workspace>>, AppError> {
-    let workspaces = state.store.list_workspaces()?;
+async fn stats<S: Store>(State(state): State<AppState<S>>) -> Result<Json<LibraryStats>, AppError> {
+    let stats = state.db.run("stats", |store| store.stats()).await?;
+    Ok(Json(stats))
+}
+
+async fn run_all_maintenance(
+    State(state): State<AppState<SqliteStore>>,
+) -> Result<Json<MaintenanceReport>, AppError> {
+    let report = state
+        .db
+        .run("maintenance", |store| {
+            store.run_maintenance(&MaintenanceTask::ALL)
+        })
+        .await?;
+    Ok(Json(report))
+}
+
+async fn run_maintenance_task(
+    State(state): State<AppState<SqliteStore>>,
+    Path(task): Path<MaintenanceTask>,
+) -> Result<Json<MaintenanceReport>, AppError> {
+    let report = state
+        .db
+        .run(task.as_str(), move |store| store.run_maintenance(&[task]))
+        .await?;
+    Ok(Json(report))
+}
+
+// Registry calls touch the filesystem (and migrate new databases), so they run
+// off the async runtime too.
+async fn blocking<T, F>(f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> lester_core::Result<T> + Send + 'static,
+{
+    let result = tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|err| AppError::Other(format!("profile task failed: {err}")))?;
+    Ok(result?)
+}
+
+async fn list_profiles(
+    State(state): State<ProfileState>,
+) -> Result<Json<Vec<Profile>>, AppError> {
+    let profiles = blocking(move || state.registry.list()).await?;
+    Ok(Json(profiles))
+}
+
+#[derive(Deserialize)]
+struct CreateProfileRequest {
+    name: String,
+}
+
+async fn create_profile(
+    State(state): State<ProfileState>,
+    Json(request): Json<CreateProfileRequest>,
+) -> Result<Json<Profile>, AppError> {
+    let profile = blocking(move || state.registry.create(&request.name)).await?;
+    Ok(Json(profile))
+}
+
+async fn switch_profile(
+    State(state): State<ProfileState>,
+    Path(name): Path<String>,
+) -> Result<Json<Profile>, AppError> {
+    let registry = state.registry.clone();
+    let (profile, store) = blocking(move || {
+        let store = registry.store(&name)?;
+        Ok((registry.switch(&name)?, store))
+    })
+    .await?;
+    state.db.replace_store(store);
+    info!("switched to profile {}", profile.name);
+    Ok(Json(profile))
+}
+
+async fn update_profile_settings(
+    State(state): State<ProfileState>,
+    Path(name): Path<String>,
+    Json(settings): Json<ProfileSettings>,
+) -> Result<Json<Profile>, AppError> {
+    let profile = blocking(move || state.registry.update_settings(&name, settings)).await?;
+    Ok(Json(profile))
+}
+
+async fn list_workspaces<S: Store>(State(state): State<AppState<S>>) -> Result<Json<Vec<lester_core::Workspace>>, AppError> {
+    let workspaces = state
+        .db
+        .run("list_workspaces", |store| store.list_workspaces())
+        .await?;
     Ok(Json(workspaces))
 }
 
-async fn create_workspace(
-    State(state): State<AppState>,
+async fn create_workspace<S: Store>(
+    State(state): State<AppState<S>>,
     Json(input): Json<WorkspaceInput>,
 ) -> Result<Json<lester_core::Workspace>, AppError> {
-    let workspace = state.store.create_workspace(&input.name)?;
+    let workspace = state
+        .db
+        .run("create_workspace", move |store| store.create_workspace(&input.name))
+        .await?;
     Ok(Json(workspace))
 }
 
-async fn list_bookmarks(
-    State(state): State<AppState>,
+async fn list_bookmarks<S: Store>(
+    State(state): State<AppState<S>>,
     Query(query): Query<BookmarkQuery>,
 ) -> Result<Json<Vec<lester_core::Bookmark>>, AppError> {
-    let filter = BookmarkFilter {
-        workspace_id: query.workspace_id,
-        tag: query.tag,
-        query: query.q,
-    };
-    let bookmarks = state.store.list_bookmarks(filter)?;
+    let filter = query.into_filter();
+    let bookmarks = state
+        .db
+        .run("list_bookmarks", move |store| store.list_bookmarks(filter))
+        .await?;
     Ok(Json(bookmarks))
 }
 
-async fn create_bookmark(
-    State(state): State<AppState>,
+async fn create_bookmark<S: Store>(
+    State(state): State<AppState<S>>,
     Json(input): Json<BookmarkInput>,
 ) -> Result<Json<CreateBookmarkResponse>, AppError> {
-    let bookmark = state.store.create_bookmark(input)?;
-    let job = state.store.enqueue_tag_job(bookmark.id)?;
-    Ok(Json(CreateBookmarkResponse { bookmark, job }))
+    let response = state
+        .db
+        .run("create_bookmark", move |store| {
+            let bookmark = store.create_bookmark(input)?;
+            let job = store.enqueue_tag_job(bookmark.id)?;
+            Ok(CreateBookmarkResponse { bookmark, job })
+        })
+        .await?;
+    Ok(Json(response))
+}
+
+async fn update_bookmark<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Json(patch): Json<BookmarkPatch>,
+) -> Result<Json<lester_core::Bookmark>, AppError> {
+    let bookmark = state
+        .db
+        .run("update_bookmark", move |store| store.update_bookmark(id, patch))
+        .await?;
+    Ok(Json(bookmark))
+}
+
+async fn record_visit<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    request: Option<Json<VisitRequest>>,
+) -> Result<Json<lester_core::Bookmark>, AppError> {
+    let progress = request.and_then(|Json(request)| request.progress);
+    let bookmark = state
+        .db
+        .run("record_visit", move |store| store.record_visit(id, progress))
+        .await?;
+    Ok(Json(bookmark))
+}
+
+async fn set_read_state<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ReadStateRequest>,
+) -> Result<Json<lester_core::Bookmark>, AppError> {
+    let bookmark = state
+        .db
+        .run("set_read_state", move |store| {
+            store.set_read_state(id, request.state, request.progress)
+        })
+        .await?;
+    Ok(Json(bookmark))
+}
+
+async fn schedule_review<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ScheduleReviewRequest>,
+) -> Result<Json<lester_core::Bookmark>, AppError> {
+    let bookmark = state
+        .db
+        .run("schedule_review", move |store| store.schedule_review(id, request.due_at))
+        .await?;
+    Ok(Json(bookmark))
+}
+
+async fn record_review<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ReviewRequest>,
+) -> Result<Json<lester_core::Bookmark>, AppError> {
+    let bookmark = state
+        .db
+        .run("record_review", move |store| store.record_review(id, request.outcome))
+        .await?;
+    Ok(Json(bookmark))
+}
+
+async fn list_due_reviews<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<DueReviewQuery>,
+) -> Result<Json<Vec<lester_core::Bookmark>>, AppError> {
+    let limit = query.limit.unwrap_or(50);
+    let tz_offset_minutes = query.tz_offset_minutes.unwrap_or(0);
+    let bookmarks = state
+        .db
+        .run("list_due_reviews", move |store| {
+            store.list_due_reviews(query.workspace_id, tz_offset_minutes, limit)
+        })
+        .await?;
+    Ok(Json(bookmarks))
+}
+
+async fn list_link_checks<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<Vec<LinkCheck>>, AppError> {
+    let limit = query.limit.unwrap_or(20);
+    let checks = state
+        .db
+        .run("list_link_checks", move |store| store.list_link_checks(id, limit))
+        .await?;
+    Ok(Json(checks))
+}
+
+async fn enqueue_link_checks<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<BookmarkQuery>,
+) -> Result<Json<Vec<TagJob>>, AppError> {
+    let filter = query.into_filter();
+    let jobs = state
+        .db
+        .run("enqueue_link_checks", move |store| {
+            store
+                .list_bookmarks(filter)?
+                .into_iter()
+                .map(|bookmark| store.enqueue_job(bookmark.id, JobKind::LinkCheck))
+                .collect()
+        })
+        .await?;
+    Ok(Json(jobs))
+}
+
+async fn list_saved_searches<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Json<Vec<SavedSearch>>, AppError> {
+    let searches = state
+        .db
+        .run("list_saved_searches", move |store| store.list_saved_searches(query.workspace_id))
+        .await?;
+    Ok(Json(searches))
+}
+
+async fn create_saved_search<S: Store>(
+    State(state): State<AppState<S>>,
+    Json(input): Json<SavedSearchInput>,
+) -> Result<Json<SavedSearch>, AppError> {
+    let search = state
+        .db
+        .run("create_saved_search", move |store| store.create_saved_search(input))
+        .await?;
+    Ok(Json(search))
+}
+
+async fn delete_saved_search<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state
+        .db
+        .run("delete_saved_search", move |store| store.delete_saved_search(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn run_saved_search<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SavedSearchResults>, AppError> {
+    let results = state
+        .db
+        .run("run_saved_search", move |store| lester_core::run_saved_search(store, id))
+        .await?;
+    Ok(Json(results))
+}
+
+#[derive(Deserialize)]
+struct LinkBookmarkRequest {
+    target_id: Uuid,
+    kind: RelationKind,
+    notes: Option<String>,
+}
+
+async fn link_bookmarks<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<LinkBookmarkRequest>,
+) -> Result<Json<BookmarkLink>, AppError> {
+    let input = BookmarkLinkInput {
+        source_id: id,
+        target_id: request.target_id,
+        kind: request.kind,
+        notes: request.notes,
+    };
+    let link = state
+        .db
+        .run("link_bookmarks", move |store| store.link_bookmarks(input))
+        .await?;
+    Ok(Json(link))
+}
+
+async fn list_neighbours<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Neighbour>>, AppError> {
+    let neighbours = state
+        .db
+        .run("list_neighbours", move |store| lester_core::list_neighbours(store, id))
+        .await?;
+    Ok(Json(neighbours))
+}
+
+async fn delete_bookmark_link<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state
+        .db
+        .run("delete_bookmark_link", move |store| store.delete_bookmark_link(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_tab_sets<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Json<Vec<TabSet>>, AppError> {
+    let tab_sets = state
+        .db
+        .run("list_tab_sets", move |store| store.list_tab_sets(query.workspace_id))
+        .await?;
+    Ok(Json(tab_sets))
 }
 
-async fn list_tags(
-    State(state): State<AppState>,
+async fn save_tab_set<S: Store>(
+    State(state): State<AppState<S>>,
+    Json(input): Json<TabSetInput>,
+) -> Result<Json<TabSet>, AppError> {
+    let tab_set = state
+        .db
+        .run("save_tab_set", move |store| store.save_tab_set(input))
+        .await?;
+    Ok(Json(tab_set))
+}
+
+async fn get_tab_set<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TabSet>, AppError> {
+    let tab_set = state
+        .db
+        .run("get_tab_set", move |store| {
+            store.get_tab_set(id)?.ok_or(lester_core::CoreError::NotFound)
+        })
+        .await?;
+    Ok(Json(tab_set))
+}
+
+#[derive(Deserialize)]
+struct ReplaceTabsRequest {
+    tabs: Vec<TabInput>,
+}
+
+async fn replace_tabs<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ReplaceTabsRequest>,
+) -> Result<Json<TabSet>, AppError> {
+    let tab_set = state
+        .db
+        .run("replace_tabs", move |store| store.replace_tabs(id, request.tabs))
+        .await?;
+    Ok(Json(tab_set))
+}
+
+async fn delete_tab_set<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state
+        .db
+        .run("delete_tab_set", move |store| store.delete_tab_set(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn promote_tab<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PromotedTab>, AppError> {
+    let promoted = state
+        .db
+        .run("promote_tab", move |store| lester_core::promote_tab(store, id))
+        .await?;
+    Ok(Json(promoted))
+}
+
+async fn list_tag_rules<S: Store>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<Vec<TagRule>>, AppError> {
+    let rules = state
+        .db
+        .run("list_tag_rules", |store| store.list_tag_rules())
+        .await?;
+    Ok(Json(rules))
+}
+
+async fn create_tag_rule<S: Store>(
+    State(state): State<AppState<S>>,
+    Json(input): Json<TagRuleInput>,
+) -> Result<Json<TagRule>, AppError> {
+    let rule = state
+        .db
+        .run("create_tag_rule", move |store| store.create_tag_rule(input))
+        .await?;
+    Ok(Json(rule))
+}
+
+async fn update_tag_rule<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+    Json(input): Json<TagRuleInput>,
+) -> Result<Json<TagRule>, AppError> {
+    let rule = state
+        .db
+        .run("update_tag_rule", move |store| store.update_tag_rule(id, input))
+        .await?;
+    Ok(Json(rule))
+}
+
+async fn delete_tag_rule<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state
+        .db
+        .run("delete_tag_rule", move |store| store.delete_tag_rule(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_bookmark_tags<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<BookmarkTagDetail>>, AppError> {
+    let tags = state
+        .db
+        .run("list_bookmark_tags", move |store| store.list_tags_for_bookmark(id))
+        .await?;
+    Ok(Json(tags))
+}
+
+async fn list_tags<S: Store>(
+    State(state): State<AppState<S>>,
 ) -> Result<Json<Vec<lester_core::Tag>>, AppError> {
-    let tags = state.store.list_tags()?;
+    let tags = state.db.run("list_tags", |store| store.list_tags()).await?;
     Ok(Json(tags))
 }
 
-async fn tag_cloud(
-    State(state): State<AppState>,
+async fn tag_cloud<S: Store>(
+    State(state): State<AppState<S>>,
     Query(query): Query<TagCloudQuery>,
 ) -> Result<Json<Vec<TagCloudEntry>>, AppError> {
     let limit = query.limit.unwrap_or(40);
-    let cloud = state.store.get_tag_cloud(limit)?;
+    let cloud = state
+        .db
+        .run("tag_cloud", move |store| store.get_tag_cloud(limit))
+        .await?;
     Ok(Json(cloud))
 }
 
+async fn import_bookmarks<S: Store>(
+    State(state): State<AppState<S>>,
+    Path(format): Path<ImportFormat>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Json<ImportReport>, AppError> {
+    let options = ImportOptions {
+        workspace_id: query.workspace_id,
+        folder_mapping: query.folders.unwrap_or_default(),
+        dry_run: query.dry_run.unwrap_or(false),
+    };
+    let report = state
+        .db
+        .run("import_bookmarks", move |store| {
+            let items = format.parse(&body)?;
+            lester_core::import_bookmarks(store, items, &options)
+        })
+        .await?;
+    Ok(Json(report))
+}
+
+async fn import_history<S: Store>(
+    State(state): State<AppState<S>>,
+    Json(request): Json<HistoryImportRequest>,
+) -> Result<Json<ImportReport>, AppError> {
+    let filter = HistoryFilter {
+        since: request.since,
+        until: request.until,
+        domain: request.domain,
+        min_visits: request.min_visits,
+    };
+    let options = HistoryImportOptions {
+        workspace_id: request.workspace_id,
+        target: request.target.unwrap_or_default(),
+        dry_run: request.dry_run.unwrap_or(false),
+    };
+    let report = state
+        .db
+        .run("import_history", move |store| {
+            let visits =
+                lester_core::read_browser_history(&request.path, request.browser, &filter)?;
+            lester_core::import_history(store, visits, &options)
+        })
+        .await?;
+    Ok(Json(report))
+}
+
+async fn list_history<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<HistoryEntry>>, AppError> {
+    let limit = query.limit.unwrap_or(200);
+    let entries = state
+        .db
+        .run("list_history", move |store| {
+            store.list_history(query.workspace_id, limit)
+        })
+        .await?;
+    Ok(Json(entries))
+}
+
+async fn export_netscape<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    let html = state
+        .db
+        .run("export_netscape", move |store| {
+            lester_core::export_netscape(store, query.workspace_id)
+        })
+        .await?;
+    Ok(([(header::CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+async fn export_markdown<S: Store>(
+    State(state): State<AppState<S>>,
+    Json(request): Json<MarkdownExportRequest>,
+) -> Result<Json<VaultReport>, AppError> {
+    let report = state
+        .db
+        .run("export_markdown", move |store| {
+            lester_core::export_markdown_vault(
+                store,
+                request.workspace_id,
+                std::path::Path::new(&request.path),
+            )
+        })
+        .await?;
+    Ok(Json(report))
+}
+
+async fn export_bibtex<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<BookmarkQuery>,
+) -> Result<Response, AppError> {
+    let filter = query.into_filter();
+    let bookmarks = state
+        .db
+        .run("export_bibtex", move |store| store.list_bookmarks(filter))
+        .await?;
+    let bibtex = lester_core::export_bibtex(&bookmarks);
+    let content_type = [(header::CONTENT_TYPE, "application/x-bibtex; charset=utf-8")];
+    Ok((content_type, bibtex).into_response())
+}
+
+async fn export_csl_json<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<BookmarkQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let filter = query.into_filter();
+    let bookmarks = state
+        .db
+        .run("export_csl_json", move |store| store.list_bookmarks(filter))
+        .await?;
+    Ok(Json(lester_core::export_csl_json(&bookmarks)))
+}
+
+#[derive(Deserialize)]
+struct GraphQuery {
+    workspace_id: Uuid,
+}
+
+async fn export_graph<S: Store>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<GraphQuery>,
+) -> Result<Json<BookmarkGraph>, AppError> {
+    let graph = state
+        .db
+        .run("export_graph", move |store| {
+            lester_core::export_graph(store, query.workspace_id)
+        })
+        .await?;
+    Ok(Json(graph))
+}
+
 #[derive(Serialize)]
 struct HealthResponse {
     status: &'static str,
@@ -109,6 +839,100 @@ struct BookmarkQuery {
     workspace_id: Option<Uuid>,
     tag: Option<String>,
     q: Option<String>,
+    language: Option<String>,
+    site_name: Option<String>,
+    author: Option<String>,
+    max_reading_time: Option<i64>,
+    read_state: Option<ReadState>,
+    link_status: Option<LinkStatus>,
+    sort: Option<BookmarkSort>,
+}
+
+impl BookmarkQuery {
+    fn into_filter(self) -> BookmarkFilter {
+        BookmarkFilter {
+            workspace_id: self.workspace_id,
+            tag: self.tag,
+            query: self.q,
+            language: self.language,
+            site_name: self.site_name,
+            author: self.author,
+            max_reading_time: self.max_reading_time,
+            read_state: self.read_state,
+            link_status: self.link_status,
+            sort: self.sort.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct VisitRequest {
+    progress: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct ReadStateRequest {
+    state: ReadState,
+    progress: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct ScheduleReviewRequest {
+    due_at: i64,
+}
+
+#[derive(Deserialize)]
+struct ReviewRequest {
+    outcome: ReviewOutcome,
+}
+
+#[derive(Deserialize)]
+struct DueReviewQuery {
+    workspace_id: Option<Uuid>,
+    tz_offset_minutes: Option<i32>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct LimitQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct ImportQuery {
+    workspace_id: Uuid,
+    folders: Option<FolderMapping>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct HistoryImportRequest {
+    path: String,
+    browser: Option<Browser>,
+    workspace_id: Uuid,
+    target: Option<HistoryTarget>,
+    since: Option<i64>,
+    until: Option<i64>,
+    domain: Option<String>,
+    min_visits: Option<i64>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+    workspace_id: Option<Uuid>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    workspace_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+struct MarkdownExportRequest {
+    workspace_id: Uuid,
+    path: String,
 }
 
 #[derive(Deserialize)]
@@ -125,6 +949,7 @@ struct CreateBookmarkResponse {
 #[derive(Debug)]
 enum AppError {
     Core(lester_core::CoreError),
+    Busy,
     Other(String),
 }
 
@@ -140,6 +965,10 @@ impl IntoResponse for AppError {
             AppError::Core(lester_core::CoreError::InvalidInput(msg)) => (StatusCode::BAD_REQUEST, msg),
             AppError::Core(lester_core::CoreError::NotFound) => (StatusCode::NOT_FOUND, "not found".to_string()),
             AppError::Core(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+            AppError::Busy => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "storage is busy, retry later".to_string(),
+            ),
             AppError::Other(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
 
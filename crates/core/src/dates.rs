@@ -0,0 +1,113 @@
+pub(crate) fn parse_rfc3339(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (date, rest) = (value.get(..10)?, value.get(10..)?);
+    let mut parts = date.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(0..=9_999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut ms = days_from_civil(year, month, day) * 86_400_000;
+
+    let rest = rest.trim_start_matches(['T', 't', ' ']);
+    if rest.is_empty() {
+        return Some(ms);
+    }
+    let offset_at = rest.find(['Z', 'z', '+', '-']).unwrap_or(rest.len());
+    let (time, offset) = rest.split_at(offset_at);
+    let mut time_parts = time.split(':');
+    let hour: i64 = time_parts.next()?.parse().ok()?;
+    let minute: i64 = time_parts.next().unwrap_or("0").parse().ok()?;
+    let second: f64 = time_parts.next().unwrap_or("0").parse().ok()?;
+    if !(0..=23).contains(&hour)
+        || !(0..=59).contains(&minute)
+        || !(0.0..61.0).contains(&second)
+    {
+        return None;
+    }
+    ms += (hour * 3_600 + minute * 60) * 1_000 + (second * 1_000.0).round() as i64;
+
+    if let Some(sign) = offset.chars().next().filter(|c| *c == '+' || *c == '-') {
+        let digits = offset[1..].replace(':', "");
+        let hours: i64 = digits.get(..2)?.parse().ok()?;
+        let minutes: i64 = digits.get(2..4).unwrap_or("0").parse().ok()?;
+        if !(0..=23).contains(&hours) || !(0..=59).contains(&minutes) {
+            return None;
+        }
+        let delta = (hours * 3_600 + minutes * 60) * 1_000;
+        ms += if sign == '+' { -delta } else { delta };
+    }
+    Some(ms)
+}
+
+pub(crate) fn format_rfc3339(ms: i64) -> String {
+    let ts = ms.div_euclid(1_000);
+    let (year, month, day) = civil_from_days(ts.div_euclid(86_400));
+    let secs = ts.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        (secs % 3_600) / 60,
+        secs % 60
+    )
+}
+
+pub(crate) fn date_parts(ms: i64) -> (i64, u32, u32) {
+    civil_from_days(ms.div_euclid(86_400_000))
+}
+
+// Howard Hinnant's days_from_civil / civil_from_days algorithms.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_rfc3339() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_rfc3339("2023-11-14T22:13:20Z"), Some(1_700_000_000_000));
+        assert_eq!(
+            parse_rfc3339("2023-11-14T23:13:20.123+01:00"),
+            Some(1_700_000_000_123)
+        );
+        assert_eq!(parse_rfc3339("2023-11-14"), Some(1_699_920_000_000));
+        assert_eq!(parse_rfc3339("yesterday"), None);
+        assert_eq!(parse_rfc3339("2023-11-1é12:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-11-14Té"), None);
+        assert_eq!(parse_rfc3339("2023-11-14T99999999999999:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-11-14T24:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-11-14T12:60:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-11-14T12:00:61Z"), None);
+        assert_eq!(parse_rfc3339("2023-11-14T12:00:infZ"), None);
+        assert_eq!(parse_rfc3339("2023-11-14T12:00:00+99:00"), None);
+        assert_eq!(format_rfc3339(1_700_000_000_999), "2023-11-14T22:13:20Z");
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(date_parts(1_700_000_000_000), (2023, 11, 14));
+    }
+}
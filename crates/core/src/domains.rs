@@ -0,0 +1,154 @@
+use std::sync::OnceLock;
+
+use publicsuffix::{List, Psl};
+use url::{Host, Url};
+
+// Snapshot of https://publicsuffix.org/list/public_suffix_list.dat; refresh
+// it by replacing the file.
+const PUBLIC_SUFFIX_LIST: &str = include_str!("../data/public_suffix_list.dat");
+
+static SUFFIXES: OnceLock<List> = OnceLock::new();
+
+fn suffixes() -> &'static List {
+    SUFFIXES.get_or_init(|| {
+        PUBLIC_SUFFIX_LIST
+            .parse()
+            .expect("bundled public suffix list parses")
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlHost {
+    // Lower-case and in Unicode form, without port, userinfo or a leading
+    // `www.`.
+    pub host: String,
+    // The public suffix plus one label (`bbc.co.uk` for `news.bbc.co.uk`).
+    // `None` for IP addresses and hosts without a known public suffix.
+    pub registrable_domain: Option<String>,
+}
+
+// Web URLs use their host, `mailto:` the domain of the (last) address. Other
+// schemes such as `file:` have no host worth tagging. Bare `example.com/path`
+// input is read as http.
+pub fn parse_host(input: &str) -> Option<UrlHost> {
+    let input = input.trim();
+    let url = match Url::parse(input) {
+        Ok(url) => url,
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            Url::parse(&format!("http://{}", input)).ok()?
+        }
+        Err(_) => return None,
+    };
+    let host = match url.scheme() {
+        "http" | "https" | "ftp" | "ws" | "wss" => url.host()?.to_owned(),
+        "mailto" => {
+            let address = url.path().split(',').next_back()?;
+            let (_, domain) = address.rsplit_once('@')?;
+            let domain = percent_decode(domain);
+            Host::parse(&domain).ok()?
+        }
+        _ => return None,
+    };
+    match host {
+        Host::Domain(ascii) => {
+            let ascii = ascii.trim_end_matches('.').to_ascii_lowercase();
+            let ascii = match ascii.strip_prefix("www.") {
+                Some(rest) if rest.contains('.') => rest.to_string(),
+                _ => ascii,
+            };
+            if ascii.is_empty() {
+                return None;
+            }
+            let registrable_domain = suffixes()
+                .domain(ascii.as_bytes())
+                .filter(|domain| domain.suffix().is_known())
+                .and_then(|domain| std::str::from_utf8(domain.as_bytes()).ok())
+                .map(to_unicode);
+            Some(UrlHost {
+                host: to_unicode(&ascii),
+                registrable_domain,
+            })
+        }
+        Host::Ipv4(ip) => Some(UrlHost {
+            host: ip.to_string(),
+            registrable_domain: None,
+        }),
+        Host::Ipv6(ip) => Some(UrlHost {
+            host: ip.to_string(),
+            registrable_domain: None,
+        }),
+    }
+}
+
+// Normalizes a user-supplied domain (Unicode or punycode) to the form
+// `parse_host` produces.
+pub(crate) fn normalize_domain(domain: &str) -> Option<String> {
+    let domain = domain.trim().trim_matches('.');
+    let domain = domain.strip_prefix("www.").unwrap_or(domain);
+    match Host::parse(domain).ok()? {
+        Host::Domain(ascii) => Some(to_unicode(&ascii.to_ascii_lowercase())),
+        host => Some(host.to_string()),
+    }
+}
+
+fn to_unicode(ascii: &str) -> String {
+    let (unicode, result) = idna::domain_to_unicode(ascii);
+    if result.is_ok() {
+        unicode
+    } else {
+        ascii.to_string()
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    url::form_urlencoded::parse(format!("d={}", value).as_bytes())
+        .next()
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_else(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(url: &str) -> Option<(String, Option<String>)> {
+        parse_host(url).map(|host| (host.host, host.registrable_domain))
+    }
+
+    #[test]
+    fn parses_hosts_and_registrable_domains() {
+        let pair = |host: &str, domain: Option<&str>| {
+            Some((host.to_string(), domain.map(str::to_string)))
+        };
+        assert_eq!(
+            hosts("https://news.ycombinator.com/item?id=1"),
+            pair("news.ycombinator.com", Some("ycombinator.com"))
+        );
+        assert_eq!(
+            hosts("https://user:pw@www.BBC.co.uk:8443/news"),
+            pair("bbc.co.uk", Some("bbc.co.uk"))
+        );
+        assert_eq!(hosts("http://foo.github.io/x"), pair("foo.github.io", Some("foo.github.io")));
+        assert_eq!(
+            hosts("https://xn--bcher-kva.example.de/"),
+            pair("bücher.example.de", Some("example.de"))
+        );
+        assert_eq!(hosts("https://münchen.de"), pair("münchen.de", Some("münchen.de")));
+        assert_eq!(
+            hosts("http://shop.example.公司.cn/"),
+            pair("shop.example.公司.cn", Some("example.公司.cn"))
+        );
+        assert_eq!(
+            hosts("mailto:ada@Mail.Example.org?subject=hi"),
+            pair("mail.example.org", Some("example.org"))
+        );
+        assert_eq!(hosts("example.com/path"), pair("example.com", Some("example.com")));
+        assert_eq!(hosts("http://127.0.0.1:8080/"), pair("127.0.0.1", None));
+        assert_eq!(hosts("http://intranet/wiki"), pair("intranet", None));
+        assert_eq!(hosts("file:///home/ada/notes.html"), None);
+        assert_eq!(hosts("about:blank"), None);
+        assert_eq!(hosts(""), None);
+
+        assert_eq!(normalize_domain("www.XN--Mnchen-3ya.de").as_deref(), Some("münchen.de"));
+    }
+}
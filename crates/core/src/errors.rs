@@ -6,6 +6,10 @@ pub enum CoreError {
     Database(#[from] rusqlite::Error),
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("csv error: {0}")]
+    Csv(#[from] csv::Error),
     #[error("not found")]
     NotFound,
     #[error("invalid input: {0}")]
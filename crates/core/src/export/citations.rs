@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::dates::date_parts;
+use crate::models::{Bookmark, Citation};
+
+// Words in a venue name that mark a conference or a journal. Anything else
+// with a venue and no DOI is taken for a website or blog.
+const CONFERENCE_WORDS: &[&str] = &[
+    "aaai", "acl", "chi", "conference", "congress", "cvpr", "eccv", "emnlp", "iccv", "iclr",
+    "icml", "icse", "ijcai", "kdd", "naacl", "neurips", "nips", "nsdi", "osdi", "pldi", "popl",
+    "proceedings", "sigir", "sigmod", "sosp", "symposium", "usenix", "vldb", "workshop",
+];
+const JOURNAL_WORDS: &[&str] = &[
+    "annals", "bulletin", "journal", "letters", "transactions",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    Article,
+    Conference,
+    Webpage,
+}
+
+impl EntryKind {
+    fn of(citation: &Citation) -> Self {
+        let Some(venue) = citation.venue.as_deref() else {
+            return EntryKind::Webpage;
+        };
+        let venue = venue.to_lowercase();
+        let has_word = |words: &[&str]| {
+            venue
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|word| words.contains(&word))
+        };
+        if has_word(CONFERENCE_WORDS) {
+            EntryKind::Conference
+        } else if has_word(JOURNAL_WORDS) || citation.doi.is_some() {
+            EntryKind::Article
+        } else {
+            EntryKind::Webpage
+        }
+    }
+
+    // BibTeX entry type and the field the venue goes in.
+    fn bibtex(self) -> (&'static str, &'static str) {
+        match self {
+            EntryKind::Article => ("article", "journal"),
+            EntryKind::Conference => ("inproceedings", "booktitle"),
+            EntryKind::Webpage => ("misc", "howpublished"),
+        }
+    }
+
+    fn csl(self) -> &'static str {
+        match self {
+            EntryKind::Article => "article-journal",
+            EntryKind::Conference => "paper-conference",
+            EntryKind::Webpage => "webpage",
+        }
+    }
+}
+
+pub fn export_bibtex(bookmarks: &[Bookmark]) -> String {
+    let keys = citation_keys(bookmarks);
+    let mut out = String::new();
+    for (bookmark, key) in bookmarks.iter().zip(keys) {
+        let citation = &bookmark.citation;
+        let (entry_type, venue_field) = EntryKind::of(citation).bibtex();
+        let mut fields = vec![("title", format!("{{{}}}", bibtex_escape(&bookmark.title)))];
+        if !citation.authors.is_empty() {
+            let authors = citation
+                .authors
+                .iter()
+                .map(|author| bibtex_escape(author))
+                .collect::<Vec<_>>()
+                .join(" and ");
+            fields.push(("author", format!("{{{}}}", authors)));
+        }
+        if let Some(venue) = &citation.venue {
+            fields.push((venue_field, format!("{{{}}}", bibtex_escape(venue))));
+        }
+        if let Some(year) = citation.year {
+            fields.push(("year", format!("{{{}}}", year)));
+        }
+        if let Some(doi) = &citation.doi {
+            fields.push(("doi", format!("{{{}}}", bibtex_verbatim(doi))));
+        }
+        fields.push(("url", format!("{{{}}}", bibtex_verbatim(&bookmark.url))));
+        let (year, month, day) = date_parts(bookmark.created_at);
+        fields.push((
+            "urldate",
+            format!("{{{:04}-{:02}-{:02}}}", year, month, day),
+        ));
+        if let Some(notes) = bookmark.notes.as_deref().filter(|notes| !notes.is_empty()) {
+            fields.push(("note", format!("{{{}}}", bibtex_escape(notes))));
+        }
+
+        out.push_str(&format!("@{}{{{},\n", entry_type, key));
+        let body = fields
+            .iter()
+            .map(|(name, value)| format!("  {} = {}", name, value))
+            .collect::<Vec<_>>()
+            .join(",\n");
+        out.push_str(&body);
+        out.push_str("\n}\n\n");
+    }
+    out
+}
+
+pub fn export_csl_json(bookmarks: &[Bookmark]) -> Value {
+    let keys = citation_keys(bookmarks);
+    let items = bookmarks
+        .iter()
+        .zip(keys)
+        .map(|(bookmark, key)| {
+            let citation = &bookmark.citation;
+            let (year, month, day) = date_parts(bookmark.created_at);
+            let mut item = json!({
+                "id": key,
+                "type": EntryKind::of(citation).csl(),
+                "title": bookmark.title,
+                "URL": bookmark.url,
+                "accessed": { "date-parts": [[year, month, day]] },
+            });
+            if !citation.authors.is_empty() {
+                item["author"] =
+                    Value::Array(citation.authors.iter().map(|a| csl_name(a)).collect());
+            }
+            if let Some(venue) = &citation.venue {
+                item["container-title"] = json!(venue);
+            }
+            if let Some(year) = citation.year {
+                item["issued"] = json!({ "date-parts": [[year]] });
+            }
+            if let Some(doi) = &citation.doi {
+                item["DOI"] = json!(doi);
+            }
+            if let Some(notes) = bookmark.notes.as_deref().filter(|notes| !notes.is_empty()) {
+                item["note"] = json!(notes);
+            }
+            item
+        })
+        .collect();
+    Value::Array(items)
+}
+
+fn citation_keys(bookmarks: &[Bookmark]) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    bookmarks
+        .iter()
+        .map(|bookmark| {
+            let author = bookmark
+                .citation
+                .authors
+                .first()
+                .map(|author| split_name(author).0)
+                .unwrap_or_default();
+            let word = bookmark
+                .title
+                .split_whitespace()
+                .map(|word| {
+                    word.chars()
+                        .filter(|c| c.is_alphanumeric())
+                        .collect::<String>()
+                })
+                .find(|word| word.chars().count() > 3)
+                .unwrap_or_default();
+            let year = bookmark
+                .citation
+                .year
+                .map(|year| year.to_string())
+                .unwrap_or_default();
+            let mut base: String = format!("{}{}{}", author, year, word)
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+                .to_lowercase();
+            if base.is_empty() {
+                base = format!("ref{}", &bookmark.id.simple().to_string()[..8]);
+            }
+            let count = counts.entry(base.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                base
+            } else {
+                format!("{}{}", base, suffix(*count - 1))
+            }
+        })
+        .collect()
+}
+
+fn suffix(index: usize) -> String {
+    let mut index = index;
+    let mut out = String::new();
+    loop {
+        out.insert(0, (b'a' + ((index - 1) % 26) as u8) as char);
+        index = (index - 1) / 26;
+        if index == 0 {
+            break;
+        }
+    }
+    out
+}
+
+fn split_name(name: &str) -> (String, String) {
+    let name = name.trim();
+    if let Some((family, given)) = name.split_once(',') {
+        return (family.trim().to_string(), given.trim().to_string());
+    }
+    match name.rsplit_once(' ') {
+        Some((given, family)) => (family.trim().to_string(), given.trim().to_string()),
+        None => (name.to_string(), String::new()),
+    }
+}
+
+fn csl_name(name: &str) -> Value {
+    let (family, given) = split_name(name);
+    if given.is_empty() {
+        json!({ "literal": family })
+    } else {
+        json!({ "family": family, "given": given })
+    }
+}
+
+fn bibtex_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '\\' => out.push_str("\\textbackslash{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// DOIs and URLs are read verbatim, so backslash escapes would end up in the
+// link; percent-encoding keeps the braces balanced and the link valid.
+fn bibtex_verbatim(value: &str) -> String {
+    value.replace('{', "%7B").replace('}', "%7D")
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::{BookmarkMetadata, Citation, Reading};
+
+    fn bookmark(title: &str, citation: Citation) -> Bookmark {
+        Bookmark {
+            id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            url: "https://arxiv.org/abs/1706.03762".to_string(),
+            title: title.to_string(),
+            notes: None,
+            created_at: 1_700_000_000_000,
+            updated_at: 1_700_000_000_000,
+            added_at: 1_700_000_000_000,
+            citation,
+            metadata: BookmarkMetadata::default(),
+            reading: Reading::default(),
+            review: None,
+            link_status: None,
+        }
+    }
+
+    #[test]
+    fn exports_bibtex_and_csl_json() {
+        let citation = Citation {
+            authors: vec!["Vaswani, Ashish".to_string(), "Noam Shazeer".to_string()],
+            venue: Some("NeurIPS".to_string()),
+            doi: Some("10.5555/3295222".to_string()),
+            year: Some(2017),
+        };
+        let mut bookmarks = vec![
+            bookmark("Attention Is All You Need", citation.clone()),
+            bookmark("Attention Is All You Need (v2)", citation),
+            bookmark("R&D notes_1", Citation::default()),
+            bookmark(
+                "Deep Residual Learning",
+                Citation {
+                    venue: Some("Journal of Machine Learning Research".to_string()),
+                    doi: Some("10.1000/a{b".to_string()),
+                    ..Citation::default()
+                },
+            ),
+            bookmark(
+                "Rust 1.75",
+                Citation {
+                    venue: Some("The Rust Blog".to_string()),
+                    ..Citation::default()
+                },
+            ),
+        ];
+        bookmarks[3].url = "https://example.com/}".to_string();
+
+        let bibtex = export_bibtex(&bookmarks);
+        assert!(bibtex.contains("@inproceedings{vaswani2017attention,\n"));
+        assert!(bibtex.contains("@inproceedings{vaswani2017attentiona,\n"));
+        assert!(bibtex.contains("  booktitle = {NeurIPS},\n"));
+        assert!(bibtex.contains("@article{deep,\n"));
+        assert!(bibtex.contains("  doi = {10.1000/a%7Bb},\n"));
+        assert!(bibtex.contains("  url = {https://example.com/%7D},\n"));
+        assert!(bibtex.contains("@misc{rust,\n"));
+        assert!(bibtex.contains("  howpublished = {The Rust Blog},\n"));
+        assert!(bibtex.contains("  author = {Vaswani, Ashish and Noam Shazeer},\n"));
+        assert!(bibtex.contains("  urldate = {2023-11-14}"));
+        assert!(bibtex.contains("@misc{notes"));
+        assert!(bibtex.contains("title = {R\\&D notes\\_1}"));
+
+        let csl = export_csl_json(&bookmarks);
+        assert_eq!(csl[0]["type"], "paper-conference");
+        assert_eq!(csl[3]["type"], "article-journal");
+        assert_eq!(csl[4]["type"], "webpage");
+        assert_eq!(csl[0]["author"][0]["family"], "Vaswani");
+        assert_eq!(csl[0]["author"][1]["given"], "Noam");
+        assert_eq!(csl[0]["issued"]["date-parts"][0][0], 2017);
+        assert_eq!(csl[0]["DOI"], "10.5555/3295222");
+        assert_eq!(csl[2]["type"], "webpage");
+        assert!(csl[2].get("author").is_none());
+    }
+}
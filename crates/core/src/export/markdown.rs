@@ -0,0 +1,311 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::dates::format_rfc3339;
+use crate::errors::{CoreError, Result};
+use crate::models::{Bookmark, BookmarkFilter, BookmarkTagDetail};
+use crate::store::Store;
+
+const ID_KEY: &str = "lester_id";
+const WORKSPACE_KEY: &str = "lester_workspace";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VaultReport {
+    pub written: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+pub fn export_markdown_vault<S: Store>(
+    store: &S,
+    workspace_id: Uuid,
+    dir: &Path,
+) -> Result<VaultReport> {
+    let workspace = store
+        .list_workspaces()?
+        .into_iter()
+        .find(|workspace| workspace.id == workspace_id)
+        .ok_or(CoreError::NotFound)?;
+    let mut bookmarks = store.list_bookmarks(BookmarkFilter {
+        workspace_id: Some(workspace_id),
+        ..BookmarkFilter::default()
+    })?;
+    bookmarks.sort_by_key(|bookmark| (bookmark.created_at, bookmark.id));
+
+    fs::create_dir_all(dir)?;
+    let mut report = VaultReport::default();
+    let mut keep = HashSet::new();
+    let index_note = index_note_name(workspace_id);
+    let mut index = format!("# {}\n\n", workspace.name);
+
+    for bookmark in &bookmarks {
+        let file_name = note_file_name(bookmark);
+        let tags = store.list_tags_for_bookmark(bookmark.id)?;
+        write_if_changed(
+            &dir.join(&file_name),
+            &render_note(bookmark, workspace_id, tags),
+            &mut report,
+        )?;
+        index.push_str(&format!(
+            "- [[{}|{}]]\n",
+            file_name.trim_end_matches(".md"),
+            bookmark.title.replace(['|', '[', ']'], " ")
+        ));
+        keep.insert(file_name);
+    }
+    write_if_changed(&dir.join(&index_note), &index, &mut report)?;
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !name.ends_with(".md") || name == index_note || keep.contains(name) {
+            continue;
+        }
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        if belongs_to_workspace(&content, workspace_id) {
+            fs::remove_file(&path)?;
+            report.removed += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+// Only notes this exporter wrote for `workspace_id` are eligible for removal,
+// so several workspaces (and the user's own notes) can share one vault.
+fn belongs_to_workspace(content: &str, workspace_id: Uuid) -> bool {
+    let Some(front_matter) = content.strip_prefix(&format!("---\n{}: ", ID_KEY)) else {
+        return false;
+    };
+    let marker = format!("{}: {}", WORKSPACE_KEY, workspace_id);
+    front_matter
+        .lines()
+        .take_while(|line| *line != "---")
+        .any(|line| line == marker)
+}
+
+fn index_note_name(workspace_id: Uuid) -> String {
+    format!("index-{}.md", &workspace_id.simple().to_string()[..8])
+}
+
+fn render_note(
+    bookmark: &Bookmark,
+    workspace_id: Uuid,
+    mut tags: Vec<BookmarkTagDetail>,
+) -> String {
+    tags.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut out = String::from("---\n");
+    out.push_str(&format!("{}: {}\n", ID_KEY, bookmark.id));
+    out.push_str(&format!("{}: {}\n", WORKSPACE_KEY, workspace_id));
+    out.push_str(&format!("url: {}\n", yaml_string(&bookmark.url)));
+    out.push_str(&format!("title: {}\n", yaml_string(&bookmark.title)));
+    if tags.is_empty() {
+        out.push_str("tags: []\nconfidence: {}\n");
+    } else {
+        out.push_str("tags:\n");
+        for tag in &tags {
+            out.push_str(&format!("  - {}\n", yaml_string(&tag.name)));
+        }
+        out.push_str("confidence:\n");
+        for tag in &tags {
+            out.push_str(&format!(
+                "  {}: {:.2}\n",
+                yaml_string(&tag.name),
+                tag.confidence
+            ));
+        }
+    }
+    out.push_str(&format!(
+        "created: {}\n",
+        format_rfc3339(bookmark.created_at)
+    ));
+    out.push_str(&format!(
+        "updated: {}\n",
+        format_rfc3339(bookmark.updated_at)
+    ));
+    if let Some(review) = &bookmark.review {
+        out.push_str(&format!("review_due: {}\n", format_rfc3339(review.due_at)));
+        out.push_str(&format!("review_interval_days: {}\n", review.interval_days));
+    }
+    out.push_str("---\n\n");
+    out.push_str(&format!("# {}\n\n<{}>\n", bookmark.title, bookmark.url));
+    if let Some(notes) = bookmark
+        .notes
+        .as_deref()
+        .filter(|notes| !notes.trim().is_empty())
+    {
+        out.push_str(&format!("\n{}\n", notes.trim_end()));
+    }
+    out
+}
+
+fn note_file_name(bookmark: &Bookmark) -> String {
+    let mut slug = String::new();
+    for c in bookmark.title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= 60 {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    let short_id = &bookmark.id.simple().to_string()[..8];
+    if slug.is_empty() {
+        format!("{}.md", short_id)
+    } else {
+        format!("{}-{}.md", slug, short_id)
+    }
+}
+
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn write_if_changed(path: &Path, content: &str, report: &mut VaultReport) -> Result<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        report.unchanged += 1;
+        return Ok(());
+    }
+    fs::write(path, content)?;
+    report.written += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+    use crate::models::{BookmarkInput, BookmarkMetadata, BookmarkPatch, TagSource, TagSuggestion};
+
+    #[test]
+    fn exports_incrementally() {
+        let vault = tempfile::tempdir().unwrap();
+        let dir = vault.path();
+        let store = MemoryStore::new();
+        let workspace = store.create_workspace("Research").unwrap();
+        let first = store
+            .create_bookmark(BookmarkInput {
+                workspace_id: workspace.id,
+                url: "https://example.com/a".to_string(),
+                title: "Attention: Is All You Need?".to_string(),
+                notes: Some("Key paper.".to_string()),
+                metadata: BookmarkMetadata::default(),
+            })
+            .unwrap();
+        store
+            .upsert_tags_for_bookmark(
+                first.id,
+                &[TagSuggestion {
+                    name: "ml".to_string(),
+                    confidence: 0.8,
+                    source: TagSource::Rules,
+                }],
+            )
+            .unwrap();
+        let second = store
+            .create_bookmark(BookmarkInput {
+                workspace_id: workspace.id,
+                url: "https://example.com/b".to_string(),
+                title: "Second".to_string(),
+                notes: None,
+                metadata: BookmarkMetadata::default(),
+            })
+            .unwrap();
+        store.schedule_review(first.id, 1_700_000_000_000).unwrap();
+
+        let report = export_markdown_vault(&store, workspace.id, dir).unwrap();
+        assert_eq!(report.written, 3);
+
+        let note = fs::read_to_string(dir.join(note_file_name(&first))).unwrap();
+        assert!(note.starts_with(&format!(
+            "---\nlester_id: {}\nlester_workspace: {}\n",
+            first.id, workspace.id
+        )));
+        assert!(note.contains("url: \"https://example.com/a\"\n"));
+        assert!(note.contains("tags:\n  - \"ml\"\n"));
+        assert!(note.contains("confidence:\n  \"ml\": 0.80\n"));
+        assert!(note.contains("review_due: 2023-11-14T22:13:20Z\nreview_interval_days: 0\n"));
+        assert!(note.ends_with("Key paper.\n"));
+        let index = fs::read_to_string(dir.join(index_note_name(workspace.id))).unwrap();
+        assert!(index.contains("|Attention: Is All You Need?]]"));
+
+        let report = export_markdown_vault(&store, workspace.id, dir).unwrap();
+        assert_eq!(report.written, 0);
+        assert_eq!(report.unchanged, 3);
+
+        fs::write(dir.join("my-own-note.md"), "# Mine\n").unwrap();
+        store
+            .update_bookmark(
+                second.id,
+                BookmarkPatch {
+                    title: Some("Second, renamed".to_string()),
+                    ..BookmarkPatch::default()
+                },
+            )
+            .unwrap();
+        let report = export_markdown_vault(&store, workspace.id, dir).unwrap();
+        assert_eq!(report.removed, 1);
+        assert_eq!(report.written, 2);
+        assert!(dir.join("my-own-note.md").exists());
+    }
+
+    #[test]
+    fn workspaces_share_a_vault() {
+        let vault = tempfile::tempdir().unwrap();
+        let dir = vault.path();
+        let store = MemoryStore::new();
+        let research = store.create_workspace("Research").unwrap();
+        let cooking = store.create_workspace("Cooking").unwrap();
+        let mut ids = Vec::new();
+        for (workspace, title) in [(&research, "Paper"), (&cooking, "Recipe")] {
+            ids.push(
+                store
+                    .create_bookmark(BookmarkInput {
+                        workspace_id: workspace.id,
+                        url: format!("https://example.com/{}", title),
+                        title: title.to_string(),
+                        notes: None,
+                        metadata: BookmarkMetadata::default(),
+                    })
+                    .unwrap(),
+            );
+        }
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("index.md"), "# My vault\n").unwrap();
+
+        export_markdown_vault(&store, research.id, dir).unwrap();
+        let report = export_markdown_vault(&store, cooking.id, dir).unwrap();
+        assert_eq!(report.removed, 0);
+        assert!(dir.join(note_file_name(&ids[0])).exists());
+        assert!(dir.join(note_file_name(&ids[1])).exists());
+        assert!(dir.join(index_note_name(research.id)).exists());
+        assert!(dir.join(index_note_name(cooking.id)).exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("index.md")).unwrap(),
+            "# My vault\n"
+        );
+
+        store
+            .update_bookmark(
+                ids[1].id,
+                BookmarkPatch {
+                    title: Some("Recipe, renamed".to_string()),
+                    ..BookmarkPatch::default()
+                },
+            )
+            .unwrap();
+        let report = export_markdown_vault(&store, cooking.id, dir).unwrap();
+        assert_eq!(report.removed, 1);
+        assert!(dir.join(note_file_name(&ids[0])).exists());
+        assert!(!dir.join(note_file_name(&ids[1])).exists());
+    }
+}
@@ -0,0 +1,7 @@
+mod citations;
+mod markdown;
+mod netscape;
+
+pub use citations::{export_bibtex, export_csl_json};
+pub use markdown::{export_markdown_vault, VaultReport};
+pub use netscape::export_netscape;
@@ -0,0 +1,141 @@
+use uuid::Uuid;
+
+use crate::errors::Result;
+use crate::html::escape;
+use crate::models::{BookmarkFilter, BookmarkTagDetail, TagSource};
+use crate::store::Store;
+
+// Other browsers treat TAGS as the user's own, so guesses below this
+// confidence stay behind.
+const MIN_EXPORTED_CONFIDENCE: f32 = 0.8;
+
+pub fn export_netscape<S: Store>(store: &S, workspace_id: Option<Uuid>) -> Result<String> {
+    let mut out = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n",
+    );
+
+    let mut workspaces = store.list_workspaces()?;
+    workspaces.retain(|workspace| workspace_id.is_none_or(|id| id == workspace.id));
+    workspaces.sort_by_key(|workspace| workspace.created_at);
+
+    for workspace in workspaces {
+        out.push_str(&format!(
+            "    <DT><H3 ADD_DATE=\"{}\">{}</H3>\n    <DL><p>\n",
+            workspace.created_at / 1000,
+            escape(&workspace.name)
+        ));
+
+        let mut bookmarks = store.list_bookmarks(BookmarkFilter {
+            workspace_id: Some(workspace.id),
+            ..BookmarkFilter::default()
+        })?;
+        bookmarks.sort_by_key(|bookmark| bookmark.created_at);
+
+        for bookmark in bookmarks {
+            let tags = store
+                .list_tags_for_bookmark(bookmark.id)?
+                .into_iter()
+                .filter(exported)
+                .map(|tag| tag.name.replace(',', " "))
+                .collect::<Vec<_>>();
+            out.push_str(&format!(
+                "        <DT><A HREF=\"{}\" ADD_DATE=\"{}\" LAST_MODIFIED=\"{}\"",
+                escape(&bookmark.url),
+                bookmark.created_at / 1000,
+                bookmark.updated_at / 1000
+            ));
+            if !tags.is_empty() {
+                out.push_str(&format!(" TAGS=\"{}\"", escape(&tags.join(","))));
+            }
+            if let Some(review) = &bookmark.review {
+                out.push_str(&format!(" REVIEW_DUE=\"{}\"", review.due_at / 1000));
+            }
+            out.push_str(&format!(">{}</A>\n", escape(&bookmark.title)));
+            if let Some(notes) = bookmark.notes.as_deref().filter(|notes| !notes.is_empty()) {
+                out.push_str(&format!("        <DD>{}\n", escape(notes)));
+            }
+        }
+
+        out.push_str("    </DL><p>\n");
+    }
+
+    out.push_str("</DL><p>\n");
+    Ok(out)
+}
+
+fn exported(tag: &BookmarkTagDetail) -> bool {
+    tag.confidence >= MIN_EXPORTED_CONFIDENCE
+        || tag.evidence.iter().any(|evidence| evidence.source == TagSource::User)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{import_bookmarks, parse_netscape, FolderMapping, ImportOptions};
+    use crate::memory::MemoryStore;
+    use crate::models::{BookmarkInput, BookmarkMetadata, TagSource, TagSuggestion};
+
+    #[test]
+    fn round_trips_workspaces_as_folders() {
+        let store = MemoryStore::new();
+        let workspace = store.create_workspace("Reading <list>").unwrap();
+        let bookmark = store
+            .create_bookmark(BookmarkInput {
+                workspace_id: workspace.id,
+                url: "https://example.com/?a=1&b=2".to_string(),
+                title: "Example".to_string(),
+                notes: Some("Notes & thoughts".to_string()),
+                metadata: BookmarkMetadata::default(),
+            })
+            .unwrap();
+        let suggestion = |name: &str, confidence, source| TagSuggestion {
+            name: name.to_string(),
+            confidence,
+            source,
+        };
+        store
+            .upsert_tags_for_bookmark(
+                bookmark.id,
+                &[
+                    suggestion("rust", 1.0, TagSource::User),
+                    suggestion("databases", 0.9, TagSource::Rules),
+                    suggestion("cooking", 0.4, TagSource::Llm),
+                ],
+            )
+            .unwrap();
+        store.schedule_review(bookmark.id, 1_700_000_000_000).unwrap();
+
+        let html = export_netscape(&store, None).unwrap();
+        assert!(html.contains(" REVIEW_DUE=\"1700000000\">Example</A>"));
+        let items = parse_netscape(&html);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].url, bookmark.url);
+        assert_eq!(items[0].folders, vec!["Reading <list>"]);
+        assert_eq!(items[0].tags, vec!["rust", "database"]);
+        assert_eq!(items[0].notes.as_deref(), Some("Notes & thoughts"));
+        assert_eq!(items[0].created_at, Some(bookmark.created_at / 1000 * 1000));
+        assert_eq!(items[0].review_due, Some(1_700_000_000_000));
+
+        let target = MemoryStore::new();
+        let inbox = target.create_workspace("Inbox").unwrap();
+        import_bookmarks(
+            &target,
+            items,
+            &ImportOptions {
+                workspace_id: inbox.id,
+                folder_mapping: FolderMapping::Tags,
+                dry_run: false,
+            },
+        )
+        .unwrap();
+        let imported = target.list_bookmarks(BookmarkFilter::default()).unwrap();
+        assert_eq!(
+            imported[0].review.as_ref().map(|review| review.due_at),
+            Some(1_700_000_000_000)
+        );
+    }
+}
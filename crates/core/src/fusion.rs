@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{CoreError, Result};
+use crate::models::{TagEvidence, TagSource};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FusionMethod {
+    // 1 - Π(1 - p): independent sources reinforce each other.
+    #[default]
+    NoisyOr,
+    // Average of the sources' confidences, weighted by their calibration.
+    Weighted,
+}
+
+// How the evidence sources give for one bookmark tag becomes the confidence
+// stored on it. Each source's confidence is scaled by its calibration weight
+// (0 to 1) first; the defaults leave a single source's confidence as it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TagFusion {
+    pub method: FusionMethod,
+    pub rules: f32,
+    pub llm: f32,
+    pub user: f32,
+}
+
+impl Default for TagFusion {
+    fn default() -> Self {
+        Self {
+            method: FusionMethod::default(),
+            rules: 1.0,
+            llm: 1.0,
+            user: 1.0,
+        }
+    }
+}
+
+impl TagFusion {
+    pub fn with_method(mut self, method: FusionMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_weight(mut self, source: TagSource, weight: f32) -> Self {
+        match source {
+            TagSource::Rules => self.rules = weight,
+            TagSource::Llm => self.llm = weight,
+            TagSource::User => self.user = weight,
+        }
+        self
+    }
+
+    pub fn weight(&self, source: &TagSource) -> f32 {
+        match source {
+            TagSource::Rules => self.rules,
+            TagSource::Llm => self.llm,
+            TagSource::User => self.user,
+        }
+    }
+
+    pub fn calibrate(&self, evidence: &TagEvidence) -> f32 {
+        (evidence.confidence * self.weight(&evidence.source)).clamp(0.0, 1.0)
+    }
+
+    // The fused confidence and the source that contributed most to it, or
+    // `None` without evidence.
+    pub fn fuse(&self, evidence: &[TagEvidence]) -> Option<(f32, TagSource)> {
+        let strongest = evidence
+            .iter()
+            .max_by(|a, b| self.calibrate(a).total_cmp(&self.calibrate(b)))?;
+        let confidence = match self.method {
+            FusionMethod::NoisyOr => {
+                1.0 - evidence
+                    .iter()
+                    .map(|e| 1.0 - self.calibrate(e))
+                    .product::<f32>()
+            }
+            FusionMethod::Weighted => {
+                let total: f32 = evidence.iter().map(|e| self.weight(&e.source)).sum();
+                if total > 0.0 {
+                    evidence.iter().map(|e| self.calibrate(e)).sum::<f32>() / total
+                } else {
+                    0.0
+                }
+            }
+        };
+        Some((confidence.clamp(0.0, 1.0), strongest.source.clone()))
+    }
+}
+
+// One `source=weight` calibration setting, as given on the command line or
+// in `LESTER_TAG_CALIBRATION`.
+pub fn parse_calibration(value: &str) -> Result<(TagSource, f32)> {
+    let invalid = || CoreError::InvalidInput(format!("expected source=weight, got `{}`", value));
+    let (source, weight) = value.split_once('=').ok_or_else(invalid)?;
+    let source = match source.trim() {
+        "rules" => TagSource::Rules,
+        "llm" => TagSource::Llm,
+        "user" => TagSource::User,
+        _ => return Err(invalid()),
+    };
+    let weight: f32 = weight.trim().parse().map_err(|_| invalid())?;
+    if !(0.0..=1.0).contains(&weight) {
+        return Err(CoreError::InvalidInput(
+            "calibration weights must be between 0 and 1".to_string(),
+        ));
+    }
+    Ok((source, weight))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evidence(source: TagSource, confidence: f32) -> TagEvidence {
+        TagEvidence {
+            source,
+            confidence,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn fuses_calibrated_evidence() {
+        let evidence = [
+            evidence(TagSource::Rules, 0.6),
+            evidence(TagSource::Llm, 0.5),
+        ];
+        let fusion = TagFusion::default();
+        let (confidence, source) = fusion.fuse(&evidence).unwrap();
+        assert!((confidence - 0.8).abs() < 1e-6);
+        assert_eq!(source, TagSource::Rules);
+        assert_eq!(fusion.fuse(&evidence[1..]), Some((0.5, TagSource::Llm)));
+        assert_eq!(fusion.fuse(&[]), None);
+
+        let weighted = fusion
+            .with_method(FusionMethod::Weighted)
+            .with_weight(TagSource::Rules, 0.5);
+        let (confidence, source) = weighted.fuse(&evidence).unwrap();
+        assert!((confidence - 0.8 / 1.5).abs() < 1e-6);
+        assert_eq!(source, TagSource::Llm);
+    }
+
+    #[test]
+    fn parses_calibration_settings() {
+        assert_eq!(
+            parse_calibration("llm=0.75").unwrap(),
+            (TagSource::Llm, 0.75)
+        );
+        assert!(parse_calibration("llm").is_err());
+        assert!(parse_calibration("robots=0.5").is_err());
+        assert!(parse_calibration("rules=1.5").is_err());
+    }
+}
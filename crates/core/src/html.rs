@@ -0,0 +1,263 @@
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Start {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    End {
+        name: String,
+    },
+    Text(String),
+}
+
+impl Token {
+    pub(crate) fn is_end(&self, tag: &str) -> bool {
+        matches!(self, Token::End { name } if name == tag)
+    }
+
+    pub(crate) fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            Token::Start { attrs, .. } => attrs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            push_text(&mut tokens, rest);
+            break;
+        };
+        push_text(&mut tokens, &rest[..open]);
+        rest = &rest[open..];
+
+        if rest.starts_with("<!--") {
+            rest = match rest.find("-->") {
+                Some(end) => &rest[end + 3..],
+                None => "",
+            };
+            continue;
+        }
+
+        let Some(close) = find_tag_end(rest) else {
+            push_text(&mut tokens, rest);
+            break;
+        };
+        let inner = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        if inner.starts_with('!') || inner.starts_with('?') {
+            continue;
+        }
+        if let Some(name) = inner.strip_prefix('/') {
+            tokens.push(Token::End {
+                name: name.trim().to_ascii_lowercase(),
+            });
+            continue;
+        }
+        let inner = inner.trim_end_matches('/');
+        let name_end = inner
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(inner.len());
+        let name = inner[..name_end].to_ascii_lowercase();
+        if name.is_empty() {
+            push_text(&mut tokens, "<");
+            continue;
+        }
+        let attrs = parse_attrs(&inner[name_end..]);
+        tokens.push(Token::Start { name, attrs });
+    }
+
+    tokens
+}
+
+pub(crate) fn collect_text<'a>(tokens: &mut impl Iterator<Item = &'a Token>, tag: &str) -> String {
+    let mut text = String::new();
+    for token in tokens {
+        match token {
+            Token::Text(value) => {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(value);
+            }
+            token if token.is_end(tag) => break,
+            _ => {}
+        }
+    }
+    text
+}
+
+pub(crate) fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+pub(crate) fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let entity = &rest[1..semi];
+            decode_entity(entity).map(|c| (c, semi))
+        });
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = if let Some(hex) = entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                entity.strip_prefix('#')?.parse().ok()?
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+fn push_text(tokens: &mut Vec<Token>, text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    tokens.push(Token::Text(unescape(text.trim())));
+}
+
+fn find_tag_end(input: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in input.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return Some(idx),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_attrs(input: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut end = start;
+        while let Some(&(idx, c)) = chars.peek() {
+            if c.is_whitespace() || c == '=' {
+                break;
+            }
+            end = idx + c.len_utf8();
+            chars.next();
+        }
+        let name = input[start..end].to_ascii_lowercase();
+        while matches!(chars.peek(), Some(&(_, c)) if c.is_whitespace()) {
+            chars.next();
+        }
+        if !matches!(chars.peek(), Some(&(_, '='))) {
+            if !name.is_empty() {
+                attrs.push((name, String::new()));
+            } else {
+                chars.next();
+            }
+            continue;
+        }
+        chars.next();
+        while matches!(chars.peek(), Some(&(_, c)) if c.is_whitespace()) {
+            chars.next();
+        }
+        let value = match chars.peek() {
+            Some(&(idx, quote)) if quote == '"' || quote == '\'' => {
+                chars.next();
+                let value_start = idx + 1;
+                let mut value_end = input.len();
+                for (idx, c) in chars.by_ref() {
+                    if c == quote {
+                        value_end = idx;
+                        break;
+                    }
+                }
+                &input[value_start..value_end.max(value_start)]
+            }
+            Some(&(idx, _)) => {
+                let mut value_end = input.len();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() {
+                        value_end = i;
+                        break;
+                    }
+                    chars.next();
+                }
+                &input[idx..value_end]
+            }
+            None => "",
+        };
+        attrs.push((name, unescape(value)));
+    }
+
+    attrs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizes_tags_attributes_and_entities() {
+        let tokens = tokenize(
+            r#"<DT><A HREF="https://example.com/?a=1&amp;b=2" TAGS=rust>Fish &amp; Chips</A>"#,
+        );
+        assert_eq!(tokens.len(), 4);
+        assert!(matches!(&tokens[0], Token::Start { name, .. } if name == "dt"));
+        assert_eq!(tokens[1].attr("href"), Some("https://example.com/?a=1&b=2"));
+        assert_eq!(tokens[1].attr("tags"), Some("rust"));
+        assert_eq!(tokens[2], Token::Text("Fish & Chips".to_string()));
+        assert!(tokens[3].is_end("a"));
+        assert_eq!(unescape(&escape("<a href=\"x\">")), "<a href=\"x\">");
+    }
+}
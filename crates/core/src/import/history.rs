@@ -0,0 +1,355 @@
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domains::{normalize_domain, parse_host};
+use crate::errors::{CoreError, Result};
+use crate::import::{
+    import_bookmarks, FolderMapping, ImportAction, ImportEntry, ImportOptions, ImportReport,
+    ImportedBookmark,
+};
+use crate::models::HistoryEntryInput;
+use crate::store::Store;
+
+const CHROMIUM_EPOCH_OFFSET_MS: i64 = 11_644_473_600_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Browser {
+    Firefox,
+    Chromium,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryTarget {
+    Bookmarks,
+    #[default]
+    History,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryFilter {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub domain: Option<String>,
+    pub min_visits: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryVisit {
+    pub url: String,
+    pub title: String,
+    pub visit_count: i64,
+    pub last_visited_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryImportOptions {
+    pub workspace_id: Uuid,
+    #[serde(default)]
+    pub target: HistoryTarget,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+pub fn read_browser_history(
+    path: &str,
+    browser: Option<Browser>,
+    filter: &HistoryFilter,
+) -> Result<Vec<HistoryVisit>> {
+    if !std::path::Path::new(path).is_file() {
+        return Err(CoreError::InvalidInput(format!(
+            "history database not found: {}",
+            path
+        )));
+    }
+    // The browser may have the database open in WAL mode, with recent visits
+    // only in `-wal`. Reading a copy of both sees them without touching the
+    // live files or racing the browser's writes.
+    let snapshot = tempfile::tempdir()?;
+    let copy = snapshot.path().join("history.sqlite");
+    std::fs::copy(path, &copy)?;
+    let wal = format!("{}-wal", path);
+    if std::path::Path::new(&wal).is_file() {
+        std::fs::copy(&wal, snapshot.path().join("history.sqlite-wal"))?;
+    }
+    let conn = Connection::open_with_flags(&copy, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let browser = match browser {
+        Some(browser) => browser,
+        None => detect_browser(&conn)?,
+    };
+
+    let sql = match browser {
+        Browser::Firefox => {
+            "SELECT url, COALESCE(title, ''), visit_count, last_visit_date / 1000
+             FROM moz_places
+             WHERE hidden = 0 AND visit_count > 0 AND last_visit_date IS NOT NULL
+             ORDER BY last_visit_date DESC"
+        }
+        Browser::Chromium => {
+            "SELECT url, COALESCE(title, ''), visit_count, last_visit_time / 1000 - ?1
+             FROM urls
+             WHERE hidden = 0 AND visit_count > 0 AND last_visit_time > 0
+             ORDER BY last_visit_time DESC"
+        }
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let map_row = |row: &rusqlite::Row| {
+        Ok(HistoryVisit {
+            url: row.get(0)?,
+            title: row.get(1)?,
+            visit_count: row.get(2)?,
+            last_visited_at: row.get(3)?,
+        })
+    };
+    let rows = match browser {
+        Browser::Firefox => stmt.query_map([], map_row)?,
+        Browser::Chromium => stmt.query_map(params![CHROMIUM_EPOCH_OFFSET_MS], map_row)?,
+    };
+
+    let domain = filter.domain.as_deref().and_then(normalize_domain);
+    let mut visits = Vec::new();
+    for visit in rows {
+        let visit = visit?;
+        if !visit.url.starts_with("http://") && !visit.url.starts_with("https://") {
+            continue;
+        }
+        if filter
+            .since
+            .is_some_and(|since| visit.last_visited_at < since)
+            || filter
+                .until
+                .is_some_and(|until| visit.last_visited_at > until)
+            || filter.min_visits.is_some_and(|min| visit.visit_count < min)
+        {
+            continue;
+        }
+        if let Some(domain) = domain.as_deref() {
+            let host = parse_host(&visit.url).map(|parsed| parsed.host).unwrap_or_default();
+            if host != domain && !host.ends_with(&format!(".{}", domain)) {
+                continue;
+            }
+        }
+        visits.push(visit);
+    }
+    Ok(visits)
+}
+
+pub fn import_history<S: Store>(
+    store: &S,
+    visits: Vec<HistoryVisit>,
+    options: &HistoryImportOptions,
+) -> Result<ImportReport> {
+    match options.target {
+        HistoryTarget::Bookmarks => {
+            let items = visits
+                .into_iter()
+                .map(|visit| ImportedBookmark {
+                    url: visit.url,
+                    title: visit.title,
+                    created_at: Some(visit.last_visited_at),
+                    updated_at: Some(visit.last_visited_at),
+                    ..ImportedBookmark::default()
+                })
+                .collect();
+            let import_options = ImportOptions {
+                workspace_id: options.workspace_id,
+                folder_mapping: FolderMapping::Workspaces,
+                dry_run: options.dry_run,
+            };
+            import_bookmarks(store, items, &import_options)
+        }
+        HistoryTarget::History => {
+            let workspace = store
+                .list_workspaces()?
+                .into_iter()
+                .find(|workspace| workspace.id == options.workspace_id)
+                .ok_or(CoreError::NotFound)?;
+            let mut report = ImportReport {
+                dry_run: options.dry_run,
+                ..ImportReport::default()
+            };
+            for visit in visits {
+                report.entries.push(ImportEntry {
+                    url: visit.url.clone(),
+                    workspace: workspace.name.clone(),
+                    tags: Vec::new(),
+                    action: ImportAction::Create,
+                });
+                report.history_entries += 1;
+                if options.dry_run {
+                    continue;
+                }
+                store.upsert_history_entry(HistoryEntryInput {
+                    workspace_id: workspace.id,
+                    url: visit.url,
+                    title: visit.title,
+                    visit_count: visit.visit_count,
+                    last_visited_at: visit.last_visited_at,
+                })?;
+            }
+            Ok(report)
+        }
+    }
+}
+
+fn detect_browser(conn: &Connection) -> Result<Browser> {
+    let has_table = |name: &str| -> Result<bool> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    };
+    if has_table("moz_places")? {
+        Ok(Browser::Firefox)
+    } else if has_table("urls")? {
+        Ok(Browser::Chromium)
+    } else {
+        Err(CoreError::InvalidInput(
+            "not a Firefox or Chromium history database".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+
+    fn firefox_fixture() -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let conn = Connection::open(file.path()).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE moz_places (
+                id INTEGER PRIMARY KEY, url LONGVARCHAR, title LONGVARCHAR,
+                rev_host LONGVARCHAR, visit_count INTEGER DEFAULT 0, hidden INTEGER DEFAULT 0 NOT NULL,
+                typed INTEGER DEFAULT 0 NOT NULL, frecency INTEGER DEFAULT -1 NOT NULL,
+                last_visit_date INTEGER, guid TEXT
+            );
+            INSERT INTO moz_places (url, title, visit_count, hidden, last_visit_date) VALUES
+                ('https://docs.rs/rusqlite', 'rusqlite docs', 12, 0, 1700000000000000),
+                ('https://www.rust-lang.org/', 'Rust', 3, 0, 1690000000000000),
+                ('https://example.com/', 'Example', 1, 0, 1700000100000000),
+                ('place:sort=8', 'Recent', 4, 0, 1700000000000000),
+                ('https://hidden.example/', 'Hidden', 9, 1, 1700000000000000);",
+        )
+        .unwrap();
+        file
+    }
+
+    fn chromium_fixture() -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let conn = Connection::open(file.path()).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, url LONGVARCHAR, title LONGVARCHAR,
+                visit_count INTEGER DEFAULT 0 NOT NULL, typed_count INTEGER DEFAULT 0 NOT NULL,
+                last_visit_time INTEGER NOT NULL, hidden INTEGER DEFAULT 0 NOT NULL
+            );
+            INSERT INTO urls (url, title, visit_count, last_visit_time) VALUES
+                ('https://news.ycombinator.com/item?id=1', 'HN', 7, 13344473600000000),
+                ('https://arxiv.org/abs/1706.03762', 'Attention', 2, 13344473700000000);",
+        )
+        .unwrap();
+        file
+    }
+
+    #[test]
+    fn reads_firefox_history_with_filters() {
+        let fixture = firefox_fixture();
+        let path = fixture.path();
+        let all =
+            read_browser_history(&path.to_string_lossy(), None, &HistoryFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let filtered = read_browser_history(
+            &path.to_string_lossy(),
+            Some(Browser::Firefox),
+            &HistoryFilter {
+                since: Some(1_695_000_000_000),
+                min_visits: Some(2),
+                ..HistoryFilter::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].url, "https://docs.rs/rusqlite");
+        assert_eq!(filtered[0].last_visited_at, 1_700_000_000_000);
+
+        let by_domain = read_browser_history(
+            &path.to_string_lossy(),
+            None,
+            &HistoryFilter {
+                domain: Some("rust-lang.org".to_string()),
+                ..HistoryFilter::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(by_domain.len(), 1);
+    }
+
+    #[test]
+    fn imports_chromium_history_as_entries_or_bookmarks() {
+        let fixture = chromium_fixture();
+        let visits =
+            read_browser_history(&fixture.path().to_string_lossy(), None, &HistoryFilter::default())
+                .unwrap();
+        assert_eq!(visits.len(), 2);
+        assert_eq!(visits[0].last_visited_at, 1_700_000_100_000);
+
+        let store = MemoryStore::new();
+        let workspace = store.create_workspace("Research").unwrap();
+
+        let options = HistoryImportOptions {
+            workspace_id: workspace.id,
+            target: HistoryTarget::History,
+            dry_run: false,
+        };
+        import_history(&store, visits.clone(), &options).unwrap();
+        let report = import_history(&store, visits.clone(), &options).unwrap();
+        assert_eq!(report.history_entries, 2);
+        let entries = store.list_history(Some(workspace.id), 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].url, "https://arxiv.org/abs/1706.03762");
+        assert_eq!(entries[1].visit_count, 7);
+
+        let options = HistoryImportOptions {
+            target: HistoryTarget::Bookmarks,
+            ..options
+        };
+        let report = import_history(&store, visits, &options).unwrap();
+        assert_eq!(report.bookmarks_created, 2);
+        assert_eq!(report.jobs_queued, 2);
+    }
+
+    #[test]
+    fn reads_visits_still_in_the_wal_of_an_odd_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("places?v=1#100%.sqlite");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+            PRAGMA wal_autocheckpoint = 0;
+            CREATE TABLE urls (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, url LONGVARCHAR, title LONGVARCHAR,
+                visit_count INTEGER DEFAULT 0 NOT NULL, last_visit_time INTEGER NOT NULL,
+                hidden INTEGER DEFAULT 0 NOT NULL
+            );
+            INSERT INTO urls (url, title, visit_count, last_visit_time) VALUES
+                ('https://example.com/', 'Example', 1, 13344473600000000);",
+        )
+        .unwrap();
+        assert!(dir.path().join("places?v=1#100%.sqlite-wal").exists());
+
+        // `conn` stays open, as a running browser would keep it.
+        let visits =
+            read_browser_history(&path.to_string_lossy(), None, &HistoryFilter::default())
+                .unwrap();
+        assert_eq!(visits.len(), 1);
+        assert_eq!(visits[0].url, "https://example.com/");
+        drop(conn);
+    }
+}
@@ -0,0 +1,390 @@
+mod history;
+mod netscape;
+mod pinboard;
+mod pocket;
+mod raindrop;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{CoreError, Result};
+use crate::models::{
+    BookmarkFilter, BookmarkInput, BookmarkMetadata, ReadState, TagSource, TagSuggestion,
+};
+use crate::store::Store;
+
+pub use history::{
+    import_history, read_browser_history, Browser, HistoryFilter, HistoryImportOptions,
+    HistoryTarget, HistoryVisit,
+};
+pub use netscape::parse_netscape;
+pub use pinboard::parse_pinboard_json;
+pub use pocket::{parse_pocket_csv, parse_pocket_html};
+pub use raindrop::parse_raindrop_csv;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedBookmark {
+    pub url: String,
+    pub title: String,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+    pub folders: Vec<String>,
+    pub unread: Option<bool>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    // Set by our own Netscape export (`REVIEW_DUE`).
+    pub review_due: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportFormat {
+    Netscape,
+    Pinboard,
+    PocketHtml,
+    PocketCsv,
+    Raindrop,
+}
+
+impl ImportFormat {
+    // A malformed export is the caller's input, so it is `InvalidInput`
+    // rather than a CSV or JSON error.
+    pub fn parse(&self, input: &str) -> Result<Vec<ImportedBookmark>> {
+        let parsed = match self {
+            ImportFormat::Netscape => Ok(parse_netscape(input)),
+            ImportFormat::Pinboard => parse_pinboard_json(input),
+            ImportFormat::PocketHtml => Ok(parse_pocket_html(input)),
+            ImportFormat::PocketCsv => parse_pocket_csv(input),
+            ImportFormat::Raindrop => parse_raindrop_csv(input),
+        };
+        parsed.map_err(|err| match err {
+            CoreError::Csv(_) | CoreError::Serialization(_) => {
+                CoreError::InvalidInput(format!("malformed {:?} export: {}", self, err))
+            }
+            err => err,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderMapping {
+    #[default]
+    Workspaces,
+    Tags,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportOptions {
+    pub workspace_id: Uuid,
+    #[serde(default)]
+    pub folder_mapping: FolderMapping,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub bookmarks_created: usize,
+    pub history_entries: usize,
+    pub workspaces_created: usize,
+    pub tags_applied: usize,
+    pub jobs_queued: usize,
+    pub skipped: usize,
+    pub entries: Vec<ImportEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportEntry {
+    pub url: String,
+    pub workspace: String,
+    pub tags: Vec<String>,
+    pub action: ImportAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    SkipDuplicate,
+    SkipInvalid,
+}
+
+pub fn import_bookmarks<S: Store>(
+    store: &S,
+    items: Vec<ImportedBookmark>,
+    options: &ImportOptions,
+) -> Result<ImportReport> {
+    let mut report = ImportReport {
+        dry_run: options.dry_run,
+        ..ImportReport::default()
+    };
+    let mut workspaces: HashMap<String, Uuid> = HashMap::new();
+    let mut workspace_names: HashMap<Uuid, String> = HashMap::new();
+    for workspace in store.list_workspaces()? {
+        workspaces.insert(workspace.name.clone(), workspace.id);
+        workspace_names.insert(workspace.id, workspace.name);
+    }
+    if !workspace_names.contains_key(&options.workspace_id) {
+        return Err(CoreError::NotFound);
+    }
+    let mut seen_urls: HashMap<Uuid, HashSet<String>> = HashMap::new();
+
+    for item in items {
+        let url = item.url.trim().to_string();
+        let mut tags = item.tags.clone();
+
+        let workspace_id = match (options.folder_mapping, item.folders.is_empty()) {
+            (_, true) => options.workspace_id,
+            (FolderMapping::Tags, false) => {
+                tags.push(item.folders.join("/"));
+                options.workspace_id
+            }
+            (FolderMapping::Workspaces, false) => {
+                let name = item.folders.join(" / ");
+                match workspaces.get(&name) {
+                    Some(id) => *id,
+                    None => {
+                        let id = if options.dry_run {
+                            Uuid::new_v4()
+                        } else {
+                            store.create_workspace(&name)?.id
+                        };
+                        report.workspaces_created += 1;
+                        workspaces.insert(name.clone(), id);
+                        workspace_names.insert(id, name);
+                        id
+                    }
+                }
+            }
+        };
+        let tags = dedup_tags(tags);
+        let mut entry = ImportEntry {
+            url: url.clone(),
+            workspace: workspace_names
+                .get(&workspace_id)
+                .cloned()
+                .unwrap_or_default(),
+            tags: tags.clone(),
+            action: ImportAction::Create,
+        };
+
+        if url.is_empty() {
+            entry.action = ImportAction::SkipInvalid;
+            report.skipped += 1;
+            report.entries.push(entry);
+            continue;
+        }
+
+        let urls = match seen_urls.entry(workspace_id) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let existing = store.list_bookmarks(BookmarkFilter {
+                    workspace_id: Some(workspace_id),
+                    ..BookmarkFilter::default()
+                })?;
+                entry.insert(existing.into_iter().map(|bookmark| bookmark.url).collect())
+            }
+        };
+        if !urls.insert(url.clone()) {
+            entry.action = ImportAction::SkipDuplicate;
+            report.skipped += 1;
+            report.entries.push(entry);
+            continue;
+        }
+
+        report.bookmarks_created += 1;
+        report.tags_applied += tags.len();
+        report.jobs_queued += 1;
+        report.entries.push(entry);
+        if options.dry_run {
+            continue;
+        }
+
+        let title = if item.title.trim().is_empty() {
+            url.clone()
+        } else {
+            item.title
+        };
+        let input = BookmarkInput {
+            workspace_id,
+            url,
+            title,
+            notes: item.notes.filter(|notes| !notes.trim().is_empty()),
+            metadata: BookmarkMetadata::default(),
+        };
+        let bookmark = store.create_bookmark_at(input, item.created_at, item.updated_at)?;
+        if let Some(unread) = item.unread {
+            let state = if unread { ReadState::Unread } else { ReadState::Read };
+            store.set_read_state(bookmark.id, state, None)?;
+        }
+        if let Some(due_at) = item.review_due {
+            store.schedule_review(bookmark.id, due_at)?;
+        }
+
+        let suggestions = tags
+            .into_iter()
+            .map(|name| TagSuggestion {
+                name,
+                confidence: 1.0,
+                source: TagSource::User,
+            })
+            .collect::<Vec<_>>();
+        if !suggestions.is_empty() {
+            store.upsert_tags_for_bookmark(bookmark.id, &suggestions)?;
+        }
+        store.enqueue_tag_job(bookmark.id)?;
+    }
+
+    Ok(report)
+}
+
+pub(crate) fn split_tags(value: &str, separator: char) -> Vec<String> {
+    value
+        .split(separator)
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+pub(crate) fn parse_epoch(value: &str) -> Option<i64> {
+    let value: i64 = value.trim().parse().ok()?;
+    if value <= 0 {
+        return None;
+    }
+    // Exporters write seconds, but some use milliseconds or microseconds.
+    if value > 100_000_000_000_000 {
+        Some(value / 1_000)
+    } else if value > 100_000_000_000 {
+        Some(value)
+    } else {
+        Some(value * 1_000)
+    }
+}
+
+fn dedup_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+
+    fn items() -> Vec<ImportedBookmark> {
+        vec![
+            ImportedBookmark {
+                url: "https://example.com/a".to_string(),
+                title: "A".to_string(),
+                folders: vec!["Research".to_string(), "ML".to_string()],
+                tags: vec!["papers".to_string()],
+                unread: Some(true),
+                created_at: Some(1_600_000_000_000),
+                ..ImportedBookmark::default()
+            },
+            ImportedBookmark {
+                url: "https://example.com/a".to_string(),
+                title: "A again".to_string(),
+                folders: vec!["Research".to_string(), "ML".to_string()],
+                ..ImportedBookmark::default()
+            },
+            ImportedBookmark {
+                url: "https://example.com/b".to_string(),
+                title: String::new(),
+                unread: Some(false),
+                ..ImportedBookmark::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn maps_folders_to_workspaces_and_skips_duplicates() {
+        let store = MemoryStore::new();
+        let inbox = store.create_workspace("Inbox").unwrap();
+
+        let options = ImportOptions {
+            workspace_id: inbox.id,
+            folder_mapping: FolderMapping::Workspaces,
+            dry_run: false,
+        };
+        let report = import_bookmarks(&store, items(), &options).unwrap();
+        assert_eq!(report.bookmarks_created, 2);
+        assert_eq!(report.workspaces_created, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.tags_applied, 1);
+        assert_eq!(report.jobs_queued, 2);
+        assert_eq!(report.entries[1].action, ImportAction::SkipDuplicate);
+        assert_eq!(store.fetch_pending_tag_jobs(10).unwrap().len(), 2);
+
+        let workspaces = store.list_workspaces().unwrap();
+        let research = workspaces
+            .iter()
+            .find(|w| w.name == "Research / ML")
+            .unwrap();
+        let imported = store
+            .list_bookmarks(BookmarkFilter {
+                workspace_id: Some(research.id),
+                ..BookmarkFilter::default()
+            })
+            .unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].reading.state, ReadState::Unread);
+        assert!(store
+            .list_tags_for_bookmark(imported[0].id)
+            .unwrap()
+            .iter()
+            .all(|tag| tag.name != "unread"));
+        assert_eq!(imported[0].created_at, 1_600_000_000_000);
+        let inbox_items = store
+            .list_bookmarks(BookmarkFilter {
+                workspace_id: Some(inbox.id),
+                ..BookmarkFilter::default()
+            })
+            .unwrap();
+        assert_eq!(inbox_items[0].title, "https://example.com/b");
+        assert_eq!(inbox_items[0].reading.state, ReadState::Read);
+    }
+
+    #[test]
+    fn malformed_exports_are_invalid_input() {
+        for (format, input) in [
+            (ImportFormat::Pinboard, "[{\"href\": "),
+            (ImportFormat::Raindrop, "id,title\n1,a,extra\n"),
+        ] {
+            assert!(matches!(format.parse(input), Err(CoreError::InvalidInput(_))));
+        }
+    }
+
+    #[test]
+    fn dry_run_reports_without_writing() {
+        let store = MemoryStore::new();
+        let inbox = store.create_workspace("Inbox").unwrap();
+
+        let options = ImportOptions {
+            workspace_id: inbox.id,
+            folder_mapping: FolderMapping::Tags,
+            dry_run: true,
+        };
+        let report = import_bookmarks(&store, items(), &options).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.bookmarks_created, 2);
+        assert_eq!(report.workspaces_created, 0);
+        assert_eq!(report.entries[0].workspace, "Inbox");
+        assert_eq!(
+            report.entries[0].tags,
+            vec!["papers", "Research/ML"]
+        );
+        assert!(store
+            .list_bookmarks(BookmarkFilter::default())
+            .unwrap()
+            .is_empty());
+        assert_eq!(store.list_workspaces().unwrap().len(), 1);
+    }
+}
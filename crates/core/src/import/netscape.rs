@@ -0,0 +1,108 @@
+use crate::html::{collect_text, tokenize, Token};
+use crate::import::{parse_epoch, split_tags, ImportedBookmark};
+
+pub fn parse_netscape(html: &str) -> Vec<ImportedBookmark> {
+    let tokens = tokenize(html);
+    let mut bookmarks: Vec<ImportedBookmark> = Vec::new();
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut pending_folder: Option<String> = None;
+    let mut last_was_bookmark = false;
+    let mut iter = tokens.iter().peekable();
+
+    while let Some(token) = iter.next() {
+        match token {
+            Token::Start { name, .. } if name == "h3" => {
+                pending_folder = Some(collect_text(&mut iter, "h3"));
+                last_was_bookmark = false;
+            }
+            Token::Start { name, .. } if name == "dl" => {
+                folders.push(pending_folder.take());
+                last_was_bookmark = false;
+            }
+            Token::End { name } if name == "dl" => {
+                folders.pop();
+                last_was_bookmark = false;
+            }
+            Token::Start { name, .. } if name == "a" => {
+                let title = collect_text(&mut iter, "a");
+                let Some(url) = token.attr("href").map(str::trim) else {
+                    last_was_bookmark = false;
+                    continue;
+                };
+                if url.is_empty() || url.starts_with("place:") || url.starts_with("javascript:") {
+                    last_was_bookmark = false;
+                    continue;
+                }
+                let tags = token
+                    .attr("tags")
+                    .map(|tags| split_tags(tags, ','))
+                    .unwrap_or_default();
+                bookmarks.push(ImportedBookmark {
+                    url: url.to_string(),
+                    title,
+                    notes: None,
+                    tags,
+                    folders: folders.iter().flatten().cloned().collect(),
+                    unread: None,
+                    created_at: token.attr("add_date").and_then(parse_epoch),
+                    updated_at: token.attr("last_modified").and_then(parse_epoch),
+                    review_due: token.attr("review_due").and_then(parse_epoch),
+                });
+                last_was_bookmark = true;
+            }
+            Token::Start { name, .. } if name == "dd" => {
+                if let (true, Some(Token::Text(text))) = (last_was_bookmark, iter.peek()) {
+                    if let Some(bookmark) = bookmarks.last_mut() {
+                        bookmark.notes = Some(text.clone());
+                    }
+                    iter.next();
+                }
+                last_was_bookmark = false;
+            }
+            _ => {}
+        }
+    }
+
+    bookmarks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><A HREF="https://top.example" ADD_DATE="1700000000">Top level</A>
+    <DT><H3 ADD_DATE="1690000000">Research</H3>
+    <DD>Folder description
+    <DL><p>
+        <DT><H3>ML</H3>
+        <DL><p>
+            <DT><A HREF="https://arxiv.org/abs/1" ADD_DATE="1690000001" LAST_MODIFIED="1690000500" TAGS="papers, ml">Attention &amp; more</A>
+            <DD>Worth a re-read
+        </DL><p>
+        <DT><A HREF="place:sort=8">Recent</A>
+    </DL><p>
+</DL><p>
+"#;
+
+    #[test]
+    fn parses_folders_dates_tags_and_notes() {
+        let items = parse_netscape(FIXTURE);
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].url, "https://top.example");
+        assert!(items[0].folders.is_empty());
+        assert_eq!(items[0].created_at, Some(1_700_000_000_000));
+
+        let paper = &items[1];
+        assert_eq!(paper.title, "Attention & more");
+        assert_eq!(paper.folders, vec!["Research", "ML"]);
+        assert_eq!(paper.tags, vec!["papers", "ml"]);
+        assert_eq!(paper.notes.as_deref(), Some("Worth a re-read"));
+        assert_eq!(paper.updated_at, Some(1_690_000_500_000));
+    }
+}
@@ -0,0 +1,66 @@
+use serde::Deserialize;
+
+use crate::dates::parse_rfc3339;
+use crate::errors::Result;
+use crate::import::{split_tags, ImportedBookmark};
+
+#[derive(Deserialize)]
+struct PinboardPost {
+    href: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    extended: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    time: String,
+    #[serde(default)]
+    toread: String,
+}
+
+pub fn parse_pinboard_json(input: &str) -> Result<Vec<ImportedBookmark>> {
+    let posts: Vec<PinboardPost> = serde_json::from_str(input)?;
+    Ok(posts
+        .into_iter()
+        .map(|post| {
+            let created_at = parse_rfc3339(&post.time);
+            ImportedBookmark {
+                url: post.href,
+                title: post.description,
+                notes: Some(post.extended).filter(|notes| !notes.trim().is_empty()),
+                tags: split_tags(&post.tags, ' '),
+                folders: Vec::new(),
+                unread: Some(post.toread == "yes"),
+                created_at,
+                updated_at: created_at,
+                review_due: None,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pinboard_posts() {
+        let input = r#"[
+            {"href":"https://example.com/","description":"Example","extended":"Some notes",
+             "meta":"abc","hash":"def","time":"2023-11-14T22:13:20Z","shared":"no",
+             "toread":"yes","tags":"rust  databases"},
+            {"href":"https://example.org/","description":"Other","extended":"",
+             "time":"2020-01-01T00:00:00Z","toread":"no","tags":""}
+        ]"#;
+        let items = parse_pinboard_json(input).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Example");
+        assert_eq!(items[0].notes.as_deref(), Some("Some notes"));
+        assert_eq!(items[0].tags, vec!["rust", "databases"]);
+        assert_eq!(items[0].unread, Some(true));
+        assert_eq!(items[0].created_at, Some(1_700_000_000_000));
+        assert_eq!(items[1].notes, None);
+        assert_eq!(items[1].unread, Some(false));
+    }
+}
@@ -0,0 +1,119 @@
+use serde::Deserialize;
+
+use crate::errors::Result;
+use crate::html::{collect_text, tokenize, Token};
+use crate::import::{parse_epoch, split_tags, ImportedBookmark};
+
+pub fn parse_pocket_html(html: &str) -> Vec<ImportedBookmark> {
+    let tokens = tokenize(html);
+    let mut items = Vec::new();
+    let mut unread = true;
+    let mut iter = tokens.iter();
+
+    while let Some(token) = iter.next() {
+        match token {
+            Token::Start { name, .. } if name == "h1" => {
+                let heading = collect_text(&mut iter, "h1").to_lowercase();
+                unread = !heading.contains("archive");
+            }
+            Token::Start { name, .. } if name == "a" => {
+                let title = collect_text(&mut iter, "a");
+                let Some(url) = token.attr("href") else {
+                    continue;
+                };
+                let created_at = token.attr("time_added").and_then(parse_epoch);
+                items.push(ImportedBookmark {
+                    url: url.trim().to_string(),
+                    title,
+                    notes: None,
+                    tags: token
+                        .attr("tags")
+                        .map(|tags| split_tags(tags, ','))
+                        .unwrap_or_default(),
+                    folders: Vec::new(),
+                    unread: Some(unread),
+                    created_at,
+                    updated_at: created_at,
+                    review_due: None,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    items
+}
+
+#[derive(Deserialize)]
+struct PocketRow {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    time_added: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    status: String,
+}
+
+pub fn parse_pocket_csv(input: &str) -> Result<Vec<ImportedBookmark>> {
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let mut items = Vec::new();
+    for row in reader.deserialize::<PocketRow>() {
+        let row = row?;
+        let created_at = parse_epoch(&row.time_added);
+        items.push(ImportedBookmark {
+            url: row.url,
+            title: row.title,
+            notes: None,
+            tags: split_tags(&row.tags, '|'),
+            folders: Vec::new(),
+            unread: Some(row.status != "archive"),
+            created_at,
+            updated_at: created_at,
+            review_due: None,
+        });
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pocket_html_sections() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Pocket Export</title></head><body>
+<h1>Unread</h1>
+<ul>
+<li><a href="https://example.com/a" time_added="1700000000" tags="rust,db">Article A</a></li>
+</ul>
+<h1>Read Archive</h1>
+<ul>
+<li><a href="https://example.com/b" time_added="1600000000" tags="">Article B</a></li>
+</ul>
+</body></html>"#;
+        let items = parse_pocket_html(html);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].tags, vec!["rust", "db"]);
+        assert_eq!(items[0].unread, Some(true));
+        assert_eq!(items[0].created_at, Some(1_700_000_000_000));
+        assert_eq!(items[1].unread, Some(false));
+        assert!(items[1].tags.is_empty());
+    }
+
+    #[test]
+    fn parses_pocket_csv_rows() {
+        let input = "title,url,time_added,tags,status\n\
+                     \"Hello, world\",https://example.com/a,1700000000,rust|db,unread\n\
+                     Other,https://example.com/b,1600000000,,archive\n";
+        let items = parse_pocket_csv(input).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Hello, world");
+        assert_eq!(items[0].tags, vec!["rust", "db"]);
+        assert_eq!(items[0].unread, Some(true));
+        assert_eq!(items[1].unread, Some(false));
+    }
+}
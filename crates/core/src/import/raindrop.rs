@@ -0,0 +1,71 @@
+use serde::Deserialize;
+
+use crate::dates::parse_rfc3339;
+use crate::errors::Result;
+use crate::import::{split_tags, ImportedBookmark};
+
+#[derive(Deserialize)]
+struct RaindropRow {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    excerpt: String,
+    url: String,
+    #[serde(default)]
+    folder: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    created: String,
+}
+
+pub fn parse_raindrop_csv(input: &str) -> Result<Vec<ImportedBookmark>> {
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let mut items = Vec::new();
+    for row in reader.deserialize::<RaindropRow>() {
+        let row = row?;
+        let notes = [row.note.trim(), row.excerpt.trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let created_at = parse_rfc3339(&row.created);
+        items.push(ImportedBookmark {
+            url: row.url,
+            title: row.title,
+            notes: Some(notes).filter(|notes| !notes.is_empty()),
+            tags: split_tags(&row.tags, ','),
+            folders: split_tags(&row.folder, '/')
+                .into_iter()
+                .filter(|folder| folder != "Unsorted")
+                .collect(),
+            unread: None,
+            created_at,
+            updated_at: created_at,
+            review_due: None,
+        });
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_raindrop_rows() {
+        let input = "id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite\n\
+                     1,Example,My note,An excerpt,https://example.com/,Research/ML,\"rust, db\",2023-11-14T22:13:20.000Z,,,false\n\
+                     2,Other,,,https://example.org/,Unsorted,,2020-01-01T00:00:00.000Z,,,false\n";
+        let items = parse_raindrop_csv(input).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].notes.as_deref(), Some("My note\n\nAn excerpt"));
+        assert_eq!(items[0].folders, vec!["Research", "ML"]);
+        assert_eq!(items[0].tags, vec!["rust", "db"]);
+        assert_eq!(items[0].created_at, Some(1_700_000_000_000));
+        assert_eq!(items[1].notes, None);
+        assert!(items[1].folders.is_empty());
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use whatlang::{Detector, Lang};
+
+use crate::tokenize::{is_cjk, keyword_tokens};
+
+// Languages we can both detect and stem, keyed by ISO 639-1 code.
+const LANGUAGES: &[(&str, Lang, Algorithm)] = &[
+    ("en", Lang::Eng, Algorithm::English),
+    ("de", Lang::Deu, Algorithm::German),
+    ("fr", Lang::Fra, Algorithm::French),
+    ("es", Lang::Spa, Algorithm::Spanish),
+    ("it", Lang::Ita, Algorithm::Italian),
+    ("pt", Lang::Por, Algorithm::Portuguese),
+    ("nl", Lang::Nld, Algorithm::Dutch),
+    ("sv", Lang::Swe, Algorithm::Swedish),
+    ("da", Lang::Dan, Algorithm::Danish),
+    ("nb", Lang::Nob, Algorithm::Norwegian),
+    ("fi", Lang::Fin, Algorithm::Finnish),
+    ("hu", Lang::Hun, Algorithm::Hungarian),
+    ("ro", Lang::Ron, Algorithm::Romanian),
+    ("tr", Lang::Tur, Algorithm::Turkish),
+    ("ru", Lang::Rus, Algorithm::Russian),
+    ("el", Lang::Ell, Algorithm::Greek),
+    ("ar", Lang::Ara, Algorithm::Arabic),
+];
+
+const DEFAULT_LANGUAGE: &str = "en";
+
+// Stopword lists live in `data/stopwords/<code>.txt`. English is applied to
+// every title since technical titles mix it into other languages.
+const STOPWORDS: &[(&str, &str)] = &[
+    ("en", include_str!("../data/stopwords/en.txt")),
+    ("de", include_str!("../data/stopwords/de.txt")),
+    ("fr", include_str!("../data/stopwords/fr.txt")),
+    ("es", include_str!("../data/stopwords/es.txt")),
+    ("it", include_str!("../data/stopwords/it.txt")),
+    ("pt", include_str!("../data/stopwords/pt.txt")),
+    ("nl", include_str!("../data/stopwords/nl.txt")),
+    ("sv", include_str!("../data/stopwords/sv.txt")),
+    ("da", include_str!("../data/stopwords/da.txt")),
+    ("nb", include_str!("../data/stopwords/nb.txt")),
+    ("fi", include_str!("../data/stopwords/fi.txt")),
+    ("hu", include_str!("../data/stopwords/hu.txt")),
+    ("ro", include_str!("../data/stopwords/ro.txt")),
+    ("tr", include_str!("../data/stopwords/tr.txt")),
+    ("ru", include_str!("../data/stopwords/ru.txt")),
+    ("el", include_str!("../data/stopwords/el.txt")),
+    ("ar", include_str!("../data/stopwords/ar.txt")),
+];
+
+// Short terms kept as keywords even though they fall under the minimum length.
+const ACRONYMS: &[&str] = &[
+    "ai", "api", "ar", "aws", "cpu", "css", "cv", "db", "dns", "gpu", "gpt", "iot", "js", "llm",
+    "ml", "nlp", "os", "rag", "rl", "sql", "ssh", "tls", "ui", "ux", "vr",
+];
+
+const MIN_KEYWORD_CHARS: usize = 4;
+const MIN_CJK_KEYWORD_CHARS: usize = 2;
+
+const MAX_PHRASE_WORDS: usize = 4;
+const MAX_KEYPHRASES: usize = 5;
+// Only the start of long pages is read; that is where the topic is set out.
+const MAX_SOURCE_WORDS: usize = 5_000;
+
+static DETECTOR: OnceLock<Detector> = OnceLock::new();
+static STOPWORD_SETS: OnceLock<HashMap<&'static str, HashSet<&'static str>>> = OnceLock::new();
+
+fn detector() -> &'static Detector {
+    DETECTOR.get_or_init(|| {
+        Detector::with_allowlist(LANGUAGES.iter().map(|(_, lang, _)| *lang).collect())
+    })
+}
+
+fn stopwords(language: &str) -> Option<&'static HashSet<&'static str>> {
+    STOPWORD_SETS
+        .get_or_init(|| {
+            STOPWORDS
+                .iter()
+                .map(|(code, list)| {
+                    let words = list
+                        .lines()
+                        .filter(|line| !line.starts_with('#'))
+                        .flat_map(str::split_whitespace)
+                        .collect();
+                    (*code, words)
+                })
+                .collect()
+        })
+        .get(language)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keyword {
+    // The most common spelling in the text, lower-cased.
+    pub term: String,
+    pub stem: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextSource {
+    Title,
+    Notes,
+    Page,
+}
+
+impl TextSource {
+    fn weight(self) -> f32 {
+        match self {
+            TextSource::Title => 1.0,
+            TextSource::Notes => 0.8,
+            TextSource::Page => 0.4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyphrase {
+    pub phrase: String,
+    // Each occurrence adds its source's weight, reduced by up to half the
+    // further into the text it appears.
+    pub score: f32,
+}
+
+#[derive(Default)]
+struct Candidate {
+    forms: Vec<String>,
+    score: f32,
+    // Seen in the title or notes rather than only in page text.
+    emphasized: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeywordExtractor {
+    acronyms: HashSet<String>,
+}
+
+impl Default for KeywordExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeywordExtractor {
+    pub fn new() -> Self {
+        Self {
+            acronyms: ACRONYMS.iter().map(|acronym| acronym.to_string()).collect(),
+        }
+    }
+
+    // Adds to the built-in acronym list.
+    pub fn with_acronyms<I, T>(mut self, acronyms: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.acronyms.extend(
+            acronyms
+                .into_iter()
+                .map(|acronym| acronym.as_ref().trim().to_lowercase())
+                .filter(|acronym| !acronym.is_empty()),
+        );
+        self
+    }
+
+    // A supported `hint` (a page's `lang`, e.g. `de-CH`) wins over detection;
+    // titles too short to detect reliably are treated as English.
+    pub fn detect_language(&self, text: &str, hint: Option<&str>) -> &'static str {
+        let hinted = hint
+            .and_then(|hint| hint.split(['-', '_']).next())
+            .map(|primary| primary.trim().to_lowercase())
+            .and_then(|primary| {
+                let primary = if primary == "no" { "nb".to_string() } else { primary };
+                LANGUAGES.iter().find(|(code, _, _)| *code == primary)
+            });
+        if let Some((code, _, _)) = hinted {
+            return code;
+        }
+        detector()
+            .detect(text)
+            .filter(|info| info.is_reliable())
+            .and_then(|info| LANGUAGES.iter().find(|(_, lang, _)| *lang == info.lang()))
+            .map_or(DEFAULT_LANGUAGE, |(code, _, _)| code)
+    }
+
+    pub fn stem(&self, word: &str, language: &str) -> String {
+        let word = word.to_lowercase();
+        let numeric = word.chars().all(|c| c.is_numeric());
+        if self.acronyms.contains(&word) || numeric || is_cjk(&word) {
+            return word;
+        }
+        stemmer(language).stem(&word).into_owned()
+    }
+
+    // Stems of every word, space separated; equal keys mean the same tag.
+    pub fn phrase_key(&self, phrase: &str, language: &str) -> String {
+        keyword_tokens(phrase)
+            .iter()
+            .map(|token| self.stem(&token.text, language))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // RAKE-style candidates: runs of two to four content words between
+    // stopwords, numbers and punctuation. A phrase has to show up in the
+    // title or notes, or at least twice in page text, and the best few by
+    // score are returned.
+    pub fn keyphrases(&self, sources: &[(TextSource, &str)], language: &str) -> Vec<Keyphrase> {
+        let english = stopwords(DEFAULT_LANGUAGE);
+        let local = stopwords(language);
+        let is_delimiter = |word: &str, cjk: bool| {
+            cjk || (!self.acronyms.contains(word)
+                && (word.chars().count() < 2
+                    || word.chars().all(char::is_numeric)
+                    || english.is_some_and(|set| set.contains(word))
+                    || local.is_some_and(|set| set.contains(word))))
+        };
+
+        let mut keys: Vec<String> = Vec::new();
+        let mut candidates: HashMap<String, Candidate> = HashMap::new();
+        for &(source, text) in sources {
+            let mut occurrences: Vec<(Vec<String>, usize)> = Vec::new();
+            let mut position = 0;
+            for chunk in text.split(is_phrase_break) {
+                if position == MAX_SOURCE_WORDS {
+                    break;
+                }
+                let mut run: Vec<String> = Vec::new();
+                let mut start = position;
+                for token in keyword_tokens(chunk) {
+                    if position == MAX_SOURCE_WORDS {
+                        break;
+                    }
+                    position += 1;
+                    let word = token.text.to_lowercase();
+                    if is_delimiter(&word, token.cjk) {
+                        occurrences.push((std::mem::take(&mut run), start));
+                        start = position;
+                    } else {
+                        run.push(word);
+                    }
+                }
+                occurrences.push((run, start));
+            }
+
+            let total = position.max(1) as f32;
+            for (words, start) in occurrences {
+                if words.len() < 2 || words.len() > MAX_PHRASE_WORDS {
+                    continue;
+                }
+                let key = words
+                    .iter()
+                    .map(|word| self.stem(word, language))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let candidate = candidates.entry(key.clone()).or_default();
+                if candidate.forms.is_empty() {
+                    keys.push(key);
+                }
+                candidate.forms.push(words.join(" "));
+                candidate.score += source.weight() * (1.0 - 0.5 * start as f32 / total);
+                candidate.emphasized |= source != TextSource::Page;
+            }
+        }
+
+        let mut phrases: Vec<Keyphrase> = keys
+            .into_iter()
+            .filter_map(|key| {
+                let candidate = &candidates[&key];
+                (candidate.emphasized || candidate.forms.len() >= 2).then(|| Keyphrase {
+                    phrase: surface_form(&candidate.forms),
+                    score: candidate.score,
+                })
+            })
+            .collect();
+        // Stable, so equal scores keep their order of appearance.
+        phrases.sort_by(|a, b| b.score.total_cmp(&a.score));
+        phrases.truncate(MAX_KEYPHRASES);
+        phrases
+    }
+
+    // Keywords in order of first appearance, one per stem. CJK words (see
+    // `tokenize`) need two characters and are neither stemmed nor filtered.
+    pub fn extract(&self, text: &str, language: &str) -> Vec<Keyword> {
+        let english = stopwords(DEFAULT_LANGUAGE);
+        let local = stopwords(language);
+        let is_stopword = |word: &str| {
+            english.is_some_and(|set| set.contains(word))
+                || local.is_some_and(|set| set.contains(word))
+        };
+
+        let mut stems: Vec<String> = Vec::new();
+        let mut forms: HashMap<String, Vec<String>> = HashMap::new();
+        for token in keyword_tokens(text) {
+            let word = token.text.to_lowercase();
+            let length = word.chars().count();
+            let keep = if token.cjk {
+                length >= MIN_CJK_KEYWORD_CHARS
+            } else {
+                self.acronyms.contains(&word)
+                    || (length >= MIN_KEYWORD_CHARS && !is_stopword(&word))
+            };
+            if !keep {
+                continue;
+            }
+            let stem = self.stem(&word, language);
+            let seen = forms.entry(stem.clone()).or_default();
+            if seen.is_empty() {
+                stems.push(stem);
+            }
+            seen.push(word);
+        }
+
+        stems
+            .into_iter()
+            .map(|stem| {
+                let term = surface_form(&forms[&stem]);
+                Keyword { term, stem }
+            })
+            .collect()
+    }
+}
+
+fn is_phrase_break(c: char) -> bool {
+    matches!(
+        c,
+        '.' | ',' | ';' | ':' | '!' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '"' | '|' | '/'
+            | '\n' | '\u{2013}' | '\u{2014}' | '\u{201C}' | '\u{201D}' | '\u{3001}'
+            | '\u{3002}' | '\u{FF01}' | '\u{FF0C}' | '\u{FF1A}' | '\u{FF1B}' | '\u{FF1F}'
+    )
+}
+
+fn stemmer(language: &str) -> Stemmer {
+    let algorithm = LANGUAGES
+        .iter()
+        .find(|(code, _, _)| *code == language)
+        .map_or(Algorithm::English, |(_, _, algorithm)| *algorithm);
+    Stemmer::create(algorithm)
+}
+
+// The most frequent spelling, preferring the shorter one on ties so
+// `models` and `model` settle on `model`.
+fn surface_form(forms: &[String]) -> String {
+    let mut counts: Vec<(&String, usize)> = Vec::new();
+    for form in forms {
+        match counts.iter_mut().find(|(seen, _)| *seen == form) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((form, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .min_by_key(|(form, count)| (std::cmp::Reverse(*count), form.chars().count()))
+        .map(|(form, _)| form.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(extractor: &KeywordExtractor, text: &str, language: &str) -> Vec<String> {
+        extractor
+            .extract(text, language)
+            .into_iter()
+            .map(|keyword| keyword.term)
+            .collect()
+    }
+
+    #[test]
+    fn stems_keeps_surface_forms_and_acronyms() {
+        let extractor = KeywordExtractor::new().with_acronyms(["K8s"]);
+        assert_eq!(
+            terms(&extractor, "Training models: a model for LLM training on GPU clusters", "en"),
+            vec!["training", "model", "llm", "gpu", "clusters"]
+        );
+        assert_eq!(
+            terms(&extractor, "Running K8s on the Pi with the new API", "en"),
+            vec!["running", "k8s", "api"]
+        );
+        assert_eq!(
+            terms(&extractor, "Die neuen Sprachmodelle und ihre Grenzen", "de"),
+            vec!["neuen", "sprachmodelle", "grenzen"]
+        );
+        assert_eq!(extractor.stem("Grenzen", "de"), extractor.stem("grenze", "de"));
+        assert_eq!(
+            terms(&extractor, "Это модель, который обучает нейросети", "ru"),
+            vec!["модель", "обучает", "нейросети"]
+        );
+        for (code, _, _) in LANGUAGES {
+            assert!(stopwords(code).is_some(), "no stopwords for {}", code);
+        }
+        assert_eq!(
+            terms(&extractor, "深度学习框架的比较 | Rust 入門", "en"),
+            vec!["深度", "学习", "框架", "比较", "rust", "入門"]
+        );
+    }
+
+    #[test]
+    fn scores_keyphrases_by_source_position_and_frequency() {
+        let extractor = KeywordExtractor::new();
+        let page = "Large language models: training on GPU clusters.\n\
+                    With tensor parallelism, GPU clusters scale.\n\
+                    Tensor parallelism, in short, splits layers. Pipeline stages appear once.";
+        let phrases = extractor.keyphrases(
+            &[
+                (TextSource::Title, "Scaling up Large Language Models on GPU Clusters"),
+                (TextSource::Notes, "Follow-up on the large language model."),
+                (TextSource::Page, page),
+            ],
+            "en",
+        );
+        let names: Vec<&str> = phrases.iter().map(|phrase| phrase.phrase.as_str()).collect();
+        assert_eq!(names, vec!["large language models", "gpu clusters", "tensor parallelism"]);
+        assert!(phrases.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert_eq!(
+            extractor.phrase_key("Large Language Model", "en"),
+            extractor.phrase_key("large language models", "en")
+        );
+    }
+
+    #[test]
+    fn detects_language_with_hint_and_fallback() {
+        let extractor = KeywordExtractor::new();
+        assert_eq!(extractor.detect_language("anything", Some("de-CH")), "de");
+        assert_eq!(extractor.detect_language("anything", Some("no")), "nb");
+        assert_eq!(
+            extractor.detect_language(
+                "Une introduction complète aux réseaux de neurones pour les débutants",
+                Some("xx")
+            ),
+            "fr"
+        );
+        assert_eq!(extractor.detect_language("GPU", None), DEFAULT_LANGUAGE);
+    }
+}
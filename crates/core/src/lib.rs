@@ -1,11 +1,57 @@
+mod clock;
+mod dates;
+mod domains;
 mod errors;
+mod export;
+mod fusion;
+mod html;
+mod import;
+mod keywords;
+mod maintenance;
+mod memory;
+mod metadata;
 mod models;
+mod profiles;
+mod relations;
+mod review;
+mod rules;
+mod search;
 mod storage;
+mod store;
 mod sync;
+mod tabs;
+mod tag_names;
 mod tagging;
+mod tokenize;
 
+pub use clock::{Clock, IdGenerator, ManualClock, RandomIds, SequentialIds, SystemClock};
+pub use domains::{parse_host, UrlHost};
 pub use errors::{CoreError, Result};
+pub use export::{
+    export_bibtex, export_csl_json, export_markdown_vault, export_netscape, VaultReport,
+};
+pub use fusion::{parse_calibration, FusionMethod, TagFusion};
+pub use import::{
+    import_bookmarks, import_history, parse_netscape, parse_pinboard_json, parse_pocket_csv,
+    parse_pocket_html, parse_raindrop_csv, read_browser_history, Browser, FolderMapping,
+    HistoryFilter, HistoryImportOptions, HistoryTarget, HistoryVisit, ImportAction, ImportEntry,
+    ImportFormat, ImportOptions, ImportReport, ImportedBookmark,
+};
+pub use keywords::{Keyphrase, Keyword, KeywordExtractor, TextSource};
+pub use maintenance::{MaintenanceReport, MaintenanceStep, MaintenanceTask};
+pub use memory::MemoryStore;
+pub use metadata::{extract_metadata, extract_text};
 pub use models::*;
+pub use profiles::{Profile, ProfileRegistry, ProfileSettings, DEFAULT_PROFILE};
+pub use relations::{
+    export_graph, list_neighbours, BookmarkGraph, GraphEdge, GraphNode, LinkDirection, Neighbour,
+};
+pub use review::{ReviewOutcome, ReviewSchedule};
+pub use rules::RuleSet;
+pub use search::{parse_query, run_saved_search, SavedSearchResults};
 pub use storage::SqliteStore;
+pub use store::Store;
 pub use sync::*;
-pub use tagging::TaggingRules;
+pub use tabs::{promote_tab, PromotedTab};
+pub use tag_names::{normalize_tag, MAX_TAG_CHARS};
+pub use tagging::{DomainTags, TaggingRules};
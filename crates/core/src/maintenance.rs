@@ -0,0 +1,191 @@
+use std::time::Instant;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Result;
+use crate::models::DatabaseStats;
+use crate::storage::SqliteStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MaintenanceTask {
+    IntegrityCheck,
+    Vacuum,
+    Analyze,
+    OptimizeFts,
+    Reindex,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [MaintenanceTask; 5] = [
+        MaintenanceTask::IntegrityCheck,
+        MaintenanceTask::Reindex,
+        MaintenanceTask::OptimizeFts,
+        MaintenanceTask::Vacuum,
+        MaintenanceTask::Analyze,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceTask::IntegrityCheck => "integrity-check",
+            MaintenanceTask::Vacuum => "vacuum",
+            MaintenanceTask::Analyze => "analyze",
+            MaintenanceTask::OptimizeFts => "optimize-fts",
+            MaintenanceTask::Reindex => "reindex",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub before: DatabaseStats,
+    pub after: DatabaseStats,
+    pub duration_ms: u64,
+    pub steps: Vec<MaintenanceStep>,
+}
+
+impl MaintenanceReport {
+    pub fn ok(&self) -> bool {
+        self.steps.iter().all(|step| step.ok)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStep {
+    pub task: MaintenanceTask,
+    pub ok: bool,
+    pub duration_ms: u64,
+    pub messages: Vec<String>,
+}
+
+impl SqliteStore {
+    pub fn run_maintenance(&self, tasks: &[MaintenanceTask]) -> Result<MaintenanceReport> {
+        let started = Instant::now();
+        let before = self.database_stats()?;
+        let steps = self.with_conn(|conn| {
+            let mut steps = Vec::new();
+            for task in tasks {
+                let step_started = Instant::now();
+                let messages = match task {
+                    MaintenanceTask::IntegrityCheck => integrity_check(conn)?,
+                    MaintenanceTask::Vacuum => vacuum(conn)?,
+                    MaintenanceTask::Analyze => {
+                        conn.execute_batch("ANALYZE; PRAGMA optimize;")?;
+                        Vec::new()
+                    }
+                    MaintenanceTask::OptimizeFts => optimize_fts(conn)?,
+                    MaintenanceTask::Reindex => {
+                        conn.execute_batch("REINDEX;")?;
+                        Vec::new()
+                    }
+                };
+                let ok = *task != MaintenanceTask::IntegrityCheck || messages.is_empty();
+                steps.push(MaintenanceStep {
+                    task: *task,
+                    ok,
+                    duration_ms: step_started.elapsed().as_millis() as u64,
+                    messages,
+                });
+            }
+            Ok(steps)
+        })?;
+
+        Ok(MaintenanceReport {
+            before,
+            after: self.database_stats()?,
+            duration_ms: started.elapsed().as_millis() as u64,
+            steps,
+        })
+    }
+}
+
+fn integrity_check(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut problems = Vec::new();
+    for row in rows {
+        let row = row?;
+        if row != "ok" {
+            problems.push(row);
+        }
+    }
+    Ok(problems)
+}
+
+// Databases created before auto_vacuum was enabled need one full VACUUM to
+// switch modes; after that only free pages are released.
+fn vacuum(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mode: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+    if mode == 2 {
+        // Each step of the pragma frees one page.
+        let mut stmt = conn.prepare("PRAGMA incremental_vacuum")?;
+        let mut rows = stmt.query([])?;
+        while rows.next()?.is_some() {}
+        return Ok(Vec::new());
+    }
+    conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+    Ok(vec!["switched to incremental auto_vacuum with a full VACUUM".to_string()])
+}
+
+fn optimize_fts(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND sql LIKE 'CREATE VIRTUAL TABLE%USING fts5%'",
+    )?;
+    let tables = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for table in &tables {
+        conn.execute(
+            &format!("INSERT INTO \"{table}\"(\"{table}\") VALUES ('optimize')"),
+            [],
+        )?;
+    }
+    Ok(tables
+        .into_iter()
+        .map(|table| format!("optimized {table}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BookmarkFilter, BookmarkInput, BookmarkMetadata};
+    use crate::store::Store;
+
+    #[test]
+    fn runs_all_tasks_and_releases_free_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lester.db");
+        let store = SqliteStore::new(path.to_string_lossy().to_string());
+        store.migrate().unwrap();
+
+        let workspace = store.create_workspace("Research").unwrap();
+        for idx in 0..200 {
+            store
+                .create_bookmark(BookmarkInput {
+                    workspace_id: workspace.id,
+                    url: format!("https://example.com/{idx}"),
+                    title: "x".repeat(500),
+                    notes: Some("y".repeat(500)),
+                    metadata: BookmarkMetadata::default(),
+                })
+                .unwrap();
+        }
+        store
+            .with_conn(|conn| Ok(conn.execute("DELETE FROM bookmarks", [])?))
+            .unwrap();
+        assert!(store.database_stats().unwrap().free_pages > 0);
+
+        let report = store.run_maintenance(&MaintenanceTask::ALL).unwrap();
+        assert!(report.ok());
+        assert_eq!(report.steps.len(), MaintenanceTask::ALL.len());
+        assert_eq!(report.after.free_pages, 0);
+        assert!(report.after.file_size_bytes < report.before.file_size_bytes);
+        assert!(store
+            .list_bookmarks(BookmarkFilter::default())
+            .unwrap()
+            .is_empty());
+    }
+}
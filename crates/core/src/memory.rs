@@ -0,0 +1,737 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use uuid::Uuid;
+
+use crate::clock::{Clock, IdGenerator, RandomIds, SystemClock};
+use crate::errors::{CoreError, Result};
+use crate::fusion::TagFusion;
+use crate::models::{
+    Bookmark, BookmarkFilter, BookmarkInput, BookmarkLink, BookmarkLinkInput, BookmarkPatch,
+    BookmarkTag, BookmarkTagDetail, HistoryEntry, HistoryEntryInput, JobKind, LibraryStats,
+    LinkCheck, LinkCheckInput, ReadState, SavedSearch, SavedSearchInput, Tab, TabInput, TabSet,
+    TabSetInput, Tag, TagCloudEntry, TagEvidence, TagJob, TagJobStatus, TagRule, TagRuleInput,
+    TagSuggestion, Workspace, WorkspaceBookmarkCount,
+};
+use crate::review::{due_cutoff, ReviewOutcome, ReviewSchedule};
+use crate::store::{
+    apply_patch, check_progress, matches_search_terms, metadata_matches, new_bookmark,
+    new_bookmark_link, new_link_check, new_saved_search, new_tab_set, new_tabs, new_tag_rule,
+    new_workspace, normalize_suggestions, search_fields, sort_bookmarks, Store,
+};
+use crate::tag_names::normalize_tag;
+use crate::tokenize::search_terms;
+
+#[derive(Clone)]
+pub struct MemoryStore {
+    state: Arc<Mutex<MemoryState>>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGenerator>,
+    fusion: TagFusion,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self {
+            state: Arc::default(),
+            clock: Arc::new(SystemClock),
+            ids: Arc::new(RandomIds),
+            fusion: TagFusion::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct MemoryState {
+    workspaces: Vec<Workspace>,
+    bookmarks: Vec<Bookmark>,
+    tags: Vec<Tag>,
+    bookmark_tags: Vec<BookmarkTag>,
+    tag_jobs: Vec<TagJob>,
+    history: Vec<HistoryEntry>,
+    link_checks: Vec<LinkCheck>,
+    saved_searches: Vec<SavedSearch>,
+    tab_sets: Vec<TabSet>,
+    links: Vec<BookmarkLink>,
+    tag_rules: Vec<TagRule>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_id_generator(mut self, ids: impl IdGenerator + 'static) -> Self {
+        self.ids = Arc::new(ids);
+        self
+    }
+
+    pub fn with_tag_fusion(mut self, fusion: TagFusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    fn now(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn update_in_place(&self, id: Uuid, f: impl FnOnce(&mut Bookmark)) -> Result<Bookmark> {
+        let mut state = self.state();
+        let bookmark = state
+            .bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.id == id)
+            .ok_or(CoreError::NotFound)?;
+        f(bookmark);
+        Ok(bookmark.clone())
+    }
+}
+
+impl MemoryState {
+    fn tag_name(&self, tag_id: Uuid) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|tag| tag.id == tag_id)
+            .map(|tag| tag.name.as_str())
+    }
+
+    // Newest first; checks made in the same millisecond keep their recording order.
+    fn newest_link_checks(&self, bookmark_id: Uuid) -> Vec<LinkCheck> {
+        let mut checks: Vec<LinkCheck> = self
+            .link_checks
+            .iter()
+            .rev()
+            .filter(|check| check.bookmark_id == bookmark_id)
+            .cloned()
+            .collect();
+        checks.sort_by_key(|check| std::cmp::Reverse(check.checked_at));
+        checks
+    }
+}
+
+impl Store for MemoryStore {
+    fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        let workspace = new_workspace(self.ids.new_id(), name, self.now())?;
+        self.state().workspaces.push(workspace.clone());
+        Ok(workspace)
+    }
+
+    fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+        let mut workspaces = self.state().workspaces.clone();
+        workspaces.sort_by_key(|workspace| std::cmp::Reverse(workspace.created_at));
+        Ok(workspaces)
+    }
+
+    fn create_bookmark_at(
+        &self,
+        input: BookmarkInput,
+        created_at: Option<i64>,
+        updated_at: Option<i64>,
+    ) -> Result<Bookmark> {
+        let bookmark = new_bookmark(self.ids.new_id(), self.now(), input, created_at, updated_at)?;
+        self.state().bookmarks.push(bookmark.clone());
+        Ok(bookmark)
+    }
+
+    fn list_bookmarks(&self, filter: BookmarkFilter) -> Result<Vec<Bookmark>> {
+        let state = self.state();
+        let needle = filter.query.as_deref().map(str::to_lowercase);
+        let tag = filter.tag.as_deref().map(|tag| normalize_tag(tag).unwrap_or_default());
+        let query_terms = filter.query.as_deref().map(search_terms).unwrap_or_default();
+        let mut bookmarks: Vec<Bookmark> = state
+            .bookmarks
+            .iter()
+            .filter(|bookmark| {
+                filter
+                    .workspace_id
+                    .is_none_or(|id| bookmark.workspace_id == id)
+            })
+            .filter(|bookmark| {
+                tag.as_deref().is_none_or(|tag| {
+                    state.bookmark_tags.iter().any(|bt| {
+                        bt.bookmark_id == bookmark.id && state.tag_name(bt.tag_id) == Some(tag)
+                    })
+                })
+            })
+            .filter(|bookmark| {
+                needle.as_deref().is_none_or(|needle| {
+                    let fields = search_fields(bookmark);
+                    fields
+                        .iter()
+                        .any(|value| value.to_lowercase().contains(needle))
+                        || matches_search_terms(&query_terms, &fields)
+                })
+            })
+            .filter(|bookmark| metadata_matches(&bookmark.metadata, &filter))
+            .filter(|bookmark| {
+                filter
+                    .read_state
+                    .is_none_or(|read_state| bookmark.reading.state == read_state)
+            })
+            .filter(|bookmark| {
+                filter
+                    .link_status
+                    .is_none_or(|link_status| bookmark.link_status == Some(link_status))
+            })
+            .cloned()
+            .collect();
+        bookmarks.sort_by_key(|bookmark| std::cmp::Reverse(bookmark.updated_at));
+        sort_bookmarks(&mut bookmarks, filter.sort, self.now());
+        Ok(bookmarks)
+    }
+
+    fn get_bookmark(&self, id: Uuid) -> Result<Option<Bookmark>> {
+        Ok(self
+            .state()
+            .bookmarks
+            .iter()
+            .find(|bookmark| bookmark.id == id)
+            .cloned())
+    }
+
+    fn update_bookmark(&self, id: Uuid, patch: BookmarkPatch) -> Result<Bookmark> {
+        let mut state = self.state();
+        let bookmark = state
+            .bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.id == id)
+            .ok_or(CoreError::NotFound)?;
+        let mut updated = bookmark.clone();
+        apply_patch(&mut updated, patch, self.now())?;
+        *bookmark = updated.clone();
+        Ok(updated)
+    }
+
+    fn record_visit(&self, id: Uuid, progress: Option<f32>) -> Result<Bookmark> {
+        check_progress(progress)?;
+        let now = self.now();
+        self.update_in_place(id, |bookmark| bookmark.reading.record_visit(now, progress))
+    }
+
+    fn set_read_state(
+        &self,
+        id: Uuid,
+        state: ReadState,
+        progress: Option<f32>,
+    ) -> Result<Bookmark> {
+        check_progress(progress)?;
+        self.update_in_place(id, |bookmark| bookmark.reading.set_state(state, progress))
+    }
+
+    fn schedule_review(&self, id: Uuid, due_at: i64) -> Result<Bookmark> {
+        self.update_in_place(id, |bookmark| {
+            bookmark.review = Some(ReviewSchedule::snooze(bookmark.review.take(), due_at));
+        })
+    }
+
+    fn record_review(&self, id: Uuid, outcome: ReviewOutcome) -> Result<Bookmark> {
+        let now = self.now();
+        self.update_in_place(id, |bookmark| {
+            bookmark.review = ReviewSchedule::review(bookmark.review.take(), outcome, now);
+        })
+    }
+
+    fn list_due_reviews(
+        &self,
+        workspace_id: Option<Uuid>,
+        tz_offset_minutes: i32,
+        limit: usize,
+    ) -> Result<Vec<Bookmark>> {
+        let cutoff = due_cutoff(self.now(), tz_offset_minutes)?;
+        let mut bookmarks: Vec<Bookmark> = self
+            .state()
+            .bookmarks
+            .iter()
+            .filter(|bookmark| workspace_id.is_none_or(|id| bookmark.workspace_id == id))
+            .filter(|bookmark| bookmark.reading.state != ReadState::Archived)
+            .filter(|bookmark| {
+                bookmark
+                    .review
+                    .as_ref()
+                    .is_some_and(|review| review.due_at < cutoff)
+            })
+            .cloned()
+            .collect();
+        bookmarks.sort_by_key(|bookmark| std::cmp::Reverse(bookmark.updated_at));
+        bookmarks.sort_by_key(|bookmark| bookmark.review.as_ref().map(|review| review.due_at));
+        bookmarks.truncate(limit);
+        Ok(bookmarks)
+    }
+
+    fn create_saved_search(&self, input: SavedSearchInput) -> Result<SavedSearch> {
+        let search = new_saved_search(self.ids.new_id(), input, self.now())?;
+        self.state().saved_searches.push(search.clone());
+        Ok(search)
+    }
+
+    fn list_saved_searches(&self, workspace_id: Option<Uuid>) -> Result<Vec<SavedSearch>> {
+        let mut searches: Vec<SavedSearch> = self
+            .state()
+            .saved_searches
+            .iter()
+            .filter(|search| {
+                workspace_id.is_none_or(|id| search.workspace_id.is_none_or(|pinned| pinned == id))
+            })
+            .cloned()
+            .collect();
+        searches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(searches)
+    }
+
+    fn get_saved_search(&self, id: Uuid) -> Result<Option<SavedSearch>> {
+        Ok(self
+            .state()
+            .saved_searches
+            .iter()
+            .find(|search| search.id == id)
+            .cloned())
+    }
+
+    fn mark_saved_search_viewed(&self, id: Uuid) -> Result<SavedSearch> {
+        let now = self.now();
+        let mut state = self.state();
+        let search = state
+            .saved_searches
+            .iter_mut()
+            .find(|search| search.id == id)
+            .ok_or(CoreError::NotFound)?;
+        search.last_viewed_at = Some(now);
+        Ok(search.clone())
+    }
+
+    fn delete_saved_search(&self, id: Uuid) -> Result<()> {
+        let mut state = self.state();
+        let before = state.saved_searches.len();
+        state.saved_searches.retain(|search| search.id != id);
+        if state.saved_searches.len() == before {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn save_tab_set(&self, input: TabSetInput) -> Result<TabSet> {
+        let mut tab_set = new_tab_set(self.ids.new_id(), &input, self.now())?;
+        tab_set.tabs = new_tabs(tab_set.id, input.tabs, || self.ids.new_id())?;
+        let mut state = self.state();
+        if !state
+            .workspaces
+            .iter()
+            .any(|workspace| workspace.id == tab_set.workspace_id)
+        {
+            return Err(CoreError::NotFound);
+        }
+        state.tab_sets.push(tab_set.clone());
+        Ok(tab_set)
+    }
+
+    fn replace_tabs(&self, tab_set_id: Uuid, tabs: Vec<TabInput>) -> Result<TabSet> {
+        let tabs = new_tabs(tab_set_id, tabs, || self.ids.new_id())?;
+        let now = self.now();
+        let mut state = self.state();
+        let tab_set = state
+            .tab_sets
+            .iter_mut()
+            .find(|tab_set| tab_set.id == tab_set_id)
+            .ok_or(CoreError::NotFound)?;
+        tab_set.tabs = tabs;
+        tab_set.updated_at = now;
+        Ok(tab_set.clone())
+    }
+
+    fn list_tab_sets(&self, workspace_id: Option<Uuid>) -> Result<Vec<TabSet>> {
+        let mut tab_sets: Vec<TabSet> = self
+            .state()
+            .tab_sets
+            .iter()
+            .filter(|tab_set| workspace_id.is_none_or(|id| tab_set.workspace_id == id))
+            .cloned()
+            .collect();
+        tab_sets.sort_by_key(|tab_set| std::cmp::Reverse(tab_set.updated_at));
+        Ok(tab_sets)
+    }
+
+    fn get_tab_set(&self, id: Uuid) -> Result<Option<TabSet>> {
+        Ok(self
+            .state()
+            .tab_sets
+            .iter()
+            .find(|tab_set| tab_set.id == id)
+            .cloned())
+    }
+
+    fn get_tab(&self, id: Uuid) -> Result<Option<Tab>> {
+        Ok(self
+            .state()
+            .tab_sets
+            .iter()
+            .flat_map(|tab_set| &tab_set.tabs)
+            .find(|tab| tab.id == id)
+            .cloned())
+    }
+
+    fn delete_tab_set(&self, id: Uuid) -> Result<()> {
+        let mut state = self.state();
+        let before = state.tab_sets.len();
+        state.tab_sets.retain(|tab_set| tab_set.id != id);
+        if state.tab_sets.len() == before {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn link_bookmarks(&self, input: BookmarkLinkInput) -> Result<BookmarkLink> {
+        let link = new_bookmark_link(self.ids.new_id(), input, self.now())?;
+        let mut state = self.state();
+        for id in [link.source_id, link.target_id] {
+            if !state.bookmarks.iter().any(|bookmark| bookmark.id == id) {
+                return Err(CoreError::NotFound);
+            }
+        }
+        if let Some(existing) = state.links.iter_mut().find(|existing| {
+            existing.source_id == link.source_id
+                && existing.target_id == link.target_id
+                && existing.kind == link.kind
+        }) {
+            existing.notes = link.notes;
+            return Ok(existing.clone());
+        }
+        state.links.push(link.clone());
+        Ok(link)
+    }
+
+    fn list_bookmark_links(&self, bookmark_id: Uuid) -> Result<Vec<BookmarkLink>> {
+        Ok(self
+            .state()
+            .links
+            .iter()
+            .filter(|link| link.source_id == bookmark_id || link.target_id == bookmark_id)
+            .cloned()
+            .collect())
+    }
+
+    fn list_workspace_links(&self, workspace_id: Uuid) -> Result<Vec<BookmarkLink>> {
+        let state = self.state();
+        let in_workspace = |id: Uuid| {
+            state
+                .bookmarks
+                .iter()
+                .any(|bookmark| bookmark.id == id && bookmark.workspace_id == workspace_id)
+        };
+        Ok(state
+            .links
+            .iter()
+            .filter(|link| in_workspace(link.source_id) && in_workspace(link.target_id))
+            .cloned()
+            .collect())
+    }
+
+    fn delete_bookmark_link(&self, id: Uuid) -> Result<()> {
+        let mut state = self.state();
+        let before = state.links.len();
+        state.links.retain(|link| link.id != id);
+        if state.links.len() == before {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn create_tag_rule(&self, input: TagRuleInput) -> Result<TagRule> {
+        let rule = new_tag_rule(self.ids.new_id(), input, self.now())?;
+        self.state().tag_rules.push(rule.clone());
+        Ok(rule)
+    }
+
+    fn list_tag_rules(&self) -> Result<Vec<TagRule>> {
+        let mut rules = self.state().tag_rules.clone();
+        rules.sort_by(|a, b| a.spec.name.cmp(&b.spec.name));
+        Ok(rules)
+    }
+
+    fn update_tag_rule(&self, id: Uuid, input: TagRuleInput) -> Result<TagRule> {
+        let updated = new_tag_rule(id, input, self.now())?;
+        let mut state = self.state();
+        let rule = state
+            .tag_rules
+            .iter_mut()
+            .find(|rule| rule.id == id)
+            .ok_or(CoreError::NotFound)?;
+        rule.spec = updated.spec;
+        rule.updated_at = updated.updated_at;
+        Ok(rule.clone())
+    }
+
+    fn delete_tag_rule(&self, id: Uuid) -> Result<()> {
+        let mut state = self.state();
+        let before = state.tag_rules.len();
+        state.tag_rules.retain(|rule| rule.id != id);
+        if state.tag_rules.len() == before {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn list_tags(&self) -> Result<Vec<Tag>> {
+        let mut tags = self.state().tags.clone();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    fn list_tags_for_bookmark(&self, bookmark_id: Uuid) -> Result<Vec<BookmarkTagDetail>> {
+        let state = self.state();
+        let mut tags: Vec<BookmarkTagDetail> = state
+            .bookmark_tags
+            .iter()
+            .filter(|bt| bt.bookmark_id == bookmark_id)
+            .filter_map(|bt| {
+                Some(BookmarkTagDetail {
+                    name: state.tag_name(bt.tag_id)?.to_string(),
+                    confidence: bt.confidence,
+                    source: bt.source.clone(),
+                    created_at: bt.created_at,
+                    evidence: bt.evidence.clone(),
+                })
+            })
+            .collect();
+        tags.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(tags)
+    }
+
+    fn get_tag_cloud(&self, limit: usize) -> Result<Vec<TagCloudEntry>> {
+        let state = self.state();
+        let mut totals: HashMap<&str, (usize, f32)> = HashMap::new();
+        for bt in &state.bookmark_tags {
+            if let Some(name) = state.tag_name(bt.tag_id) {
+                let entry = totals.entry(name).or_default();
+                entry.0 += 1;
+                entry.1 += bt.confidence;
+            }
+        }
+        let mut counted: Vec<(&str, usize, f32)> = totals
+            .into_iter()
+            .map(|(name, (count, sum))| (name, count, sum))
+            .collect();
+        counted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        Ok(counted
+            .into_iter()
+            .take(limit)
+            .map(|(name, _, sum)| TagCloudEntry {
+                name: name.to_string(),
+                weight: sum,
+            })
+            .collect())
+    }
+
+    fn upsert_tags_for_bookmark(
+        &self,
+        bookmark_id: Uuid,
+        suggestions: &[TagSuggestion],
+    ) -> Result<Vec<Tag>> {
+        let mut state = self.state();
+        let mut tags = Vec::new();
+        for suggestion in &normalize_suggestions(suggestions) {
+            let now = self.now();
+            let tag_id = match state.tags.iter().find(|tag| tag.name == suggestion.name) {
+                Some(tag) => tag.id,
+                None => {
+                    let tag = Tag {
+                        id: self.ids.new_id(),
+                        name: suggestion.name.clone(),
+                        created_at: now,
+                    };
+                    state.tags.push(tag.clone());
+                    tag.id
+                }
+            };
+            let mut evidence = match state
+                .bookmark_tags
+                .iter()
+                .position(|bt| bt.bookmark_id == bookmark_id && bt.tag_id == tag_id)
+            {
+                Some(index) => state.bookmark_tags.remove(index).evidence,
+                None => Vec::new(),
+            };
+            evidence.retain(|e| e.source != suggestion.source);
+            evidence.push(TagEvidence {
+                source: suggestion.source.clone(),
+                confidence: suggestion.confidence,
+                created_at: now,
+            });
+            evidence.sort_by_key(|e| e.source.as_str());
+            if let Some((confidence, source)) = self.fusion.fuse(&evidence) {
+                state.bookmark_tags.push(BookmarkTag {
+                    bookmark_id,
+                    tag_id,
+                    confidence,
+                    source,
+                    created_at: now,
+                    evidence,
+                });
+            }
+            tags.push(Tag {
+                id: tag_id,
+                name: suggestion.name.clone(),
+                created_at: now,
+            });
+        }
+        Ok(tags)
+    }
+
+    fn enqueue_job(&self, bookmark_id: Uuid, kind: JobKind) -> Result<TagJob> {
+        let now = self.now();
+        let job = TagJob {
+            id: self.ids.new_id(),
+            bookmark_id,
+            kind,
+            status: TagJobStatus::Pending,
+            attempts: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        self.state().tag_jobs.push(job.clone());
+        Ok(job)
+    }
+
+    fn fetch_pending_tag_jobs(&self, limit: usize) -> Result<Vec<TagJob>> {
+        let mut jobs: Vec<TagJob> = self
+            .state()
+            .tag_jobs
+            .iter()
+            .filter(|job| job.status == TagJobStatus::Pending)
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        jobs.truncate(limit);
+        Ok(jobs)
+    }
+
+    fn update_tag_job_status(&self, id: Uuid, status: TagJobStatus) -> Result<()> {
+        let now = self.now();
+        if let Some(job) = self.state().tag_jobs.iter_mut().find(|job| job.id == id) {
+            job.status = status;
+            job.updated_at = now;
+            job.attempts += 1;
+        }
+        Ok(())
+    }
+
+    fn record_link_check(&self, bookmark_id: Uuid, input: LinkCheckInput) -> Result<LinkCheck> {
+        let id = self.ids.new_id();
+        let now = self.now();
+        let mut state = self.state();
+        let recent = state.newest_link_checks(bookmark_id);
+        let bookmark = state
+            .bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.id == bookmark_id)
+            .ok_or(CoreError::NotFound)?;
+        let check = new_link_check(id, bookmark, input, &recent, now);
+        bookmark.link_status = Some(check.status);
+        state.link_checks.push(check.clone());
+        Ok(check)
+    }
+
+    fn list_link_checks(&self, bookmark_id: Uuid, limit: usize) -> Result<Vec<LinkCheck>> {
+        let mut checks = self.state().newest_link_checks(bookmark_id);
+        checks.truncate(limit);
+        Ok(checks)
+    }
+
+    fn upsert_history_entry(&self, input: HistoryEntryInput) -> Result<HistoryEntry> {
+        if input.url.trim().is_empty() {
+            return Err(CoreError::InvalidInput("history url is empty".to_string()));
+        }
+        let url = input.url.trim();
+        let title = input.title.trim();
+        let mut state = self.state();
+        if let Some(entry) = state
+            .history
+            .iter_mut()
+            .find(|entry| entry.workspace_id == input.workspace_id && entry.url == url)
+        {
+            if !title.is_empty() {
+                entry.title = title.to_string();
+            }
+            entry.visit_count = entry.visit_count.max(input.visit_count);
+            entry.last_visited_at = entry.last_visited_at.max(input.last_visited_at);
+            return Ok(entry.clone());
+        }
+        let entry = HistoryEntry {
+            id: self.ids.new_id(),
+            workspace_id: input.workspace_id,
+            url: url.to_string(),
+            title: title.to_string(),
+            visit_count: input.visit_count,
+            last_visited_at: input.last_visited_at,
+            created_at: self.now(),
+        };
+        state.history.push(entry.clone());
+        Ok(entry)
+    }
+
+    fn list_history(&self, workspace_id: Option<Uuid>, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let mut entries: Vec<HistoryEntry> = self
+            .state()
+            .history
+            .iter()
+            .filter(|entry| workspace_id.is_none_or(|id| entry.workspace_id == id))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_visited_at));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    fn stats(&self) -> Result<LibraryStats> {
+        let state = self.state();
+        let mut stats = LibraryStats {
+            workspaces: state.workspaces.len() as i64,
+            bookmarks: state.bookmarks.len() as i64,
+            tags: state.tags.len() as i64,
+            ..LibraryStats::default()
+        };
+
+        let mut per_workspace: Vec<WorkspaceBookmarkCount> = state
+            .workspaces
+            .iter()
+            .map(|workspace| WorkspaceBookmarkCount {
+                workspace_id: workspace.id,
+                name: workspace.name.clone(),
+                bookmarks: state
+                    .bookmarks
+                    .iter()
+                    .filter(|bookmark| bookmark.workspace_id == workspace.id)
+                    .count() as i64,
+            })
+            .collect();
+        per_workspace.sort_by(|a, b| a.name.cmp(&b.name));
+        stats.bookmarks_per_workspace = per_workspace;
+
+        for link in &state.bookmark_tags {
+            stats.tag_sources.add(&link.source, 1);
+        }
+        for job in &state.tag_jobs {
+            stats.jobs.add(&job.status, 1);
+        }
+        stats.jobs.oldest_pending_age_secs = state
+            .tag_jobs
+            .iter()
+            .filter(|job| job.status == TagJobStatus::Pending)
+            .map(|job| job.created_at)
+            .min()
+            .map(|created_at| (self.now() - created_at).max(0) / 1000);
+        Ok(stats)
+    }
+}
@@ -0,0 +1,187 @@
+use crate::dates::parse_rfc3339;
+use crate::html::{tokenize, Token};
+use crate::models::BookmarkMetadata;
+
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "noscript", "template", "svg"];
+
+pub fn extract_metadata(html: &str, page_url: &str) -> BookmarkMetadata {
+    let mut metadata = BookmarkMetadata::default();
+    let mut og_description = None;
+    let mut og_site_name = None;
+    let mut application_name = None;
+    let mut icon = None;
+    let mut shortcut_icon = None;
+    let mut word_count = 0_i64;
+    let mut skip_depth = 0_usize;
+
+    for token in tokenize(html) {
+        match &token {
+            Token::Start { name, .. } => {
+                if SKIPPED_ELEMENTS.contains(&name.as_str()) {
+                    skip_depth += 1;
+                }
+                match name.as_str() {
+                    "html" => metadata.language = token.attr("lang").map(str::to_string),
+                    "meta" => {
+                        let key = token
+                            .attr("property")
+                            .or_else(|| token.attr("name"))
+                            .or_else(|| token.attr("itemprop"))
+                            .unwrap_or_default()
+                            .to_ascii_lowercase();
+                        let Some(content) = token.attr("content").map(str::to_string) else {
+                            continue;
+                        };
+                        match key.as_str() {
+                            "description" => metadata.description = Some(content),
+                            "og:description" | "twitter:description" => {
+                                og_description.get_or_insert(content);
+                            }
+                            "og:site_name" => og_site_name = Some(content),
+                            "application-name" => application_name = Some(content),
+                            "author" | "article:author" | "dc.creator" => {
+                                metadata.author.get_or_insert(content);
+                            }
+                            "article:published_time" | "datepublished" | "dc.date" | "date" => {
+                                metadata.published_at =
+                                    metadata.published_at.or_else(|| parse_rfc3339(&content));
+                            }
+                            "og:locale" => {
+                                metadata.language.get_or_insert(content);
+                            }
+                            _ => {}
+                        }
+                    }
+                    "link" => {
+                        let rel = token.attr("rel").unwrap_or_default().to_ascii_lowercase();
+                        let href = token.attr("href").map(str::to_string);
+                        if rel.split_whitespace().any(|rel| rel == "icon") {
+                            if rel.contains("shortcut") {
+                                shortcut_icon = shortcut_icon.or(href);
+                            } else {
+                                icon = icon.or(href);
+                            }
+                        } else if rel == "apple-touch-icon" {
+                            shortcut_icon = shortcut_icon.or(href);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Token::End { name } => {
+                if SKIPPED_ELEMENTS.contains(&name.as_str()) {
+                    skip_depth = skip_depth.saturating_sub(1);
+                }
+            }
+            Token::Text(text) => {
+                if skip_depth == 0 {
+                    word_count += text
+                        .split_whitespace()
+                        .filter(|word| word.chars().any(char::is_alphanumeric))
+                        .count() as i64;
+                }
+            }
+        }
+    }
+
+    metadata.description = metadata.description.or(og_description);
+    metadata.site_name = og_site_name.or(application_name);
+    metadata.favicon_url = icon
+        .or(shortcut_icon)
+        .map(|href| resolve_url(page_url, &href))
+        .or_else(|| origin(page_url).map(|origin| format!("{}/favicon.ico", origin)));
+    if word_count > 0 {
+        metadata.word_count = Some(word_count);
+    }
+    metadata.normalized()
+}
+
+// The page's visible text, one line per text node, for keyphrase extraction.
+pub fn extract_text(html: &str) -> String {
+    let mut lines = Vec::new();
+    let mut skip_depth = 0_usize;
+    for token in tokenize(html) {
+        match token {
+            Token::Start { name, .. } if SKIPPED_ELEMENTS.contains(&name.as_str()) => {
+                skip_depth += 1;
+            }
+            Token::End { name } if SKIPPED_ELEMENTS.contains(&name.as_str()) => {
+                skip_depth = skip_depth.saturating_sub(1);
+            }
+            Token::Text(text) if skip_depth == 0 && !text.is_empty() => lines.push(text),
+            _ => {}
+        }
+    }
+    lines.join("\n")
+}
+
+fn origin(url: &str) -> Option<&str> {
+    let scheme_end = url.find("://")? + 3;
+    let host_end = url[scheme_end..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |idx| scheme_end + idx);
+    Some(&url[..host_end])
+}
+
+fn resolve_url(base: &str, href: &str) -> String {
+    let href = href.trim();
+    if href.contains("://") || href.starts_with("data:") {
+        return href.to_string();
+    }
+    if let Some(rest) = href.strip_prefix("//") {
+        let scheme = base.split("://").next().unwrap_or("https");
+        return format!("{}://{}", scheme, rest);
+    }
+    let Some(origin) = origin(base) else {
+        return href.to_string();
+    };
+    if href.starts_with('/') {
+        return format!("{}{}", origin, href);
+    }
+    let path = &base[origin.len()..];
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let dir = path.rfind('/').map_or("/", |idx| &path[..=idx]);
+    format!("{}{}{}", origin, dir, href)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_page_metadata() {
+        let html = r#"<!doctype html>
+            <html lang="en_US">
+            <head>
+              <title>Ignored title words</title>
+              <meta name="description" content="  A guide to
+                 SQLite internals ">
+              <meta property="og:site_name" content="Example Blog">
+              <meta name="author" content="Ada Lovelace">
+              <meta property="article:published_time" content="2023-11-14T22:13:20Z">
+              <link rel="icon" href="img/icon.png">
+              <script>var ignored = "not counted";</script>
+            </head>
+            <body><h1>Pages and B-trees</h1><p>SQLite stores every table &amp; index.</p></body>
+            </html>"#;
+        let metadata = extract_metadata(html, "https://blog.example.com/posts/sqlite?ref=rss");
+        assert_eq!(metadata.description.as_deref(), Some("A guide to SQLite internals"));
+        assert_eq!(metadata.site_name.as_deref(), Some("Example Blog"));
+        assert_eq!(metadata.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(metadata.published_at, Some(1_700_000_000_000));
+        assert_eq!(metadata.language.as_deref(), Some("en-us"));
+        assert_eq!(
+            metadata.favicon_url.as_deref(),
+            Some("https://blog.example.com/posts/img/icon.png")
+        );
+        assert_eq!(metadata.word_count, Some(8));
+        assert_eq!(metadata.reading_time_minutes, Some(1));
+        assert_eq!(extract_text(html), "Pages and B-trees\nSQLite stores every table & index.");
+
+        let bare = extract_metadata("<p>hi</p>", "https://example.com/a/b");
+        assert_eq!(
+            bare.favicon_url.as_deref(),
+            Some("https://example.com/favicon.ico")
+        );
+    }
+}
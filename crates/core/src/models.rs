@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::review::ReviewSchedule;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Workspace {
     pub id: Uuid,
@@ -8,6 +10,46 @@ pub struct Workspace {
     pub created_at: i64,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabSet {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub tabs: Vec<Tab>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tab {
+    pub id: Uuid,
+    pub tab_set_id: Uuid,
+    pub url: String,
+    pub title: String,
+    pub position: i64,
+    pub pinned: bool,
+    pub last_active_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabSetInput {
+    pub workspace_id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub tabs: Vec<TabInput>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TabInput {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub last_active_at: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bookmark {
     pub id: Uuid,
@@ -17,6 +59,149 @@ pub struct Bookmark {
     pub notes: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
+    // When the row was written here; `created_at` keeps an import's original
+    // date.
+    #[serde(default)]
+    pub added_at: i64,
+    #[serde(default)]
+    pub citation: Citation,
+    #[serde(default)]
+    pub metadata: BookmarkMetadata,
+    #[serde(default)]
+    pub reading: Reading,
+    #[serde(default)]
+    pub review: Option<ReviewSchedule>,
+    #[serde(default)]
+    pub link_status: Option<LinkStatus>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadState {
+    #[default]
+    Unread,
+    Reading,
+    Read,
+    Archived,
+}
+
+impl ReadState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadState::Unread => "unread",
+            ReadState::Reading => "reading",
+            ReadState::Read => "read",
+            ReadState::Archived => "archived",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "reading" => ReadState::Reading,
+            "read" => ReadState::Read,
+            "archived" => ReadState::Archived,
+            _ => ReadState::Unread,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reading {
+    pub state: ReadState,
+    pub progress: f32,
+    pub visit_count: i64,
+    pub last_visited_at: Option<i64>,
+}
+
+impl Reading {
+    pub fn record_visit(&mut self, now: i64, progress: Option<f32>) {
+        self.visit_count += 1;
+        self.last_visited_at = Some(now);
+        if let Some(progress) = progress {
+            self.progress = progress.clamp(0.0, 1.0);
+        }
+        if self.state == ReadState::Unread {
+            self.state = ReadState::Reading;
+        }
+        if self.progress >= 1.0 && self.state == ReadState::Reading {
+            self.state = ReadState::Read;
+        }
+    }
+
+    pub fn set_state(&mut self, state: ReadState, progress: Option<f32>) {
+        self.state = state;
+        self.progress = match (state, progress) {
+            (_, Some(progress)) => progress.clamp(0.0, 1.0),
+            (ReadState::Unread, None) => 0.0,
+            (ReadState::Read, None) => 1.0,
+            (_, None) => self.progress,
+        };
+    }
+
+    // Visit count weighted by how recently the bookmark was last visited,
+    // in the spirit of Firefox's frecency buckets.
+    pub fn frecency(&self, now: i64) -> f64 {
+        let Some(last_visited_at) = self.last_visited_at else {
+            return 0.0;
+        };
+        let age_days = (now - last_visited_at).max(0) / 86_400_000;
+        let weight = match age_days {
+            0..=3 => 100.0,
+            4..=13 => 70.0,
+            14..=30 => 50.0,
+            31..=90 => 30.0,
+            _ => 10.0,
+        };
+        self.visit_count as f64 * weight
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookmarkSort {
+    #[default]
+    Updated,
+    Frecency,
+    LastVisited,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub venue: Option<String>,
+    pub doi: Option<String>,
+    pub year: Option<i32>,
+}
+
+impl Citation {
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty() && self.venue.is_none() && self.doi.is_none() && self.year.is_none()
+    }
+
+    pub fn normalized(self) -> Self {
+        let clean = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Citation {
+            authors: self
+                .authors
+                .into_iter()
+                .map(|author| author.trim().to_string())
+                .filter(|author| !author.is_empty())
+                .collect(),
+            venue: clean(self.venue),
+            doi: clean(self.doi).map(|doi| {
+                doi.trim_start_matches("https://doi.org/")
+                    .trim_start_matches("doi:")
+                    .to_string()
+            }),
+            year: self.year,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,9 +215,126 @@ pub struct Tag {
 pub struct BookmarkTag {
     pub bookmark_id: Uuid,
     pub tag_id: Uuid,
+    // Fused from `evidence`; `source` is the one that contributed most.
     pub confidence: f32,
     pub source: TagSource,
     pub created_at: i64,
+    pub evidence: Vec<TagEvidence>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkTagDetail {
+    pub name: String,
+    pub confidence: f32,
+    pub source: TagSource,
+    pub created_at: i64,
+    pub evidence: Vec<TagEvidence>,
+}
+
+// The latest confidence one source gave a bookmark tag, before calibration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagEvidence {
+    pub source: TagSource,
+    pub confidence: f32,
+    pub created_at: i64,
+}
+
+// Every condition that is set must match; `tags` are then applied with
+// `confidence`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRuleInput {
+    pub name: String,
+    // Glob over the whole URL; `*` matches any run of characters.
+    #[serde(default)]
+    pub url_pattern: Option<String>,
+    // Matches the host and its subdomains.
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub title_regex: Option<String>,
+    #[serde(default)]
+    pub has_tags: Vec<String>,
+    pub tags: Vec<String>,
+    #[serde(default = "default_rule_confidence")]
+    pub confidence: f32,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_rule_confidence() -> f32 {
+    0.8
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRule {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub spec: TagRuleInput,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BookmarkMetadata {
+    pub description: Option<String>,
+    pub favicon_url: Option<String>,
+    pub site_name: Option<String>,
+    pub author: Option<String>,
+    pub published_at: Option<i64>,
+    pub language: Option<String>,
+    pub word_count: Option<i64>,
+    pub reading_time_minutes: Option<i64>,
+}
+
+const WORDS_PER_MINUTE: i64 = 230;
+
+impl BookmarkMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == BookmarkMetadata::default()
+    }
+
+    pub fn normalized(self) -> Self {
+        let clean = |value: Option<String>| {
+            value
+                .map(|value| value.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|value| !value.is_empty())
+        };
+        let word_count = self.word_count.filter(|count| *count >= 0);
+        BookmarkMetadata {
+            description: clean(self.description),
+            favicon_url: clean(self.favicon_url),
+            site_name: clean(self.site_name),
+            author: clean(self.author),
+            published_at: self.published_at,
+            language: clean(self.language)
+                .map(|language| language.to_lowercase().replace('_', "-")),
+            word_count,
+            reading_time_minutes: self
+                .reading_time_minutes
+                .filter(|minutes| *minutes >= 0)
+                .or_else(|| {
+                    word_count
+                        .map(|count| ((count + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE).max(1))
+                }),
+        }
+    }
+
+    pub fn fill_missing(self, other: BookmarkMetadata) -> Self {
+        BookmarkMetadata {
+            description: self.description.or(other.description),
+            favicon_url: self.favicon_url.or(other.favicon_url),
+            site_name: self.site_name.or(other.site_name),
+            author: self.author.or(other.author),
+            published_at: self.published_at.or(other.published_at),
+            language: self.language.or(other.language),
+            word_count: self.word_count.or(other.word_count),
+            reading_time_minutes: self.reading_time_minutes.or(other.reading_time_minutes),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +343,17 @@ pub struct BookmarkInput {
     pub url: String,
     pub title: String,
     pub notes: Option<String>,
+    #[serde(default)]
+    pub metadata: BookmarkMetadata,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkPatch {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    pub citation: Option<Citation>,
+    pub metadata: Option<BookmarkMetadata>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,10 +368,11 @@ pub struct TagSuggestion {
     pub source: TagSource,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TagSource {
     Rules,
     Llm,
+    User,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +385,8 @@ pub struct TagCloudEntry {
 pub struct TagJob {
     pub id: Uuid,
     pub bookmark_id: Uuid,
+    #[serde(default)]
+    pub kind: JobKind,
     pub status: TagJobStatus,
     pub attempts: i32,
     pub created_at: i64,
@@ -95,6 +411,7 @@ impl TagJobStatus {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(value: &str) -> Self {
         match value {
             "running" => TagJobStatus::Running,
@@ -105,9 +422,281 @@ impl TagJobStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    #[default]
+    Tag,
+    LinkCheck,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::Tag => "tag",
+            JobKind::LinkCheck => "link_check",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "link_check" => JobKind::LinkCheck,
+            _ => JobKind::Tag,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    Ok,
+    Moved,
+    Unreachable,
+    Broken,
+}
+
+impl LinkStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkStatus::Ok => "ok",
+            LinkStatus::Moved => "moved",
+            LinkStatus::Unreachable => "unreachable",
+            LinkStatus::Broken => "broken",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "moved" => LinkStatus::Moved,
+            "unreachable" => LinkStatus::Unreachable,
+            "broken" => LinkStatus::Broken,
+            _ => LinkStatus::Ok,
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self, LinkStatus::Unreachable | LinkStatus::Broken)
+    }
+
+    // 401, 403 and 429 mean the server is there but won't talk to us, which
+    // says nothing about whether the page still exists. Timeouts, dropped
+    // connections and 5xx are often a bad moment for the server, so they only
+    // count as Unreachable; `new_link_check` turns repeated ones into Broken.
+    pub fn classify(url: &str, check: &LinkCheckInput) -> Self {
+        let Some(code) = check.status_code else {
+            return LinkStatus::Unreachable;
+        };
+        if code >= 500 {
+            return LinkStatus::Unreachable;
+        }
+        if code >= 400 && !matches!(code, 401 | 403 | 429) {
+            return LinkStatus::Broken;
+        }
+        match check.final_url.as_deref() {
+            Some(final_url) if final_url.trim_end_matches('/') != url.trim_end_matches('/') => {
+                LinkStatus::Moved
+            }
+            _ => LinkStatus::Ok,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    Cites,
+    Related,
+    Supersedes,
+    Contradicts,
+}
+
+impl RelationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationKind::Cites => "cites",
+            RelationKind::Related => "related",
+            RelationKind::Supersedes => "supersedes",
+            RelationKind::Contradicts => "contradicts",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "cites" => RelationKind::Cites,
+            "supersedes" => RelationKind::Supersedes,
+            "contradicts" => RelationKind::Contradicts,
+            _ => RelationKind::Related,
+        }
+    }
+}
+
+// A directed edge: `source_id` cites / supersedes / contradicts `target_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookmarkLink {
+    pub id: Uuid,
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+    pub kind: RelationKind,
+    pub notes: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkLinkInput {
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+    pub kind: RelationKind,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkCheckInput {
+    pub status_code: Option<i64>,
+    pub final_url: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkCheck {
+    pub id: Uuid,
+    pub bookmark_id: Uuid,
+    pub status: LinkStatus,
+    pub status_code: Option<i64>,
+    pub final_url: Option<String>,
+    pub error: Option<String>,
+    pub checked_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub url: String,
+    pub title: String,
+    pub visit_count: i64,
+    pub last_visited_at: i64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntryInput {
+    pub workspace_id: Uuid,
+    pub url: String,
+    pub title: String,
+    pub visit_count: i64,
+    pub last_visited_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct BookmarkFilter {
     pub workspace_id: Option<Uuid>,
     pub tag: Option<String>,
     pub query: Option<String>,
+    pub language: Option<String>,
+    pub site_name: Option<String>,
+    pub author: Option<String>,
+    pub max_reading_time: Option<i64>,
+    pub read_state: Option<ReadState>,
+    pub link_status: Option<LinkStatus>,
+    pub sort: BookmarkSort,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    pub name: String,
+    pub workspace_id: Option<Uuid>,
+    pub query: Option<String>,
+    pub filter: BookmarkFilter,
+    pub created_at: i64,
+    pub last_viewed_at: Option<i64>,
+}
+
+impl SavedSearch {
+    pub fn effective_filter(&self) -> BookmarkFilter {
+        let mut filter = self.filter.clone();
+        if self.workspace_id.is_some() {
+            filter.workspace_id = self.workspace_id;
+        }
+        filter
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedSearchInput {
+    pub name: String,
+    #[serde(default)]
+    pub workspace_id: Option<Uuid>,
+    #[serde(default)]
+    pub query: Option<String>,
+    #[serde(default)]
+    pub filter: Option<BookmarkFilter>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryStats {
+    pub workspaces: i64,
+    pub bookmarks: i64,
+    pub tags: i64,
+    pub bookmarks_per_workspace: Vec<WorkspaceBookmarkCount>,
+    pub tag_sources: TagSourceCounts,
+    pub jobs: JobQueueStats,
+    pub database: Option<DatabaseStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceBookmarkCount {
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub bookmarks: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagSourceCounts {
+    pub rules: i64,
+    pub llm: i64,
+    pub user: i64,
+}
+
+impl TagSourceCounts {
+    pub(crate) fn add(&mut self, source: &TagSource, count: i64) {
+        match source {
+            TagSource::Rules => self.rules += count,
+            TagSource::Llm => self.llm += count,
+            TagSource::User => self.user += count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobQueueStats {
+    pub pending: i64,
+    pub running: i64,
+    pub done: i64,
+    pub failed: i64,
+    pub oldest_pending_age_secs: Option<i64>,
+}
+
+impl JobQueueStats {
+    pub(crate) fn add(&mut self, status: &TagJobStatus, count: i64) {
+        match status {
+            TagJobStatus::Pending => self.pending += count,
+            TagJobStatus::Running => self.running += count,
+            TagJobStatus::Done => self.done += count,
+            TagJobStatus::Failed => self.failed += count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    pub file_size_bytes: u64,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub free_pages: i64,
 }
@@ -0,0 +1,306 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{CoreError, Result};
+use crate::fusion::TagFusion;
+use crate::storage::SqliteStore;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+const REGISTRY_FILE: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+const DB_FILE: &str = "lester.db";
+const DEFAULT_DATA_DIR: &str = "lester-data";
+const SETTINGS_FILE: &str = "settings.json";
+const MAX_NAME_LEN: usize = 64;
+
+// Free-form per-profile preferences; the UI decides what goes in here.
+pub type ProfileSettings = serde_json::Map<String, serde_json::Value>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub db_path: String,
+    pub active: bool,
+    pub settings: ProfileSettings,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    active: Option<String>,
+}
+
+// Layout under the data directory:
+//   profiles.json                      {"active": "<name>"}
+//   profiles/<name>/lester.db
+//   profiles/<name>/settings.json
+#[derive(Debug, Clone)]
+pub struct ProfileRegistry {
+    data_dir: PathBuf,
+    fusion: TagFusion,
+}
+
+impl ProfileRegistry {
+    // Creates the data directory and the `default` profile on first use. A
+    // `lester.db` from before profiles existed becomes the `default` profile's
+    // database; see `legacy_db`.
+    pub fn open(data_dir: impl Into<PathBuf>) -> Result<Self> {
+        let registry = Self {
+            data_dir: data_dir.into(),
+            fusion: TagFusion::default(),
+        };
+        fs::create_dir_all(registry.data_dir.join(PROFILES_DIR))?;
+        if !registry.profile_dir(DEFAULT_PROFILE).exists() {
+            registry.init_profile(DEFAULT_PROFILE, registry.legacy_db().as_deref())?;
+        }
+        Ok(registry)
+    }
+
+    // Applied to every store the registry opens.
+    pub fn with_tag_fusion(mut self, fusion: TagFusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn list(&self) -> Result<Vec<Profile>> {
+        let active = self.active_name()?;
+        let mut names = Vec::new();
+        for entry in fs::read_dir(self.data_dir.join(PROFILES_DIR))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.file_type()?.is_dir() && validate_name(&name).is_ok() {
+                names.push(name);
+            }
+        }
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| self.load(&name, &active))
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<Profile>> {
+        if validate_name(name).is_err() || !self.profile_dir(name).is_dir() {
+            return Ok(None);
+        }
+        Ok(Some(self.load(name, &self.active_name()?)?))
+    }
+
+    pub fn create(&self, name: &str) -> Result<Profile> {
+        validate_name(name)?;
+        let dir = self.profile_dir(name);
+        if dir.exists() {
+            return Err(CoreError::InvalidInput(format!("profile {} already exists", name)));
+        }
+        self.init_profile(name, None)
+    }
+
+    // The active profile, falling back to `default` when the recorded one is
+    // gone.
+    pub fn active(&self) -> Result<Profile> {
+        let active = self.active_name()?;
+        self.load(&active, &active)
+    }
+
+    pub fn switch(&self, name: &str) -> Result<Profile> {
+        self.get(name)?.ok_or(CoreError::NotFound)?;
+        write_json(
+            &self.data_dir.join(REGISTRY_FILE),
+            &RegistryFile {
+                active: Some(name.to_string()),
+            },
+        )?;
+        self.load(name, name)
+    }
+
+    pub fn update_settings(&self, name: &str, settings: ProfileSettings) -> Result<Profile> {
+        self.get(name)?.ok_or(CoreError::NotFound)?;
+        write_json(&self.profile_dir(name).join(SETTINGS_FILE), &settings)?;
+        self.load(name, &self.active_name()?)
+    }
+
+    // A migrated store for the profile's database.
+    pub fn store(&self, name: &str) -> Result<SqliteStore> {
+        self.get(name)?.ok_or(CoreError::NotFound)?;
+        let store = SqliteStore::new(self.db_path(name)).with_tag_fusion(self.fusion);
+        store.migrate()?;
+        Ok(store)
+    }
+
+    fn init_profile(&self, name: &str, adopt: Option<&Path>) -> Result<Profile> {
+        let dir = self.profile_dir(name);
+        fs::create_dir_all(&dir)?;
+        if let Some(legacy) = adopt {
+            move_database(legacy, &dir.join(DB_FILE))?;
+        }
+        write_json(&dir.join(SETTINGS_FILE), &ProfileSettings::new())?;
+        SqliteStore::new(self.db_path(name)).migrate()?;
+        self.load(name, &self.active_name()?)
+    }
+
+    // The single database used before profiles: `lester.db` in the data
+    // directory, or next to it, where the old `./lester.db` default put it
+    // when the data directory is the default `lester-data`.
+    fn legacy_db(&self) -> Option<PathBuf> {
+        let mut candidates = vec![self.data_dir.join(DB_FILE)];
+        if self.data_dir.file_name() == Some(DEFAULT_DATA_DIR.as_ref()) {
+            candidates.extend(self.data_dir.parent().map(|parent| parent.join(DB_FILE)));
+        }
+        candidates.into_iter().find(|path| path.is_file())
+    }
+
+    fn active_name(&self) -> Result<String> {
+        let path = self.data_dir.join(REGISTRY_FILE);
+        let registry: RegistryFile = if path.exists() {
+            serde_json::from_slice(&fs::read(path)?)?
+        } else {
+            RegistryFile::default()
+        };
+        Ok(registry
+            .active
+            .filter(|name| validate_name(name).is_ok() && self.profile_dir(name).is_dir())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+    }
+
+    fn load(&self, name: &str, active: &str) -> Result<Profile> {
+        let settings_path = self.profile_dir(name).join(SETTINGS_FILE);
+        let settings = if settings_path.exists() {
+            serde_json::from_slice(&fs::read(settings_path)?)?
+        } else {
+            ProfileSettings::new()
+        };
+        Ok(Profile {
+            name: name.to_string(),
+            db_path: self.db_path(name),
+            active: name == active,
+            settings,
+        })
+    }
+
+    fn profile_dir(&self, name: &str) -> PathBuf {
+        self.data_dir.join(PROFILES_DIR).join(name)
+    }
+
+    fn db_path(&self, name: &str) -> String {
+        self.profile_dir(name).join(DB_FILE).to_string_lossy().to_string()
+    }
+}
+
+// Names double as directory names, so keep them to a portable character set.
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && !name.starts_with(['-', '_'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(CoreError::InvalidInput(format!(
+            "profile names use a-z, 0-9, '-' and '_' (up to {} characters): {}",
+            MAX_NAME_LEN, name
+        )));
+    }
+    Ok(())
+}
+
+// Moves a SQLite database together with its `-wal` and `-shm` files, copying
+// instead when a rename is not possible (e.g. across file systems).
+fn move_database(from: &Path, to: &Path) -> Result<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let (mut source, mut target) = (from.as_os_str().to_owned(), to.as_os_str().to_owned());
+        source.push(suffix);
+        target.push(suffix);
+        if !Path::new(&source).exists() {
+            continue;
+        }
+        if fs::rename(&source, &target).is_err() {
+            fs::copy(&source, &target)?;
+        }
+    }
+    Ok(())
+}
+
+// Write to a sibling temp file first so a crash never leaves half a file.
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+
+    #[test]
+    fn creates_switches_and_isolates_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = ProfileRegistry::open(dir.path()).unwrap();
+        assert_eq!(registry.active().unwrap().name, DEFAULT_PROFILE);
+
+        let work = registry.create("work").unwrap();
+        assert!(!work.active);
+        assert!(matches!(registry.create("work"), Err(CoreError::InvalidInput(_))));
+        assert!(matches!(registry.create("../etc"), Err(CoreError::InvalidInput(_))));
+        assert!(matches!(registry.switch("missing"), Err(CoreError::NotFound)));
+
+        registry
+            .store("work")
+            .unwrap()
+            .create_workspace("Research")
+            .unwrap();
+        assert!(registry.store(DEFAULT_PROFILE).unwrap().list_workspaces().unwrap().is_empty());
+
+        let mut settings = ProfileSettings::new();
+        settings.insert("theme".to_string(), serde_json::json!("dark"));
+        registry.update_settings("work", settings.clone()).unwrap();
+        assert!(registry.switch("work").unwrap().active);
+
+        let reopened = ProfileRegistry::open(dir.path()).unwrap();
+        let active = reopened.active().unwrap();
+        assert_eq!(active.name, "work");
+        assert_eq!(active.settings, settings);
+        assert_eq!(
+            reopened
+                .list()
+                .unwrap()
+                .into_iter()
+                .map(|profile| profile.name)
+                .collect::<Vec<_>>(),
+            vec!["default", "work"]
+        );
+    }
+
+    #[test]
+    fn adopts_a_database_from_before_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join(DB_FILE);
+        let store = SqliteStore::new(legacy.to_string_lossy().to_string());
+        store.migrate().unwrap();
+        store.create_workspace("Reading").unwrap();
+
+        // Next to any other data directory it belongs to someone else.
+        let registry = ProfileRegistry::open(dir.path().join("elsewhere")).unwrap();
+        assert!(registry.store(DEFAULT_PROFILE).unwrap().list_workspaces().unwrap().is_empty());
+        assert!(legacy.exists());
+
+        let registry = ProfileRegistry::open(dir.path().join("lester-data")).unwrap();
+        let workspaces = registry.store(DEFAULT_PROFILE).unwrap().list_workspaces().unwrap();
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0].name, "Reading");
+        assert!(!legacy.exists());
+
+        // Only on first use: a database that shows up later is left alone.
+        SqliteStore::new(legacy.to_string_lossy().to_string()).migrate().unwrap();
+        let registry = ProfileRegistry::open(dir.path().join("lester-data")).unwrap();
+        assert_eq!(registry.store(DEFAULT_PROFILE).unwrap().list_workspaces().unwrap().len(), 1);
+        assert!(legacy.exists());
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{CoreError, Result};
+use crate::models::{Bookmark, BookmarkFilter, BookmarkLink, RelationKind};
+use crate::store::Store;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkDirection {
+    Outgoing,
+    Incoming,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Neighbour {
+    pub link: BookmarkLink,
+    pub direction: LinkDirection,
+    pub bookmark: Bookmark,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: Uuid,
+    pub url: String,
+    pub title: String,
+    pub site_name: Option<String>,
+    pub degree: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub id: Uuid,
+    pub source: Uuid,
+    pub target: Uuid,
+    pub kind: RelationKind,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookmarkGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+// Bookmarks linked to or from `bookmark_id`, in link creation order.
+pub fn list_neighbours<S: Store>(store: &S, bookmark_id: Uuid) -> Result<Vec<Neighbour>> {
+    store.get_bookmark(bookmark_id)?.ok_or(CoreError::NotFound)?;
+    let mut neighbours = Vec::new();
+    for link in store.list_bookmark_links(bookmark_id)? {
+        let (direction, other) = if link.source_id == bookmark_id {
+            (LinkDirection::Outgoing, link.target_id)
+        } else {
+            (LinkDirection::Incoming, link.source_id)
+        };
+        if let Some(bookmark) = store.get_bookmark(other)? {
+            neighbours.push(Neighbour {
+                link,
+                direction,
+                bookmark,
+            });
+        }
+    }
+    Ok(neighbours)
+}
+
+// Nodes/edges JSON in the shape d3-force and Cytoscape expect. Every bookmark
+// in the workspace is a node; links leaving the workspace are dropped.
+pub fn export_graph<S: Store>(store: &S, workspace_id: Uuid) -> Result<BookmarkGraph> {
+    let bookmarks = store.list_bookmarks(BookmarkFilter {
+        workspace_id: Some(workspace_id),
+        ..BookmarkFilter::default()
+    })?;
+    let edges: Vec<GraphEdge> = store
+        .list_workspace_links(workspace_id)?
+        .into_iter()
+        .map(|link| GraphEdge {
+            id: link.id,
+            source: link.source_id,
+            target: link.target_id,
+            kind: link.kind,
+            notes: link.notes,
+        })
+        .collect();
+    let mut degrees: HashMap<Uuid, usize> = HashMap::new();
+    for edge in &edges {
+        *degrees.entry(edge.source).or_default() += 1;
+        *degrees.entry(edge.target).or_default() += 1;
+    }
+    let nodes = bookmarks
+        .into_iter()
+        .map(|bookmark| GraphNode {
+            degree: degrees.get(&bookmark.id).copied().unwrap_or(0),
+            id: bookmark.id,
+            url: bookmark.url,
+            title: bookmark.title,
+            site_name: bookmark.metadata.site_name,
+        })
+        .collect();
+    Ok(BookmarkGraph { nodes, edges })
+}
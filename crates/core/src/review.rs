@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{CoreError, Result};
+
+const DAY_MS: i64 = 86_400_000;
+const DEFAULT_EASE: f32 = 2.5;
+const MIN_EASE: f32 = 1.3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewSchedule {
+    pub due_at: i64,
+    pub interval_days: i64,
+    pub ease: f32,
+    pub repetitions: i64,
+    pub last_reviewed_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewOutcome {
+    Again,
+    Hard,
+    Good,
+    Easy,
+    Done,
+}
+
+impl ReviewSchedule {
+    // Moves the due date and keeps any spaced-repetition progress.
+    pub fn snooze(schedule: Option<Self>, until: i64) -> Self {
+        match schedule {
+            Some(schedule) => Self {
+                due_at: until,
+                ..schedule
+            },
+            None => Self {
+                due_at: until,
+                interval_days: 0,
+                ease: DEFAULT_EASE,
+                repetitions: 0,
+                last_reviewed_at: None,
+            },
+        }
+    }
+
+    // SM-2 style: successful reviews grow the interval by the ease factor,
+    // lapses reset it. `Done` takes the bookmark out of rotation.
+    pub fn review(schedule: Option<Self>, outcome: ReviewOutcome, now: i64) -> Option<Self> {
+        let mut schedule = Self::snooze(schedule, now);
+        let interval = schedule.interval_days.max(1) as f32;
+        let (interval_days, ease, repetitions) = match outcome {
+            ReviewOutcome::Done => return None,
+            ReviewOutcome::Again => (1, schedule.ease - 0.2, 0),
+            ReviewOutcome::Hard => (
+                (interval * 1.2).round() as i64,
+                schedule.ease - 0.15,
+                schedule.repetitions + 1,
+            ),
+            ReviewOutcome::Good | ReviewOutcome::Easy => {
+                let mut days = match schedule.repetitions {
+                    0 => 1.0,
+                    1 => 6.0,
+                    _ => interval * schedule.ease,
+                };
+                let mut ease = schedule.ease;
+                if outcome == ReviewOutcome::Easy {
+                    days *= 1.3;
+                    ease += 0.15;
+                }
+                (days.round() as i64, ease, schedule.repetitions + 1)
+            }
+        };
+        schedule.interval_days = interval_days.max(1);
+        schedule.ease = ease.max(MIN_EASE);
+        schedule.repetitions = repetitions;
+        schedule.last_reviewed_at = Some(now);
+        schedule.due_at = now + schedule.interval_days * DAY_MS;
+        Some(schedule)
+    }
+}
+
+// Real offsets run from UTC-12:00 to UTC+14:00.
+const MAX_TZ_OFFSET_MINUTES: i32 = 14 * 60;
+
+// Everything due before the end of the caller's current day counts as due
+// today; `tz_offset_minutes` is their offset east of UTC (120 for UTC+2).
+pub(crate) fn due_cutoff(now: i64, tz_offset_minutes: i32) -> Result<i64> {
+    if tz_offset_minutes.abs() > MAX_TZ_OFFSET_MINUTES {
+        return Err(CoreError::InvalidInput(format!(
+            "timezone offset out of range: {} minutes",
+            tz_offset_minutes
+        )));
+    }
+    let offset = i64::from(tz_offset_minutes) * 60_000;
+    Ok(((now + offset).div_euclid(DAY_MS) + 1) * DAY_MS - offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_interval_on_success_and_resets_on_lapse() {
+        let now = 1_700_000_000_000;
+        let first = ReviewSchedule::review(None, ReviewOutcome::Good, now).unwrap();
+        assert_eq!(first.interval_days, 1);
+        assert_eq!(first.due_at, now + DAY_MS);
+
+        let second = ReviewSchedule::review(Some(first), ReviewOutcome::Good, now).unwrap();
+        assert_eq!(second.interval_days, 6);
+        let third = ReviewSchedule::review(Some(second), ReviewOutcome::Easy, now).unwrap();
+        assert_eq!(third.interval_days, 20);
+        assert!((third.ease - 2.65).abs() < 1e-6);
+
+        let lapsed = ReviewSchedule::review(Some(third.clone()), ReviewOutcome::Again, now).unwrap();
+        assert_eq!(lapsed.interval_days, 1);
+        assert_eq!(lapsed.repetitions, 0);
+        assert_eq!(ReviewSchedule::review(Some(third.clone()), ReviewOutcome::Done, now), None);
+
+        let snoozed = ReviewSchedule::snooze(Some(third), now + 3 * DAY_MS);
+        assert_eq!(snoozed.due_at, now + 3 * DAY_MS);
+        assert_eq!(snoozed.interval_days, 20);
+
+        // 2023-11-14 22:13 UTC: still the 14th in UTC and New York, already
+        // the 15th in Tokyo.
+        assert_eq!(due_cutoff(now, 0).unwrap(), 1_700_006_400_000);
+        assert_eq!(due_cutoff(now, -300).unwrap(), 1_700_024_400_000);
+        assert_eq!(due_cutoff(now, 540).unwrap(), 1_700_060_400_000);
+        assert!(due_cutoff(now, 15 * 60).is_err());
+    }
+}
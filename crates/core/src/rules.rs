@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::domains::{normalize_domain, parse_host};
+use crate::errors::{CoreError, Result};
+use crate::models::{TagRuleInput, TagSource, TagSuggestion};
+use crate::tag_names::normalize_tag;
+
+// A set of validated rules, ready to match bookmarks.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    url_pattern: Option<Regex>,
+    domain: Option<String>,
+    title_regex: Option<Regex>,
+    has_tags: Vec<String>,
+    tags: Vec<String>,
+    confidence: f32,
+}
+
+#[derive(Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<TagRuleInput>,
+}
+
+impl RuleSet {
+    // Disabled rules are skipped; any invalid rule fails the whole set.
+    pub fn new<'a>(rules: impl IntoIterator<Item = &'a TagRuleInput>) -> Result<Self> {
+        let rules = rules
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .map(compile)
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    // `[[rule]]` tables with the same fields as the API.
+    pub fn parse_toml(text: &str) -> Result<Vec<TagRuleInput>> {
+        let file: RulesFile = toml::from_str(text)
+            .map_err(|err| CoreError::InvalidInput(format!("rules file: {}", err)))?;
+        for rule in &file.rule {
+            validate_rule(rule)?;
+        }
+        Ok(file.rule)
+    }
+
+    // When several rules emit the same tag, the highest confidence wins.
+    pub fn apply(&self, url: &str, title: &str, existing_tags: &[String]) -> Vec<TagSuggestion> {
+        let domain = parse_host(url).map(|parsed| parsed.host);
+        let mut suggestions: Vec<TagSuggestion> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for rule in &self.rules {
+            if !rule.matches(url, domain.as_deref(), title, existing_tags) {
+                continue;
+            }
+            for tag in &rule.tags {
+                match seen.get(tag) {
+                    Some(&index) => {
+                        let existing = &mut suggestions[index];
+                        existing.confidence = existing.confidence.max(rule.confidence);
+                    }
+                    None => {
+                        seen.insert(tag.clone(), suggestions.len());
+                        suggestions.push(TagSuggestion {
+                            name: tag.clone(),
+                            confidence: rule.confidence,
+                            source: TagSource::Rules,
+                        });
+                    }
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+impl CompiledRule {
+    fn matches(&self, url: &str, domain: Option<&str>, title: &str, tags: &[String]) -> bool {
+        if let Some(pattern) = &self.url_pattern {
+            if !pattern.is_match(url.trim()) {
+                return false;
+            }
+        }
+        if let Some(wanted) = &self.domain {
+            let Some(domain) = domain else {
+                return false;
+            };
+            if domain != wanted && !domain.ends_with(&format!(".{}", wanted)) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.title_regex {
+            if !regex.is_match(title) {
+                return false;
+            }
+        }
+        self.has_tags.iter().all(|wanted| {
+            tags.iter()
+                .any(|tag| normalize_tag(tag).as_deref() == Some(wanted.as_str()))
+        })
+    }
+}
+
+pub(crate) fn validate_rule(rule: &TagRuleInput) -> Result<()> {
+    compile(rule).map(|_| ())
+}
+
+fn compile(rule: &TagRuleInput) -> Result<CompiledRule> {
+    let invalid =
+        |reason: String| CoreError::InvalidInput(format!("rule {}: {}", rule.name, reason));
+    if rule.name.trim().is_empty() {
+        return Err(CoreError::InvalidInput("rule name is empty".to_string()));
+    }
+    let tags = normalized_list(&rule.tags);
+    if tags.is_empty() {
+        return Err(invalid("no tags to apply".to_string()));
+    }
+    if !(rule.confidence > 0.0 && rule.confidence <= 1.0) {
+        return Err(invalid(format!("confidence {} is outside (0, 1]", rule.confidence)));
+    }
+    let url_pattern = non_empty(&rule.url_pattern)
+        .map(|pattern| Regex::new(&glob_to_regex(pattern)))
+        .transpose()
+        .map_err(|err| invalid(format!("url pattern: {}", err)))?;
+    let title_regex = non_empty(&rule.title_regex)
+        .map(Regex::new)
+        .transpose()
+        .map_err(|err| invalid(format!("title regex: {}", err)))?;
+    let domain = non_empty(&rule.domain)
+        .map(|domain| {
+            normalize_domain(domain).ok_or_else(|| invalid(format!("invalid domain {}", domain)))
+        })
+        .transpose()?;
+    let has_tags = normalized_list(&rule.has_tags);
+    if url_pattern.is_none() && domain.is_none() && title_regex.is_none() && has_tags.is_empty() {
+        return Err(invalid(
+            "needs a url pattern, domain, title regex or existing tags".to_string(),
+        ));
+    }
+    Ok(CompiledRule {
+        url_pattern,
+        domain,
+        title_regex,
+        has_tags,
+        tags,
+        confidence: rule.confidence,
+    })
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|value| !value.is_empty())
+}
+
+// Tag names in their stored form, without duplicates.
+fn normalized_list(values: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for value in values.iter().filter_map(|value| normalize_tag(value)) {
+        if !cleaned.contains(&value) {
+            cleaned.push(value);
+        }
+    }
+    cleaned
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+    let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+    format!("(?i)^{}$", parts.join(".*"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str) -> TagRuleInput {
+        TagRuleInput {
+            name: name.to_string(),
+            url_pattern: None,
+            domain: None,
+            title_regex: None,
+            has_tags: Vec::new(),
+            tags: vec!["paper".to_string()],
+            confidence: 0.8,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn matches_every_condition_and_keeps_highest_confidence() {
+        let rules = RuleSet::parse_toml(
+            r#"
+            [[rule]]
+            name = "arxiv"
+            domain = "arxiv.org"
+            tags = ["paper", "preprint"]
+            confidence = 0.9
+
+            [[rule]]
+            name = "pdfs"
+            url_pattern = "https://*.pdf"
+            tags = ["paper"]
+            confidence = 0.7
+
+            [[rule]]
+            name = "ml surveys"
+            title_regex = "(?i)\\bsurvey\\b"
+            has_tags = ["ml"]
+            tags = ["survey"]
+
+            [[rule]]
+            name = "off"
+            domain = "example.com"
+            tags = ["never"]
+            enabled = false
+            "#,
+        )
+        .unwrap();
+        let rules = RuleSet::new(&rules).unwrap();
+
+        let tags = |url: &str, title: &str, existing: &[&str]| {
+            let existing: Vec<String> = existing.iter().map(|tag| tag.to_string()).collect();
+            rules
+                .apply(url, title, &existing)
+                .into_iter()
+                .map(|suggestion| (suggestion.name, suggestion.confidence))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            tags("https://export.arxiv.org/pdf/1.pdf", "Attention", &[]),
+            vec![("paper".to_string(), 0.9), ("preprint".to_string(), 0.9)]
+        );
+        assert_eq!(tags("https://notarxiv.org/a", "A Survey", &[]), Vec::new());
+        assert_eq!(
+            tags("https://blog.example/a", "A Survey of Transformers", &["ML"]),
+            vec![("survey".to_string(), 0.8)]
+        );
+        assert!(tags("https://example.com/", "", &[]).is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let bad_regex = TagRuleInput {
+            title_regex: Some("(unclosed".to_string()),
+            ..rule("bad regex")
+        };
+        let no_condition = rule("no condition");
+        let bad_confidence = TagRuleInput {
+            domain: Some("arxiv.org".to_string()),
+            confidence: 1.5,
+            ..rule("bad confidence")
+        };
+        for rule in [bad_regex, no_condition, bad_confidence] {
+            assert!(matches!(validate_rule(&rule), Err(CoreError::InvalidInput(_))));
+        }
+        assert!(RuleSet::parse_toml("[[rule]]\nname = 1").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{CoreError, Result};
+use crate::models::{Bookmark, BookmarkFilter, SavedSearch};
+use crate::store::Store;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearchResults {
+    pub search: SavedSearch,
+    pub total: usize,
+    pub new_count: usize,
+    pub new_bookmark_ids: Vec<Uuid>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+// `tag:ml lang:en site:"Example Blog" author:ada read:unread link:broken
+// time:10 sort:frecency attention` — anything that isn't a known `key:value`
+// pair becomes free text.
+pub fn parse_query(input: &str) -> Result<BookmarkFilter> {
+    let mut filter = BookmarkFilter::default();
+    let mut text = Vec::new();
+    for term in split_terms(input) {
+        let Some((key, value)) = term.split_once(':').filter(|(_, value)| !value.is_empty())
+        else {
+            text.push(term);
+            continue;
+        };
+        let value = value.to_string();
+        match key.to_ascii_lowercase().as_str() {
+            "tag" => set_once(&mut filter.tag, value, "tag")?,
+            "lang" | "language" => set_once(&mut filter.language, value, "lang")?,
+            "site" => set_once(&mut filter.site_name, value, "site")?,
+            "author" => set_once(&mut filter.author, value, "author")?,
+            "read" => filter.read_state = Some(parse_value(&value, "read")?),
+            "link" => filter.link_status = Some(parse_value(&value, "link")?),
+            "sort" => filter.sort = parse_value(&value, "sort")?,
+            "time" => {
+                filter.max_reading_time = Some(value.parse().map_err(|_| {
+                    CoreError::InvalidInput(format!("time expects minutes, got {}", value))
+                })?)
+            }
+            _ => text.push(term),
+        }
+    }
+    if !text.is_empty() {
+        filter.query = Some(text.join(" "));
+    }
+    Ok(filter)
+}
+
+// Runs the search, reports which results were added since the last view and
+// records this view.
+pub fn run_saved_search<S: Store>(store: &S, id: Uuid) -> Result<SavedSearchResults> {
+    let search = store.get_saved_search(id)?.ok_or(CoreError::NotFound)?;
+    let bookmarks = store.list_bookmarks(search.effective_filter())?;
+    let new_bookmark_ids: Vec<Uuid> = bookmarks
+        .iter()
+        .filter(|bookmark| {
+            search
+                .last_viewed_at
+                .is_none_or(|viewed| bookmark.added_at > viewed)
+        })
+        .map(|bookmark| bookmark.id)
+        .collect();
+    let search = store.mark_saved_search_viewed(id)?;
+    Ok(SavedSearchResults {
+        search,
+        total: bookmarks.len(),
+        new_count: new_bookmark_ids.len(),
+        new_bookmark_ids,
+        bookmarks,
+    })
+}
+
+fn split_terms(input: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+fn set_once(slot: &mut Option<String>, value: String, key: &str) -> Result<()> {
+    if slot.replace(value).is_some() {
+        return Err(CoreError::InvalidInput(format!("{} can only be given once", key)));
+    }
+    Ok(())
+}
+
+fn parse_value<T: serde::de::DeserializeOwned>(value: &str, key: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_ascii_lowercase()))
+        .map_err(|_| CoreError::InvalidInput(format!("unknown {} value: {}", key, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BookmarkSort, LinkStatus, ReadState};
+
+    #[test]
+    fn parses_query_language() {
+        let filter = parse_query(
+            r#"tag:ml site:"Example Blog" read:unread link:broken time:10 sort:frecency attention heads"#,
+        )
+        .unwrap();
+        assert_eq!(filter.tag.as_deref(), Some("ml"));
+        assert_eq!(filter.site_name.as_deref(), Some("Example Blog"));
+        assert_eq!(filter.read_state, Some(ReadState::Unread));
+        assert_eq!(filter.link_status, Some(LinkStatus::Broken));
+        assert_eq!(filter.max_reading_time, Some(10));
+        assert_eq!(filter.sort, BookmarkSort::Frecency);
+        assert_eq!(filter.query.as_deref(), Some("attention heads"));
+
+        assert_eq!(
+            parse_query("https://example.com/a").unwrap().query.as_deref(),
+            Some("https://example.com/a")
+        );
+        assert!(parse_query("read:later").is_err());
+        assert!(parse_query("tag:a tag:b").is_err());
+    }
+}
@@ -1,27 +1,96 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
-use rusqlite::{params, types::Value, Connection, OptionalExtension};
+use rusqlite::{params, types::Value, Connection, OptionalExtension, TransactionBehavior};
 use uuid::Uuid;
 
+use crate::clock::{Clock, IdGenerator, RandomIds, SystemClock};
 use crate::errors::{CoreError, Result};
+use crate::fusion::TagFusion;
 use crate::models::{
-    Bookmark, BookmarkFilter, BookmarkInput, Tag, TagCloudEntry, TagJob, TagJobStatus,
-    TagSuggestion, TagSource, Workspace,
+    Bookmark, BookmarkFilter, BookmarkInput, BookmarkLink, BookmarkLinkInput, BookmarkMetadata,
+    BookmarkPatch, BookmarkTagDetail, Citation, DatabaseStats, HistoryEntry, HistoryEntryInput,
+    JobKind, LibraryStats, LinkCheck, LinkCheckInput, LinkStatus, ReadState, Reading,
+    RelationKind, SavedSearch, SavedSearchInput, Tab, TabInput, TabSet, TabSetInput, Tag,
+    TagCloudEntry, TagEvidence, TagJob, TagJobStatus, TagRule, TagRuleInput, TagSuggestion,
+    TagSource, Workspace, WorkspaceBookmarkCount,
 };
+use crate::review::{due_cutoff, ReviewOutcome, ReviewSchedule};
+use crate::store::{
+    apply_patch, check_progress, new_bookmark, new_bookmark_link, new_link_check, new_saved_search,
+    new_tab_set, new_tabs, new_tag_rule, new_workspace, normalize_suggestions, search_fields,
+    sort_bookmarks, Store, FAILED_CHECKS_BEFORE_BROKEN,
+};
+use crate::tag_names::normalize_tag;
+use crate::tokenize::search_terms;
+
+const BOOKMARK_COLUMNS: &str = "b.id, b.workspace_id, b.url, b.title, b.notes, b.created_at, \
+     b.updated_at, b.authors, b.venue, b.doi, b.year, b.description, b.favicon_url, b.site_name, \
+     b.author, b.published_at, b.language, b.word_count, b.reading_time_minutes, b.read_state, \
+     b.progress, b.visit_count, b.last_visited_at, b.review_due_at, b.review_interval_days, \
+     b.review_ease, b.review_repetitions, b.last_reviewed_at, b.link_status, b.added_at";
+
+type Migration = fn(&Connection) -> rusqlite::Result<()>;
+
+// Each entry upgrades the schema by one `user_version`. Append only.
+const MIGRATIONS: &[Migration] = &[
+    add_citation_columns,
+    timestamps_to_millis,
+    add_metadata_columns,
+    add_reading_columns,
+    add_review_columns,
+    add_link_checks,
+    add_saved_searches,
+    add_tab_sets,
+    add_bookmark_links,
+    add_tag_rules,
+    add_search_index,
+    normalize_tag_names,
+    add_tag_evidence,
+    add_bookmark_added_at,
+];
 
 #[derive(Clone)]
 pub struct SqliteStore {
     path: String,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGenerator>,
+    fusion: TagFusion,
 }
 
 impl SqliteStore {
     pub fn new(path: impl Into<String>) -> Self {
-        Self { path: path.into() }
+        Self {
+            path: path.into(),
+            clock: Arc::new(SystemClock),
+            ids: Arc::new(RandomIds),
+            fusion: TagFusion::default(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_id_generator(mut self, ids: impl IdGenerator + 'static) -> Self {
+        self.ids = Arc::new(ids);
+        self
+    }
+
+    pub fn with_tag_fusion(mut self, fusion: TagFusion) -> Self {
+        self.fusion = fusion;
+        self
+    }
+
+    fn now(&self) -> i64 {
+        self.clock.now_ms()
     }
 
     pub fn migrate(&self) -> Result<()> {
         self.with_conn(|conn| {
-            conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+            conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA auto_vacuum = INCREMENTAL;")?;
             conn.execute_batch(
                 "CREATE TABLE IF NOT EXISTS workspaces (
                     id TEXT PRIMARY KEY,
@@ -57,21 +126,74 @@ impl SqliteStore {
                     attempts INTEGER NOT NULL,
                     created_at INTEGER NOT NULL,
                     updated_at INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS history_entries (
+                    id TEXT PRIMARY KEY,
+                    workspace_id TEXT NOT NULL,
+                    url TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    visit_count INTEGER NOT NULL,
+                    last_visited_at INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    UNIQUE (workspace_id, url)
                 );",
             )?;
+
+            let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+            for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+                let tx = conn.transaction()?;
+                migration(&tx)?;
+                tx.pragma_update(None, "user_version", (idx + 1) as i64)?;
+                tx.commit()?;
+            }
             Ok(())
         })
     }
 
-    pub fn create_workspace(&self, name: &str) -> Result<Workspace> {
-        if name.trim().is_empty() {
-            return Err(CoreError::InvalidInput("workspace name is empty".to_string()));
-        }
-        let workspace = Workspace {
-            id: Uuid::new_v4(),
-            name: name.trim().to_string(),
-            created_at: now_ts(),
-        };
+    pub fn database_stats(&self) -> Result<DatabaseStats> {
+        let mut database = self.with_conn(|conn| {
+            let pragma = |name: &str| -> rusqlite::Result<i64> {
+                conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+            };
+            Ok(DatabaseStats {
+                file_size_bytes: 0,
+                page_size: pragma("page_size")?,
+                page_count: pragma("page_count")?,
+                free_pages: pragma("freelist_count")?,
+            })
+        })?;
+        database.file_size_bytes = std::fs::metadata(&self.path)?.len();
+        Ok(database)
+    }
+
+    pub(crate) fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        let mut conn = Connection::open(Path::new(&self.path))?;
+        f(&mut conn)
+    }
+
+    // Reads the row, changes it and writes it back under one write lock, so
+    // concurrent read-modify-write calls (two visits, a snooze and a review)
+    // cannot undo each other.
+    fn modify_bookmark(
+        &self,
+        id: Uuid,
+        modify: impl FnOnce(&mut Bookmark),
+        save: fn(&Connection, &Bookmark) -> rusqlite::Result<usize>,
+    ) -> Result<Bookmark> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let mut bookmark = read_bookmark(&tx, id)?.ok_or(CoreError::NotFound)?;
+            modify(&mut bookmark);
+            save(&tx, &bookmark)?;
+            tx.commit()?;
+            Ok(bookmark)
+        })
+    }
+}
+
+impl Store for SqliteStore {
+    fn create_workspace(&self, name: &str) -> Result<Workspace> {
+        let workspace = new_workspace(self.ids.new_id(), name, self.now())?;
         self.with_conn(|conn| {
             conn.execute(
                 "INSERT INTO workspaces (id, name, created_at) VALUES (?1, ?2, ?3)",
@@ -81,7 +203,7 @@ impl SqliteStore {
         })
     }
 
-    pub fn list_workspaces(&self) -> Result<Vec<Workspace>> {
+    fn list_workspaces(&self) -> Result<Vec<Workspace>> {
         self.with_conn(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT id, name, created_at FROM workspaces ORDER BY created_at DESC",
@@ -101,24 +223,20 @@ impl SqliteStore {
         })
     }
 
-    pub fn create_bookmark(&self, input: BookmarkInput) -> Result<Bookmark> {
-        if input.url.trim().is_empty() || input.title.trim().is_empty() {
-            return Err(CoreError::InvalidInput("bookmark url or title is empty".to_string()));
-        }
-        let now = now_ts();
-        let bookmark = Bookmark {
-            id: Uuid::new_v4(),
-            workspace_id: input.workspace_id,
-            url: input.url.trim().to_string(),
-            title: input.title.trim().to_string(),
-            notes: input.notes,
-            created_at: now,
-            updated_at: now,
-        };
+    fn create_bookmark_at(
+        &self,
+        input: BookmarkInput,
+        created_at: Option<i64>,
+        updated_at: Option<i64>,
+    ) -> Result<Bookmark> {
+        let bookmark = new_bookmark(self.ids.new_id(), self.now(), input, created_at, updated_at)?;
         self.with_conn(|conn| {
-            conn.execute(
-                "INSERT INTO bookmarks (id, workspace_id, url, title, notes, created_at, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO bookmarks (id, workspace_id, url, title, notes, created_at, updated_at,
+                    description, favicon_url, site_name, author, published_at, language,
+                    word_count, reading_time_minutes, added_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
                 params![
                     bookmark.id.to_string(),
                     bookmark.workspace_id.to_string(),
@@ -126,23 +244,39 @@ impl SqliteStore {
                     bookmark.title,
                     bookmark.notes,
                     bookmark.created_at,
-                    bookmark.updated_at
+                    bookmark.updated_at,
+                    bookmark.metadata.description,
+                    bookmark.metadata.favicon_url,
+                    bookmark.metadata.site_name,
+                    bookmark.metadata.author,
+                    bookmark.metadata.published_at,
+                    bookmark.metadata.language,
+                    bookmark.metadata.word_count,
+                    bookmark.metadata.reading_time_minutes,
+                    bookmark.added_at
                 ],
             )?;
+            index_bookmark(&tx, &bookmark.id.to_string(), &search_fields(&bookmark))?;
+            tx.commit()?;
             Ok(bookmark)
         })
     }
 
-    pub fn list_bookmarks(&self, filter: BookmarkFilter) -> Result<Vec<Bookmark>> {
+    fn list_bookmarks(&self, filter: BookmarkFilter) -> Result<Vec<Bookmark>> {
         self.with_conn(|conn| {
-            let mut sql =
-                "SELECT DISTINCT b.id, b.workspace_id, b.url, b.title, b.notes, b.created_at, b.updated_at FROM bookmarks b"
-                    .to_string();
+            let mut sql = format!("SELECT DISTINCT {} FROM bookmarks b", BOOKMARK_COLUMNS);
 
             let BookmarkFilter {
                 workspace_id,
                 tag,
                 query,
+                language,
+                site_name,
+                author,
+                max_reading_time,
+                read_state,
+                link_status,
+                sort,
             } = filter;
 
             if tag.is_some() {
@@ -156,17 +290,63 @@ impl SqliteStore {
 
             if let Some(tag) = tag {
                 conditions.push("t.name = ?");
-                params.push(Value::from(tag));
+                params.push(Value::from(normalize_tag(&tag).unwrap_or_default()));
             }
             if let Some(workspace_id) = workspace_id {
                 conditions.push("b.workspace_id = ?");
                 params.push(Value::from(workspace_id.to_string()));
             }
             if let Some(query) = query {
+                // Substring matches keep working for URLs and partial words;
+                // the index adds word matches in any order and CJK bigrams.
                 let needle = format!("%{}%", query);
-                conditions.push("(b.title LIKE ? OR b.url LIKE ?)");
-                params.push(Value::from(needle.clone()));
-                params.push(Value::from(needle));
+                let terms = search_terms(&query);
+                if terms.is_empty() {
+                    conditions.push(
+                        "(b.title LIKE ? OR b.url LIKE ? OR b.description LIKE ? \
+                         OR b.site_name LIKE ? OR b.author LIKE ?)",
+                    );
+                } else {
+                    conditions.push(
+                        "(b.title LIKE ? OR b.url LIKE ? OR b.description LIKE ? \
+                         OR b.site_name LIKE ? OR b.author LIKE ? OR b.id IN \
+                         (SELECT bookmark_id FROM bookmark_search WHERE bookmark_search MATCH ?))",
+                    );
+                }
+                for _ in 0..5 {
+                    params.push(Value::from(needle.clone()));
+                }
+                if !terms.is_empty() {
+                    let expression: Vec<String> =
+                        terms.iter().map(|term| format!("\"{}\"*", term)).collect();
+                    params.push(Value::from(expression.join(" ")));
+                }
+            }
+            if let Some(language) = language {
+                let language = language.trim().to_lowercase();
+                conditions.push("(b.language = ? OR b.language LIKE ?)");
+                params.push(Value::from(language.clone()));
+                params.push(Value::from(format!("{}-%", language)));
+            }
+            if let Some(site_name) = site_name {
+                conditions.push("b.site_name = ? COLLATE NOCASE");
+                params.push(Value::from(site_name.trim().to_string()));
+            }
+            if let Some(author) = author {
+                conditions.push("b.author = ? COLLATE NOCASE");
+                params.push(Value::from(author.trim().to_string()));
+            }
+            if let Some(max_reading_time) = max_reading_time {
+                conditions.push("b.reading_time_minutes <= ?");
+                params.push(Value::from(max_reading_time));
+            }
+            if let Some(read_state) = read_state {
+                conditions.push("b.read_state = ?");
+                params.push(Value::from(read_state.as_str().to_string()));
+            }
+            if let Some(link_status) = link_status {
+                conditions.push("b.link_status = ?");
+                params.push(Value::from(link_status.as_str().to_string()));
             }
 
             if !conditions.is_empty() {
@@ -176,49 +356,498 @@ impl SqliteStore {
 
             sql.push_str(" ORDER BY b.updated_at DESC");
             let mut stmt = conn.prepare(&sql)?;
-            let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
-                Ok(Bookmark {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    workspace_id: parse_uuid(row.get::<_, String>(1)?),
-                    url: row.get(2)?,
-                    title: row.get(3)?,
-                    notes: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                })
-            })?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), bookmark_from_row)?;
 
             let mut bookmarks = Vec::new();
             for bookmark in rows {
                 bookmarks.push(bookmark?);
             }
+            sort_bookmarks(&mut bookmarks, sort, self.now());
             Ok(bookmarks)
         })
     }
 
-    pub fn get_bookmark(&self, id: Uuid) -> Result<Option<Bookmark>> {
+    fn get_bookmark(&self, id: Uuid) -> Result<Option<Bookmark>> {
+        self.with_conn(|conn| Ok(read_bookmark(conn, id)?))
+    }
+
+    fn update_bookmark(&self, id: Uuid, patch: BookmarkPatch) -> Result<Bookmark> {
+        self.with_conn(|conn| {
+            // Read under the write lock: every column is written back, so a
+            // row read before a concurrent update would undo that update.
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let mut bookmark = read_bookmark(&tx, id)?.ok_or(CoreError::NotFound)?;
+            apply_patch(&mut bookmark, patch, self.now())?;
+            tx.execute(
+                "UPDATE bookmarks SET url = ?1, title = ?2, notes = ?3, updated_at = ?4,
+                    authors = ?5, venue = ?6, doi = ?7, year = ?8, description = ?9,
+                    favicon_url = ?10, site_name = ?11, author = ?12, published_at = ?13,
+                    language = ?14, word_count = ?15, reading_time_minutes = ?16
+                 WHERE id = ?17",
+                params![
+                    bookmark.url,
+                    bookmark.title,
+                    bookmark.notes,
+                    bookmark.updated_at,
+                    serde_json::to_string(&bookmark.citation.authors)?,
+                    bookmark.citation.venue,
+                    bookmark.citation.doi,
+                    bookmark.citation.year,
+                    bookmark.metadata.description,
+                    bookmark.metadata.favicon_url,
+                    bookmark.metadata.site_name,
+                    bookmark.metadata.author,
+                    bookmark.metadata.published_at,
+                    bookmark.metadata.language,
+                    bookmark.metadata.word_count,
+                    bookmark.metadata.reading_time_minutes,
+                    id.to_string()
+                ],
+            )?;
+            index_bookmark(&tx, &id.to_string(), &search_fields(&bookmark))?;
+            tx.commit()?;
+            Ok(bookmark)
+        })
+    }
+
+    fn record_visit(&self, id: Uuid, progress: Option<f32>) -> Result<Bookmark> {
+        check_progress(progress)?;
+        let now = self.now();
+        self.modify_bookmark(
+            id,
+            |bookmark| bookmark.reading.record_visit(now, progress),
+            save_reading,
+        )
+    }
+
+    fn set_read_state(
+        &self,
+        id: Uuid,
+        state: ReadState,
+        progress: Option<f32>,
+    ) -> Result<Bookmark> {
+        check_progress(progress)?;
+        self.modify_bookmark(
+            id,
+            |bookmark| bookmark.reading.set_state(state, progress),
+            save_reading,
+        )
+    }
+
+    fn schedule_review(&self, id: Uuid, due_at: i64) -> Result<Bookmark> {
+        self.modify_bookmark(
+            id,
+            |bookmark| {
+                bookmark.review = Some(ReviewSchedule::snooze(bookmark.review.take(), due_at))
+            },
+            save_review,
+        )
+    }
+
+    fn record_review(&self, id: Uuid, outcome: ReviewOutcome) -> Result<Bookmark> {
+        let now = self.now();
+        self.modify_bookmark(
+            id,
+            |bookmark| {
+                bookmark.review = ReviewSchedule::review(bookmark.review.take(), outcome, now)
+            },
+            save_review,
+        )
+    }
+
+    fn list_due_reviews(
+        &self,
+        workspace_id: Option<Uuid>,
+        tz_offset_minutes: i32,
+        limit: usize,
+    ) -> Result<Vec<Bookmark>> {
+        let cutoff = due_cutoff(self.now(), tz_offset_minutes)?;
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM bookmarks b
+                 WHERE b.review_due_at < ?1 AND b.read_state != 'archived'
+                   AND (?2 IS NULL OR b.workspace_id = ?2)
+                 ORDER BY b.review_due_at ASC, b.updated_at DESC
+                 LIMIT ?3",
+                BOOKMARK_COLUMNS
+            ))?;
+            let rows = stmt.query_map(
+                params![cutoff, workspace_id.map(|id| id.to_string()), limit as i64],
+                bookmark_from_row,
+            )?;
+            let mut bookmarks = Vec::new();
+            for bookmark in rows {
+                bookmarks.push(bookmark?);
+            }
+            Ok(bookmarks)
+        })
+    }
+
+    fn create_saved_search(&self, input: SavedSearchInput) -> Result<SavedSearch> {
+        let search = new_saved_search(self.ids.new_id(), input, self.now())?;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO saved_searches (id, name, workspace_id, query, filter, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    search.id.to_string(),
+                    search.name,
+                    search.workspace_id.map(|id| id.to_string()),
+                    search.query,
+                    serde_json::to_string(&search.filter)?,
+                    search.created_at
+                ],
+            )?;
+            Ok(search)
+        })
+    }
+
+    fn list_saved_searches(&self, workspace_id: Option<Uuid>) -> Result<Vec<SavedSearch>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, name, workspace_id, query, filter, created_at, last_viewed_at
+                 FROM saved_searches
+                 WHERE ?1 IS NULL OR workspace_id IS NULL OR workspace_id = ?1
+                 ORDER BY name",
+            )?;
+            let rows = stmt.query_map(
+                params![workspace_id.map(|id| id.to_string())],
+                saved_search_from_row,
+            )?;
+            let mut searches = Vec::new();
+            for search in rows {
+                searches.push(search?);
+            }
+            Ok(searches)
+        })
+    }
+
+    fn get_saved_search(&self, id: Uuid) -> Result<Option<SavedSearch>> {
+        self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT id, name, workspace_id, query, filter, created_at, last_viewed_at
+                 FROM saved_searches WHERE id = ?1",
+                params![id.to_string()],
+                saved_search_from_row,
+            )
+            .optional()
+            .map_err(CoreError::from)
+        })
+    }
+
+    fn mark_saved_search_viewed(&self, id: Uuid) -> Result<SavedSearch> {
+        let now = self.now();
+        let updated = self.with_conn(|conn| {
+            Ok(conn.execute(
+                "UPDATE saved_searches SET last_viewed_at = ?1 WHERE id = ?2",
+                params![now, id.to_string()],
+            )?)
+        })?;
+        if updated == 0 {
+            return Err(CoreError::NotFound);
+        }
+        self.get_saved_search(id)?.ok_or(CoreError::NotFound)
+    }
+
+    fn delete_saved_search(&self, id: Uuid) -> Result<()> {
+        let deleted = self.with_conn(|conn| {
+            Ok(conn.execute(
+                "DELETE FROM saved_searches WHERE id = ?1",
+                params![id.to_string()],
+            )?)
+        })?;
+        if deleted == 0 {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn save_tab_set(&self, input: TabSetInput) -> Result<TabSet> {
+        let mut tab_set = new_tab_set(self.ids.new_id(), &input, self.now())?;
+        tab_set.tabs = new_tabs(tab_set.id, input.tabs, || self.ids.new_id())?;
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let workspace_exists: bool = tx.query_row(
+                "SELECT EXISTS (SELECT 1 FROM workspaces WHERE id = ?1)",
+                params![tab_set.workspace_id.to_string()],
+                |row| row.get(0),
+            )?;
+            if !workspace_exists {
+                return Err(CoreError::NotFound);
+            }
+            tx.execute(
+                "INSERT INTO tab_sets (id, workspace_id, name, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    tab_set.id.to_string(),
+                    tab_set.workspace_id.to_string(),
+                    tab_set.name,
+                    tab_set.created_at,
+                    tab_set.updated_at
+                ],
+            )?;
+            insert_tabs(&tx, &tab_set.tabs)?;
+            tx.commit()?;
+            Ok(tab_set)
+        })
+    }
+
+    fn replace_tabs(&self, tab_set_id: Uuid, tabs: Vec<TabInput>) -> Result<TabSet> {
+        let mut tab_set = self.get_tab_set(tab_set_id)?.ok_or(CoreError::NotFound)?;
+        tab_set.tabs = new_tabs(tab_set_id, tabs, || self.ids.new_id())?;
+        tab_set.updated_at = self.now();
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM tabs WHERE tab_set_id = ?1",
+                params![tab_set_id.to_string()],
+            )?;
+            insert_tabs(&tx, &tab_set.tabs)?;
+            tx.execute(
+                "UPDATE tab_sets SET updated_at = ?1 WHERE id = ?2",
+                params![tab_set.updated_at, tab_set_id.to_string()],
+            )?;
+            tx.commit()?;
+            Ok(tab_set)
+        })
+    }
+
+    fn list_tab_sets(&self, workspace_id: Option<Uuid>) -> Result<Vec<TabSet>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, workspace_id, name, created_at, updated_at
+                 FROM tab_sets
+                 WHERE ?1 IS NULL OR workspace_id = ?1
+                 ORDER BY updated_at DESC",
+            )?;
+            let rows = stmt.query_map(
+                params![workspace_id.map(|id| id.to_string())],
+                tab_set_from_row,
+            )?;
+            let mut tab_sets = Vec::new();
+            for tab_set in rows {
+                let mut tab_set = tab_set?;
+                load_tabs(conn, &mut tab_set)?;
+                tab_sets.push(tab_set);
+            }
+            Ok(tab_sets)
+        })
+    }
+
+    fn get_tab_set(&self, id: Uuid) -> Result<Option<TabSet>> {
+        self.with_conn(|conn| {
+            let tab_set = conn
+                .query_row(
+                    "SELECT id, workspace_id, name, created_at, updated_at
+                     FROM tab_sets WHERE id = ?1",
+                    params![id.to_string()],
+                    tab_set_from_row,
+                )
+                .optional()?;
+            let Some(mut tab_set) = tab_set else {
+                return Ok(None);
+            };
+            load_tabs(conn, &mut tab_set)?;
+            Ok(Some(tab_set))
+        })
+    }
+
+    fn get_tab(&self, id: Uuid) -> Result<Option<Tab>> {
         self.with_conn(|conn| {
             conn.query_row(
-                "SELECT id, workspace_id, url, title, notes, created_at, updated_at FROM bookmarks WHERE id = ?1",
+                "SELECT id, tab_set_id, url, title, position, pinned, last_active_at
+                 FROM tabs WHERE id = ?1",
                 params![id.to_string()],
-                |row| {
-                    Ok(Bookmark {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        workspace_id: parse_uuid(row.get::<_, String>(1)?),
-                        url: row.get(2)?,
-                        title: row.get(3)?,
-                        notes: row.get(4)?,
-                        created_at: row.get(5)?,
-                        updated_at: row.get(6)?,
-                    })
-                },
+                tab_from_row,
             )
             .optional()
             .map_err(CoreError::from)
         })
     }
 
-    pub fn list_tags(&self) -> Result<Vec<Tag>> {
+    fn delete_tab_set(&self, id: Uuid) -> Result<()> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM tabs WHERE tab_set_id = ?1", params![id.to_string()])?;
+            let deleted =
+                tx.execute("DELETE FROM tab_sets WHERE id = ?1", params![id.to_string()])?;
+            if deleted == 0 {
+                return Err(CoreError::NotFound);
+            }
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn link_bookmarks(&self, input: BookmarkLinkInput) -> Result<BookmarkLink> {
+        let link = new_bookmark_link(self.ids.new_id(), input, self.now())?;
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            for id in [link.source_id, link.target_id] {
+                let exists: bool = tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM bookmarks WHERE id = ?1)",
+                    params![id.to_string()],
+                    |row| row.get(0),
+                )?;
+                if !exists {
+                    return Err(CoreError::NotFound);
+                }
+            }
+            tx.execute(
+                "INSERT INTO bookmark_links (id, source_id, target_id, kind, notes, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (source_id, target_id, kind) DO UPDATE SET notes = excluded.notes",
+                params![
+                    link.id.to_string(),
+                    link.source_id.to_string(),
+                    link.target_id.to_string(),
+                    link.kind.as_str(),
+                    link.notes,
+                    link.created_at
+                ],
+            )?;
+            let link = tx.query_row(
+                &format!(
+                    "SELECT {} FROM bookmark_links l
+                     WHERE l.source_id = ?1 AND l.target_id = ?2 AND l.kind = ?3",
+                    BOOKMARK_LINK_COLUMNS
+                ),
+                params![
+                    link.source_id.to_string(),
+                    link.target_id.to_string(),
+                    link.kind.as_str()
+                ],
+                bookmark_link_from_row,
+            )?;
+            tx.commit()?;
+            Ok(link)
+        })
+    }
+
+    fn list_bookmark_links(&self, bookmark_id: Uuid) -> Result<Vec<BookmarkLink>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM bookmark_links l
+                 WHERE l.source_id = ?1 OR l.target_id = ?1
+                 ORDER BY l.created_at, l.rowid",
+                BOOKMARK_LINK_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![bookmark_id.to_string()], bookmark_link_from_row)?;
+            rows.collect::<rusqlite::Result<_>>().map_err(CoreError::from)
+        })
+    }
+
+    fn list_workspace_links(&self, workspace_id: Uuid) -> Result<Vec<BookmarkLink>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM bookmark_links l
+                 JOIN bookmarks s ON s.id = l.source_id
+                 JOIN bookmarks t ON t.id = l.target_id
+                 WHERE s.workspace_id = ?1 AND t.workspace_id = ?1
+                 ORDER BY l.created_at, l.rowid",
+                BOOKMARK_LINK_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![workspace_id.to_string()], bookmark_link_from_row)?;
+            rows.collect::<rusqlite::Result<_>>().map_err(CoreError::from)
+        })
+    }
+
+    fn delete_bookmark_link(&self, id: Uuid) -> Result<()> {
+        self.with_conn(|conn| {
+            let deleted =
+                conn.execute("DELETE FROM bookmark_links WHERE id = ?1", params![id.to_string()])?;
+            if deleted == 0 {
+                return Err(CoreError::NotFound);
+            }
+            Ok(())
+        })
+    }
+
+    fn create_tag_rule(&self, input: TagRuleInput) -> Result<TagRule> {
+        let rule = new_tag_rule(self.ids.new_id(), input, self.now())?;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO tag_rules (id, name, url_pattern, domain, title_regex, has_tags, tags,
+                    confidence, enabled, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    rule.id.to_string(),
+                    rule.spec.name,
+                    rule.spec.url_pattern,
+                    rule.spec.domain,
+                    rule.spec.title_regex,
+                    serde_json::to_string(&rule.spec.has_tags)?,
+                    serde_json::to_string(&rule.spec.tags)?,
+                    rule.spec.confidence as f64,
+                    rule.spec.enabled,
+                    rule.created_at,
+                    rule.updated_at
+                ],
+            )?;
+            Ok(rule)
+        })
+    }
+
+    fn list_tag_rules(&self) -> Result<Vec<TagRule>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, name, url_pattern, domain, title_regex, has_tags, tags, confidence,
+                    enabled, created_at, updated_at
+                 FROM tag_rules
+                 ORDER BY name",
+            )?;
+            let rows = stmt.query_map([], tag_rule_from_row)?;
+            rows.collect::<rusqlite::Result<_>>().map_err(CoreError::from)
+        })
+    }
+
+    fn update_tag_rule(&self, id: Uuid, input: TagRuleInput) -> Result<TagRule> {
+        let updated = new_tag_rule(id, input, self.now())?;
+        self.with_conn(|conn| {
+            let changed = conn.execute(
+                "UPDATE tag_rules
+                 SET name = ?1, url_pattern = ?2, domain = ?3, title_regex = ?4, has_tags = ?5,
+                     tags = ?6, confidence = ?7, enabled = ?8, updated_at = ?9
+                 WHERE id = ?10",
+                params![
+                    updated.spec.name,
+                    updated.spec.url_pattern,
+                    updated.spec.domain,
+                    updated.spec.title_regex,
+                    serde_json::to_string(&updated.spec.has_tags)?,
+                    serde_json::to_string(&updated.spec.tags)?,
+                    updated.spec.confidence as f64,
+                    updated.spec.enabled,
+                    updated.updated_at,
+                    id.to_string()
+                ],
+            )?;
+            if changed == 0 {
+                return Err(CoreError::NotFound);
+            }
+            conn.query_row(
+                "SELECT id, name, url_pattern, domain, title_regex, has_tags, tags, confidence,
+                    enabled, created_at, updated_at
+                 FROM tag_rules WHERE id = ?1",
+                params![id.to_string()],
+                tag_rule_from_row,
+            )
+            .map_err(CoreError::from)
+        })
+    }
+
+    fn delete_tag_rule(&self, id: Uuid) -> Result<()> {
+        self.with_conn(|conn| {
+            let deleted =
+                conn.execute("DELETE FROM tag_rules WHERE id = ?1", params![id.to_string()])?;
+            if deleted == 0 {
+                return Err(CoreError::NotFound);
+            }
+            Ok(())
+        })
+    }
+
+    fn list_tags(&self) -> Result<Vec<Tag>> {
         self.with_conn(|conn| {
             let mut stmt = conn.prepare("SELECT id, name, created_at FROM tags ORDER BY name")?;
             let rows = stmt.query_map([], |row| {
@@ -236,7 +865,38 @@ impl SqliteStore {
         })
     }
 
-    pub fn get_tag_cloud(&self, limit: usize) -> Result<Vec<TagCloudEntry>> {
+    fn list_tags_for_bookmark(&self, bookmark_id: Uuid) -> Result<Vec<BookmarkTagDetail>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.name, bt.confidence, bt.source, bt.created_at, bt.tag_id
+                 FROM bookmark_tags bt
+                 INNER JOIN tags t ON bt.tag_id = t.id
+                 WHERE bt.bookmark_id = ?1
+                 ORDER BY bt.confidence DESC, t.name",
+            )?;
+            let rows = stmt.query_map(params![bookmark_id.to_string()], |row| {
+                Ok((
+                    BookmarkTagDetail {
+                        name: row.get(0)?,
+                        confidence: row.get(1)?,
+                        source: TagSource::from_str(&row.get::<_, String>(2)?),
+                        created_at: row.get(3)?,
+                        evidence: Vec::new(),
+                    },
+                    row.get::<_, String>(4)?,
+                ))
+            })?;
+            let mut tags = Vec::new();
+            for tag in rows {
+                let (mut tag, tag_id) = tag?;
+                tag.evidence = tag_evidence(conn, &bookmark_id.to_string(), &tag_id)?;
+                tags.push(tag);
+            }
+            Ok(tags)
+        })
+    }
+
+    fn get_tag_cloud(&self, limit: usize) -> Result<Vec<TagCloudEntry>> {
         self.with_conn(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT t.name, COUNT(*) as count, AVG(bt.confidence) as avg_conf
@@ -263,7 +923,7 @@ impl SqliteStore {
         })
     }
 
-    pub fn upsert_tags_for_bookmark(
+    fn upsert_tags_for_bookmark(
         &self,
         bookmark_id: Uuid,
         suggestions: &[TagSuggestion],
@@ -271,7 +931,7 @@ impl SqliteStore {
         self.with_conn(|conn| {
             let tx = conn.transaction()?;
             let mut tags = Vec::new();
-            for suggestion in suggestions {
+            for suggestion in &normalize_suggestions(suggestions) {
                 let tag_id: Option<String> = tx
                     .query_row(
                         "SELECT id FROM tags WHERE name = ?1",
@@ -283,31 +943,47 @@ impl SqliteStore {
                 let tag_id = match tag_id {
                     Some(id) => id,
                     None => {
-                        let id = Uuid::new_v4().to_string();
+                        let id = self.ids.new_id().to_string();
                         tx.execute(
                             "INSERT INTO tags (id, name, created_at) VALUES (?1, ?2, ?3)",
-                            params![id, suggestion.name, now_ts()],
+                            params![id, suggestion.name, self.now()],
                         )?;
                         id
                     }
                 };
 
                 tx.execute(
-                    "INSERT OR REPLACE INTO bookmark_tags (bookmark_id, tag_id, confidence, source, created_at)
+                    "INSERT OR REPLACE INTO bookmark_tag_evidence
+                         (bookmark_id, tag_id, source, confidence, created_at)
                      VALUES (?1, ?2, ?3, ?4, ?5)",
                     params![
                         bookmark_id.to_string(),
                         tag_id,
-                        suggestion.confidence,
                         suggestion.source.as_str(),
-                        now_ts()
+                        suggestion.confidence,
+                        self.now()
                     ],
                 )?;
+                let evidence = tag_evidence(&tx, &bookmark_id.to_string(), &tag_id)?;
+                if let Some((confidence, source)) = self.fusion.fuse(&evidence) {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO bookmark_tags
+                             (bookmark_id, tag_id, confidence, source, created_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![
+                            bookmark_id.to_string(),
+                            tag_id,
+                            confidence,
+                            source.as_str(),
+                            self.now()
+                        ],
+                    )?;
+                }
 
                 tags.push(Tag {
                     id: parse_uuid(tag_id),
                     name: suggestion.name.clone(),
-                    created_at: now_ts(),
+                    created_at: self.now(),
                 });
             }
             tx.commit()?;
@@ -315,11 +991,12 @@ impl SqliteStore {
         })
     }
 
-    pub fn enqueue_tag_job(&self, bookmark_id: Uuid) -> Result<TagJob> {
-        let now = now_ts();
+    fn enqueue_job(&self, bookmark_id: Uuid, kind: JobKind) -> Result<TagJob> {
+        let now = self.now();
         let job = TagJob {
-            id: Uuid::new_v4(),
+            id: self.ids.new_id(),
             bookmark_id,
+            kind,
             status: TagJobStatus::Pending,
             attempts: 0,
             created_at: now,
@@ -327,11 +1004,13 @@ impl SqliteStore {
         };
         self.with_conn(|conn| {
             conn.execute(
-                "INSERT INTO tag_jobs (id, bookmark_id, status, attempts, created_at, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "INSERT INTO tag_jobs (id, bookmark_id, kind, status, attempts, created_at,
+                    updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                 params![
                     job.id.to_string(),
                     job.bookmark_id.to_string(),
+                    job.kind.as_str(),
                     job.status.as_str(),
                     job.attempts,
                     job.created_at,
@@ -342,10 +1021,10 @@ impl SqliteStore {
         })
     }
 
-    pub fn fetch_pending_tag_jobs(&self, limit: usize) -> Result<Vec<TagJob>> {
+    fn fetch_pending_tag_jobs(&self, limit: usize) -> Result<Vec<TagJob>> {
         self.with_conn(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, bookmark_id, status, attempts, created_at, updated_at
+                "SELECT id, bookmark_id, status, attempts, created_at, updated_at, kind
                  FROM tag_jobs
                  WHERE status = 'pending'
                  ORDER BY created_at ASC
@@ -355,6 +1034,7 @@ impl SqliteStore {
                 Ok(TagJob {
                     id: parse_uuid(row.get::<_, String>(0)?),
                     bookmark_id: parse_uuid(row.get::<_, String>(1)?),
+                    kind: JobKind::from_str(&row.get::<_, String>(6)?),
                     status: TagJobStatus::from_str(&row.get::<_, String>(2)?),
                     attempts: row.get(3)?,
                     created_at: row.get(4)?,
@@ -369,8 +1049,8 @@ impl SqliteStore {
         })
     }
 
-    pub fn update_tag_job_status(&self, id: Uuid, status: TagJobStatus) -> Result<()> {
-        let now = now_ts();
+    fn update_tag_job_status(&self, id: Uuid, status: TagJobStatus) -> Result<()> {
+        let now = self.now();
         self.with_conn(|conn| {
             conn.execute(
                 "UPDATE tag_jobs SET status = ?1, updated_at = ?2, attempts = attempts + 1 WHERE id = ?3",
@@ -380,9 +1060,154 @@ impl SqliteStore {
         })
     }
 
-    fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
-        let mut conn = Connection::open(Path::new(&self.path))?;
-        f(&mut conn)
+    fn record_link_check(&self, bookmark_id: Uuid, input: LinkCheckInput) -> Result<LinkCheck> {
+        let id = self.ids.new_id();
+        let now = self.now();
+        self.with_conn(|conn| {
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let bookmark = read_bookmark(&tx, bookmark_id)?.ok_or(CoreError::NotFound)?;
+            let recent = read_link_checks(&tx, bookmark_id, FAILED_CHECKS_BEFORE_BROKEN)?;
+            let check = new_link_check(id, &bookmark, input, &recent, now);
+            tx.execute(
+                "INSERT INTO link_checks (id, bookmark_id, status, status_code, final_url, error,
+                    checked_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    check.id.to_string(),
+                    check.bookmark_id.to_string(),
+                    check.status.as_str(),
+                    check.status_code,
+                    check.final_url,
+                    check.error,
+                    check.checked_at
+                ],
+            )?;
+            tx.execute(
+                "UPDATE bookmarks SET link_status = ?1 WHERE id = ?2",
+                params![check.status.as_str(), bookmark_id.to_string()],
+            )?;
+            tx.commit()?;
+            Ok(check)
+        })
+    }
+
+    fn list_link_checks(&self, bookmark_id: Uuid, limit: usize) -> Result<Vec<LinkCheck>> {
+        self.with_conn(|conn| Ok(read_link_checks(conn, bookmark_id, limit)?))
+    }
+
+    fn upsert_history_entry(&self, input: HistoryEntryInput) -> Result<HistoryEntry> {
+        if input.url.trim().is_empty() {
+            return Err(CoreError::InvalidInput("history url is empty".to_string()));
+        }
+        let now = self.now();
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO history_entries (id, workspace_id, url, title, visit_count, last_visited_at, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT (workspace_id, url) DO UPDATE SET
+                    title = CASE WHEN excluded.title = '' THEN title ELSE excluded.title END,
+                    visit_count = MAX(visit_count, excluded.visit_count),
+                    last_visited_at = MAX(last_visited_at, excluded.last_visited_at)",
+                params![
+                    self.ids.new_id().to_string(),
+                    input.workspace_id.to_string(),
+                    input.url.trim(),
+                    input.title.trim(),
+                    input.visit_count,
+                    input.last_visited_at,
+                    now
+                ],
+            )?;
+            conn.query_row(
+                "SELECT id, workspace_id, url, title, visit_count, last_visited_at, created_at
+                 FROM history_entries WHERE workspace_id = ?1 AND url = ?2",
+                params![input.workspace_id.to_string(), input.url.trim()],
+                history_entry_from_row,
+            )
+            .map_err(CoreError::from)
+        })
+    }
+
+    fn list_history(&self, workspace_id: Option<Uuid>, limit: usize) -> Result<Vec<HistoryEntry>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, workspace_id, url, title, visit_count, last_visited_at, created_at
+                 FROM history_entries
+                 WHERE ?1 IS NULL OR workspace_id = ?1
+                 ORDER BY last_visited_at DESC
+                 LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(
+                params![workspace_id.map(|id| id.to_string()), limit as i64],
+                history_entry_from_row,
+            )?;
+            let mut entries = Vec::new();
+            for entry in rows {
+                entries.push(entry?);
+            }
+            Ok(entries)
+        })
+    }
+
+    fn stats(&self) -> Result<LibraryStats> {
+        let mut stats = self.with_conn(|conn| {
+            let count = |table: &str| -> rusqlite::Result<i64> {
+                conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+            };
+            let mut stats = LibraryStats {
+                workspaces: count("workspaces")?,
+                bookmarks: count("bookmarks")?,
+                tags: count("tags")?,
+                ..LibraryStats::default()
+            };
+
+            let mut stmt = conn.prepare(
+                "SELECT w.id, w.name, COUNT(b.id)
+                 FROM workspaces w
+                 LEFT JOIN bookmarks b ON b.workspace_id = w.id
+                 GROUP BY w.id
+                 ORDER BY w.name",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(WorkspaceBookmarkCount {
+                    workspace_id: parse_uuid(row.get::<_, String>(0)?),
+                    name: row.get(1)?,
+                    bookmarks: row.get(2)?,
+                })
+            })?;
+            for row in rows {
+                stats.bookmarks_per_workspace.push(row?);
+            }
+
+            let mut stmt =
+                conn.prepare("SELECT source, COUNT(*) FROM bookmark_tags GROUP BY source")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?;
+            for row in rows {
+                let (source, count) = row?;
+                stats.tag_sources.add(&TagSource::from_str(&source), count);
+            }
+
+            let mut stmt = conn.prepare("SELECT status, COUNT(*) FROM tag_jobs GROUP BY status")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?;
+            for row in rows {
+                let (status, count) = row?;
+                stats.jobs.add(&TagJobStatus::from_str(&status), count);
+            }
+            let oldest_pending: Option<i64> = conn.query_row(
+                "SELECT MIN(created_at) FROM tag_jobs WHERE status = 'pending'",
+                [],
+                |row| row.get(0),
+            )?;
+            stats.jobs.oldest_pending_age_secs =
+                oldest_pending.map(|created_at| (self.now() - created_at).max(0) / 1000);
+            Ok(stats)
+        })?;
+        stats.database = Some(self.database_stats()?);
+        Ok(stats)
     }
 }
 
@@ -391,29 +1216,531 @@ impl TagSource {
         match self {
             TagSource::Rules => "rules",
             TagSource::Llm => "llm",
+            TagSource::User => "user",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "llm" => TagSource::Llm,
+            "user" => TagSource::User,
+            _ => TagSource::Rules,
+        }
+    }
+}
+
+fn read_bookmark(conn: &Connection, id: Uuid) -> rusqlite::Result<Option<Bookmark>> {
+    conn.query_row(
+        &format!("SELECT {} FROM bookmarks b WHERE b.id = ?1", BOOKMARK_COLUMNS),
+        params![id.to_string()],
+        bookmark_from_row,
+    )
+    .optional()
+}
+
+fn save_reading(conn: &Connection, bookmark: &Bookmark) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE bookmarks SET read_state = ?1, progress = ?2, visit_count = ?3,
+            last_visited_at = ?4
+         WHERE id = ?5",
+        params![
+            bookmark.reading.state.as_str(),
+            bookmark.reading.progress as f64,
+            bookmark.reading.visit_count,
+            bookmark.reading.last_visited_at,
+            bookmark.id.to_string()
+        ],
+    )
+}
+
+fn save_review(conn: &Connection, bookmark: &Bookmark) -> rusqlite::Result<usize> {
+    let review = bookmark.review.as_ref();
+    conn.execute(
+        "UPDATE bookmarks SET review_due_at = ?1, review_interval_days = ?2,
+            review_ease = ?3, review_repetitions = ?4, last_reviewed_at = ?5
+         WHERE id = ?6",
+        params![
+            review.map(|review| review.due_at),
+            review.map_or(0, |review| review.interval_days),
+            review.map_or(2.5, |review| review.ease as f64),
+            review.map_or(0, |review| review.repetitions),
+            review.and_then(|review| review.last_reviewed_at),
+            bookmark.id.to_string()
+        ],
+    )
+}
+
+// Newest first; rowid breaks ties between checks made in the same millisecond.
+fn read_link_checks(
+    conn: &Connection,
+    bookmark_id: Uuid,
+    limit: usize,
+) -> rusqlite::Result<Vec<LinkCheck>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, bookmark_id, status, status_code, final_url, error, checked_at
+         FROM link_checks
+         WHERE bookmark_id = ?1
+         ORDER BY checked_at DESC, rowid DESC
+         LIMIT ?2",
+    )?;
+    let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+    let rows = stmt.query_map(params![bookmark_id.to_string(), limit], |row| {
+        Ok(LinkCheck {
+            id: parse_uuid(row.get::<_, String>(0)?),
+            bookmark_id: parse_uuid(row.get::<_, String>(1)?),
+            status: LinkStatus::from_str(&row.get::<_, String>(2)?),
+            status_code: row.get(3)?,
+            final_url: row.get(4)?,
+            error: row.get(5)?,
+            checked_at: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+fn bookmark_from_row(row: &rusqlite::Row) -> rusqlite::Result<Bookmark> {
+    Ok(Bookmark {
+        id: parse_uuid(row.get::<_, String>(0)?),
+        workspace_id: parse_uuid(row.get::<_, String>(1)?),
+        url: row.get(2)?,
+        title: row.get(3)?,
+        notes: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        added_at: row.get(29)?,
+        citation: Citation {
+            authors: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
+            venue: row.get(8)?,
+            doi: row.get(9)?,
+            year: row.get(10)?,
+        },
+        metadata: BookmarkMetadata {
+            description: row.get(11)?,
+            favicon_url: row.get(12)?,
+            site_name: row.get(13)?,
+            author: row.get(14)?,
+            published_at: row.get(15)?,
+            language: row.get(16)?,
+            word_count: row.get(17)?,
+            reading_time_minutes: row.get(18)?,
+        },
+        reading: Reading {
+            state: ReadState::from_str(&row.get::<_, String>(19)?),
+            progress: row.get::<_, f64>(20)? as f32,
+            visit_count: row.get(21)?,
+            last_visited_at: row.get(22)?,
+        },
+        review: match row.get::<_, Option<i64>>(23)? {
+            Some(due_at) => Some(ReviewSchedule {
+                due_at,
+                interval_days: row.get(24)?,
+                ease: row.get::<_, f64>(25)? as f32,
+                repetitions: row.get(26)?,
+                last_reviewed_at: row.get(27)?,
+            }),
+            None => None,
+        },
+        link_status: row
+            .get::<_, Option<String>>(28)?
+            .map(|status| LinkStatus::from_str(&status)),
+    })
+}
+
+fn history_entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: parse_uuid(row.get::<_, String>(0)?),
+        workspace_id: parse_uuid(row.get::<_, String>(1)?),
+        url: row.get(2)?,
+        title: row.get(3)?,
+        visit_count: row.get(4)?,
+        last_visited_at: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn add_citation_columns(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE bookmarks ADD COLUMN authors TEXT NOT NULL DEFAULT '[]';
+         ALTER TABLE bookmarks ADD COLUMN venue TEXT;
+         ALTER TABLE bookmarks ADD COLUMN doi TEXT;
+         ALTER TABLE bookmarks ADD COLUMN year INTEGER;",
+    )
+}
+
+fn timestamps_to_millis(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "UPDATE workspaces SET created_at = created_at * 1000;
+         UPDATE bookmarks SET created_at = created_at * 1000, updated_at = updated_at * 1000;
+         UPDATE tags SET created_at = created_at * 1000;
+         UPDATE bookmark_tags SET created_at = created_at * 1000;
+         UPDATE tag_jobs SET created_at = created_at * 1000, updated_at = updated_at * 1000;
+         UPDATE history_entries SET created_at = created_at * 1000,
+             last_visited_at = last_visited_at * 1000;",
+    )
+}
+
+fn add_metadata_columns(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE bookmarks ADD COLUMN description TEXT;
+         ALTER TABLE bookmarks ADD COLUMN favicon_url TEXT;
+         ALTER TABLE bookmarks ADD COLUMN site_name TEXT;
+         ALTER TABLE bookmarks ADD COLUMN author TEXT;
+         ALTER TABLE bookmarks ADD COLUMN published_at INTEGER;
+         ALTER TABLE bookmarks ADD COLUMN language TEXT;
+         ALTER TABLE bookmarks ADD COLUMN word_count INTEGER;
+         ALTER TABLE bookmarks ADD COLUMN reading_time_minutes INTEGER;",
+    )
+}
+
+fn add_reading_columns(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE bookmarks ADD COLUMN read_state TEXT NOT NULL DEFAULT 'unread';
+         ALTER TABLE bookmarks ADD COLUMN progress REAL NOT NULL DEFAULT 0;
+         ALTER TABLE bookmarks ADD COLUMN visit_count INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE bookmarks ADD COLUMN last_visited_at INTEGER;",
+    )
+}
+
+fn add_review_columns(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE bookmarks ADD COLUMN review_due_at INTEGER;
+         ALTER TABLE bookmarks ADD COLUMN review_interval_days INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE bookmarks ADD COLUMN review_ease REAL NOT NULL DEFAULT 2.5;
+         ALTER TABLE bookmarks ADD COLUMN review_repetitions INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE bookmarks ADD COLUMN last_reviewed_at INTEGER;
+         CREATE INDEX IF NOT EXISTS idx_bookmarks_review_due ON bookmarks (review_due_at);",
+    )
+}
+
+fn add_link_checks(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE tag_jobs ADD COLUMN kind TEXT NOT NULL DEFAULT 'tag';
+         ALTER TABLE bookmarks ADD COLUMN link_status TEXT;
+         CREATE TABLE link_checks (
+             id TEXT PRIMARY KEY,
+             bookmark_id TEXT NOT NULL,
+             status TEXT NOT NULL,
+             status_code INTEGER,
+             final_url TEXT,
+             error TEXT,
+             checked_at INTEGER NOT NULL
+         );
+         CREATE INDEX idx_link_checks_bookmark ON link_checks (bookmark_id, checked_at);",
+    )
+}
+
+fn add_saved_searches(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE saved_searches (
+             id TEXT PRIMARY KEY,
+             name TEXT NOT NULL,
+             workspace_id TEXT,
+             query TEXT,
+             filter TEXT NOT NULL,
+             created_at INTEGER NOT NULL,
+             last_viewed_at INTEGER
+         );",
+    )
+}
+
+fn add_tab_sets(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE tab_sets (
+             id TEXT PRIMARY KEY,
+             workspace_id TEXT NOT NULL,
+             name TEXT NOT NULL,
+             created_at INTEGER NOT NULL,
+             updated_at INTEGER NOT NULL
+         );
+         CREATE TABLE tabs (
+             id TEXT PRIMARY KEY,
+             tab_set_id TEXT NOT NULL,
+             url TEXT NOT NULL,
+             title TEXT NOT NULL,
+             position INTEGER NOT NULL,
+             pinned INTEGER NOT NULL DEFAULT 0,
+             last_active_at INTEGER
+         );
+         CREATE INDEX idx_tabs_tab_set ON tabs (tab_set_id, position);",
+    )
+}
+
+fn add_bookmark_links(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE bookmark_links (
+             id TEXT PRIMARY KEY,
+             source_id TEXT NOT NULL,
+             target_id TEXT NOT NULL,
+             kind TEXT NOT NULL,
+             notes TEXT,
+             created_at INTEGER NOT NULL,
+             UNIQUE (source_id, target_id, kind)
+         );
+         CREATE INDEX idx_bookmark_links_target ON bookmark_links (target_id);",
+    )
+}
+
+fn add_tag_rules(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE tag_rules (
+             id TEXT PRIMARY KEY,
+             name TEXT NOT NULL,
+             url_pattern TEXT,
+             domain TEXT,
+             title_regex TEXT,
+             has_tags TEXT NOT NULL DEFAULT '[]',
+             tags TEXT NOT NULL,
+             confidence REAL NOT NULL,
+             enabled INTEGER NOT NULL DEFAULT 1,
+             created_at INTEGER NOT NULL,
+             updated_at INTEGER NOT NULL
+         );",
+    )
+}
+
+// Rows hold terms already split by `search_terms`, so the `ascii` tokenizer
+// only has to break on the spaces between them.
+fn add_search_index(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE bookmark_search USING fts5(
+             bookmark_id UNINDEXED,
+             terms,
+             tokenize = 'ascii'
+         );",
+    )?;
+    let mut stmt =
+        conn.prepare("SELECT id, title, url, description, site_name, author FROM bookmarks")?;
+    let rows = stmt.query_map([], |row| {
+        let mut fields = Vec::new();
+        for index in 1..6 {
+            fields.extend(row.get::<_, Option<String>>(index)?);
+        }
+        Ok((row.get::<_, String>(0)?, fields))
+    })?;
+    for row in rows {
+        let (id, fields) = row?;
+        let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+        index_bookmark(conn, &id, &fields)?;
+    }
+    Ok(())
+}
+
+// One row per source that suggested a tag for a bookmark; `bookmark_tags`
+// keeps the fused confidence. Existing assignments become their source's
+// evidence.
+fn add_tag_evidence(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE bookmark_tag_evidence (
+             bookmark_id TEXT NOT NULL,
+             tag_id TEXT NOT NULL,
+             source TEXT NOT NULL,
+             confidence REAL NOT NULL,
+             created_at INTEGER NOT NULL,
+             PRIMARY KEY (bookmark_id, tag_id, source)
+         );
+         INSERT INTO bookmark_tag_evidence (bookmark_id, tag_id, source, confidence, created_at)
+         SELECT bookmark_id, tag_id, source, confidence, created_at FROM bookmark_tags;",
+    )
+}
+
+// Rows from before this column have only their `created_at` to go on.
+fn add_bookmark_added_at(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE bookmarks ADD COLUMN added_at INTEGER NOT NULL DEFAULT 0;
+         UPDATE bookmarks SET added_at = created_at;",
+    )
+}
+
+fn tag_evidence(
+    conn: &Connection,
+    bookmark_id: &str,
+    tag_id: &str,
+) -> rusqlite::Result<Vec<TagEvidence>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT source, confidence, created_at FROM bookmark_tag_evidence
+         WHERE bookmark_id = ?1 AND tag_id = ?2
+         ORDER BY source",
+    )?;
+    let rows = stmt.query_map(params![bookmark_id, tag_id], |row| {
+        Ok(TagEvidence {
+            source: TagSource::from_str(&row.get::<_, String>(0)?),
+            confidence: row.get(1)?,
+            created_at: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// Renames tags to their normalized form. Tags that collide merge into the
+// oldest one, keeping the most confident assignment per bookmark; tags that
+// normalize to nothing are dropped.
+fn normalize_tag_names(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT id, name FROM tags ORDER BY created_at, id")?;
+    let tags = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut survivors: HashMap<String, String> = HashMap::new();
+    let mut renames = Vec::new();
+    for (id, name) in tags {
+        let Some(normalized) = normalize_tag(&name) else {
+            conn.execute("DELETE FROM bookmark_tags WHERE tag_id = ?1", params![id])?;
+            conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+            continue;
+        };
+        match survivors.get(&normalized) {
+            Some(survivor) => {
+                conn.execute(
+                    "INSERT INTO bookmark_tags (bookmark_id, tag_id, confidence, source, created_at)
+                     SELECT bookmark_id, ?1, confidence, source, created_at
+                     FROM bookmark_tags WHERE tag_id = ?2
+                     ON CONFLICT (bookmark_id, tag_id) DO UPDATE SET
+                         confidence = excluded.confidence,
+                         source = excluded.source,
+                         created_at = excluded.created_at
+                     WHERE excluded.confidence > bookmark_tags.confidence",
+                    params![survivor, id],
+                )?;
+                conn.execute("DELETE FROM bookmark_tags WHERE tag_id = ?1", params![id])?;
+                conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+            }
+            None => {
+                if normalized != name {
+                    renames.push((id.clone(), normalized.clone()));
+                }
+                survivors.insert(normalized, id);
+            }
         }
     }
+    // Only after the merges, so no rename hits a name that is still taken.
+    for (id, name) in renames {
+        conn.execute("UPDATE tags SET name = ?1 WHERE id = ?2", params![name, id])?;
+    }
+    Ok(())
+}
+
+fn index_bookmark(conn: &Connection, id: &str, fields: &[&str]) -> rusqlite::Result<()> {
+    let terms: Vec<String> = fields.iter().flat_map(|field| search_terms(field)).collect();
+    conn.execute("DELETE FROM bookmark_search WHERE bookmark_id = ?1", params![id])?;
+    conn.execute(
+        "INSERT INTO bookmark_search (bookmark_id, terms) VALUES (?1, ?2)",
+        params![id, terms.join(" ")],
+    )?;
+    Ok(())
+}
+
+fn tag_rule_from_row(row: &rusqlite::Row) -> rusqlite::Result<TagRule> {
+    let list = |index: usize| -> rusqlite::Result<Vec<String>> {
+        Ok(serde_json::from_str(&row.get::<_, String>(index)?).unwrap_or_default())
+    };
+    Ok(TagRule {
+        id: parse_uuid(row.get::<_, String>(0)?),
+        spec: TagRuleInput {
+            name: row.get(1)?,
+            url_pattern: row.get(2)?,
+            domain: row.get(3)?,
+            title_regex: row.get(4)?,
+            has_tags: list(5)?,
+            tags: list(6)?,
+            confidence: row.get::<_, f64>(7)? as f32,
+            enabled: row.get(8)?,
+        },
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+const BOOKMARK_LINK_COLUMNS: &str = "l.id, l.source_id, l.target_id, l.kind, l.notes, l.created_at";
+
+fn bookmark_link_from_row(row: &rusqlite::Row) -> rusqlite::Result<BookmarkLink> {
+    Ok(BookmarkLink {
+        id: parse_uuid(row.get::<_, String>(0)?),
+        source_id: parse_uuid(row.get::<_, String>(1)?),
+        target_id: parse_uuid(row.get::<_, String>(2)?),
+        kind: RelationKind::from_str(&row.get::<_, String>(3)?),
+        notes: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+fn insert_tabs(tx: &rusqlite::Transaction, tabs: &[Tab]) -> rusqlite::Result<()> {
+    let mut stmt = tx.prepare(
+        "INSERT INTO tabs (id, tab_set_id, url, title, position, pinned, last_active_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    for tab in tabs {
+        stmt.execute(params![
+            tab.id.to_string(),
+            tab.tab_set_id.to_string(),
+            tab.url,
+            tab.title,
+            tab.position,
+            tab.pinned,
+            tab.last_active_at
+        ])?;
+    }
+    Ok(())
+}
+
+fn load_tabs(conn: &Connection, tab_set: &mut TabSet) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT id, tab_set_id, url, title, position, pinned, last_active_at
+         FROM tabs WHERE tab_set_id = ?1 ORDER BY position",
+    )?;
+    let rows = stmt.query_map(params![tab_set.id.to_string()], tab_from_row)?;
+    tab_set.tabs = rows.collect::<rusqlite::Result<_>>()?;
+    Ok(())
+}
+
+fn tab_from_row(row: &rusqlite::Row) -> rusqlite::Result<Tab> {
+    Ok(Tab {
+        id: parse_uuid(row.get::<_, String>(0)?),
+        tab_set_id: parse_uuid(row.get::<_, String>(1)?),
+        url: row.get(2)?,
+        title: row.get(3)?,
+        position: row.get(4)?,
+        pinned: row.get(5)?,
+        last_active_at: row.get(6)?,
+    })
+}
+
+fn tab_set_from_row(row: &rusqlite::Row) -> rusqlite::Result<TabSet> {
+    Ok(TabSet {
+        id: parse_uuid(row.get::<_, String>(0)?),
+        workspace_id: parse_uuid(row.get::<_, String>(1)?),
+        name: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        tabs: Vec::new(),
+    })
+}
+
+fn saved_search_from_row(row: &rusqlite::Row) -> rusqlite::Result<SavedSearch> {
+    Ok(SavedSearch {
+        id: parse_uuid(row.get::<_, String>(0)?),
+        name: row.get(1)?,
+        workspace_id: row.get::<_, Option<String>>(2)?.map(parse_uuid),
+        query: row.get(3)?,
+        filter: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+        created_at: row.get(5)?,
+        last_viewed_at: row.get(6)?,
+    })
 }
 
 fn parse_uuid(value: String) -> Uuid {
     Uuid::parse_str(&value).unwrap_or_else(|_| Uuid::nil())
 }
 
-fn now_ts() -> i64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or(0)
-}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
 
     #[test]
     fn creates_and_lists_workspaces() {
-        let path = std::env::temp_dir().join(format!("lester-test-{}.db", Uuid::new_v4()));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lester.db");
         let store = SqliteStore::new(path.to_string_lossy().to_string());
         store.migrate().unwrap();
 
@@ -421,7 +1748,126 @@ mod tests {
         let list = store.list_workspaces().unwrap();
         assert_eq!(list.len(), 1);
         assert_eq!(list[0].name, created.name);
+    }
+
+    #[test]
+    fn migrates_and_updates_bookmark_citation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lester.db");
+        let store = SqliteStore::new(path.to_string_lossy().to_string());
+        store.migrate().unwrap();
+        store.migrate().unwrap();
+
+        let workspace = store.create_workspace("Papers").unwrap();
+        let bookmark = store
+            .create_bookmark(BookmarkInput {
+                workspace_id: workspace.id,
+                url: "https://arxiv.org/abs/1706.03762".to_string(),
+                title: "Attention".to_string(),
+                notes: None,
+                metadata: BookmarkMetadata::default(),
+            })
+            .unwrap();
+        let patch = BookmarkPatch {
+            title: Some("Attention Is All You Need".to_string()),
+            citation: Some(Citation {
+                authors: vec![" Ashish Vaswani ".to_string(), String::new()],
+                venue: Some("NeurIPS".to_string()),
+                doi: Some("https://doi.org/10.5555/3295222".to_string()),
+                year: Some(2017),
+            }),
+            ..BookmarkPatch::default()
+        };
+        store.update_bookmark(bookmark.id, patch).unwrap();
+
+        let stored = store.get_bookmark(bookmark.id).unwrap().unwrap();
+        assert_eq!(stored.title, "Attention Is All You Need");
+        assert_eq!(stored.citation.authors, vec!["Ashish Vaswani"]);
+        assert_eq!(stored.citation.doi.as_deref(), Some("10.5555/3295222"));
+        assert_eq!(stored.citation.year, Some(2017));
+        assert!(matches!(
+            store.update_bookmark(Uuid::new_v4(), BookmarkPatch::default()),
+            Err(CoreError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn reports_database_file_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lester.db");
+        let store = SqliteStore::new(path.to_string_lossy().to_string());
+        store.migrate().unwrap();
+
+        let database = store.stats().unwrap().database.unwrap();
+        assert!(database.page_size > 0);
+        assert!(database.page_count > 0);
+        assert_eq!(
+            database.file_size_bytes,
+            (database.page_size * database.page_count) as u64
+        );
+    }
+
+    #[test]
+    fn migrates_second_timestamps_to_millis() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lester.db");
+        let store = SqliteStore::new(path.to_string_lossy().to_string())
+            .with_clock(ManualClock::new(1_700_000_000));
+        store.migrate().unwrap();
+        let workspace = store.create_workspace("Old").unwrap();
+        store
+            .with_conn(|conn| Ok(timestamps_to_millis(conn)?))
+            .unwrap();
+
+        let migrated = store.list_workspaces().unwrap();
+        assert_eq!(migrated[0].id, workspace.id);
+        assert_eq!(migrated[0].created_at, 1_700_000_000_000);
+    }
+
+    #[test]
+    fn normalizes_and_merges_existing_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lester.db");
+        let store = SqliteStore::new(path.to_string_lossy().to_string());
+        store.migrate().unwrap();
+        let workspace = store.create_workspace("Old").unwrap();
+        let bookmark = |url: &str| {
+            store
+                .create_bookmark(BookmarkInput {
+                    workspace_id: workspace.id,
+                    url: url.to_string(),
+                    title: url.to_string(),
+                    notes: None,
+                    metadata: BookmarkMetadata::default(),
+                })
+                .unwrap()
+                .id
+        };
+        let (first, second) = (bookmark("https://a.example/"), bookmark("https://b.example/"));
+        store
+            .with_conn(|conn| {
+                conn.execute_batch(&format!(
+                    "INSERT INTO tags (id, name, created_at) VALUES
+                         ('t1', 'Rust', 1), ('t2', 'rust', 2), ('t3', 'Databases', 3),
+                         ('t4', ' - ', 4);
+                     INSERT INTO bookmark_tags VALUES
+                         ('{first}', 't1', 0.6, 'rules', 1), ('{first}', 't2', 0.9, 'llm', 2),
+                         ('{second}', 't2', 0.5, 'llm', 2), ('{second}', 't3', 0.8, 'rules', 3),
+                         ('{second}', 't4', 0.7, 'rules', 4);"
+                ))?;
+                Ok(normalize_tag_names(conn)?)
+            })
+            .unwrap();
 
-        let _ = std::fs::remove_file(path);
+        let names: Vec<String> =
+            store.list_tags().unwrap().into_iter().map(|tag| tag.name).collect();
+        assert_eq!(names, vec!["database", "rust"]);
+        let details = |id| store.list_tags_for_bookmark(id).unwrap();
+        let first_tags = details(first);
+        assert_eq!(first_tags.len(), 1);
+        assert_eq!((first_tags[0].name.as_str(), first_tags[0].confidence), ("rust", 0.9));
+        assert!(matches!(first_tags[0].source, TagSource::Llm));
+        let second_tags: Vec<String> = details(second).into_iter().map(|tag| tag.name).collect();
+        assert_eq!(second_tags, vec!["database", "rust"]);
     }
 }
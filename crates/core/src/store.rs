@@ -0,0 +1,430 @@
+use uuid::Uuid;
+
+use crate::errors::{CoreError, Result};
+use crate::models::{
+    Bookmark, BookmarkFilter, BookmarkInput, BookmarkLink, BookmarkLinkInput, BookmarkMetadata,
+    BookmarkPatch, BookmarkSort, BookmarkTagDetail, Citation, HistoryEntry, HistoryEntryInput,
+    JobKind, LibraryStats, LinkCheck, LinkCheckInput, LinkStatus, ReadState, Reading, SavedSearch,
+    SavedSearchInput, Tab, TabInput, TabSet, TabSetInput, Tag, TagCloudEntry, TagJob,
+    TagJobStatus, TagRule, TagRuleInput, TagSuggestion, Workspace,
+};
+use crate::review::ReviewOutcome;
+use crate::rules::validate_rule;
+use crate::search::parse_query;
+use crate::tag_names::normalize_tag;
+use crate::tokenize::search_terms;
+
+pub trait Store: Clone + Send + Sync + 'static {
+    fn create_workspace(&self, name: &str) -> Result<Workspace>;
+
+    fn list_workspaces(&self) -> Result<Vec<Workspace>>;
+
+    fn create_bookmark(&self, input: BookmarkInput) -> Result<Bookmark> {
+        self.create_bookmark_at(input, None, None)
+    }
+
+    fn create_bookmark_at(
+        &self,
+        input: BookmarkInput,
+        created_at: Option<i64>,
+        updated_at: Option<i64>,
+    ) -> Result<Bookmark>;
+
+    fn list_bookmarks(&self, filter: BookmarkFilter) -> Result<Vec<Bookmark>>;
+
+    fn get_bookmark(&self, id: Uuid) -> Result<Option<Bookmark>>;
+
+    fn update_bookmark(&self, id: Uuid, patch: BookmarkPatch) -> Result<Bookmark>;
+
+    fn record_visit(&self, id: Uuid, progress: Option<f32>) -> Result<Bookmark>;
+
+    fn set_read_state(
+        &self,
+        id: Uuid,
+        state: ReadState,
+        progress: Option<f32>,
+    ) -> Result<Bookmark>;
+
+    fn schedule_review(&self, id: Uuid, due_at: i64) -> Result<Bookmark>;
+
+    fn record_review(&self, id: Uuid, outcome: ReviewOutcome) -> Result<Bookmark>;
+
+    fn list_due_reviews(
+        &self,
+        workspace_id: Option<Uuid>,
+        tz_offset_minutes: i32,
+        limit: usize,
+    ) -> Result<Vec<Bookmark>>;
+
+    fn create_saved_search(&self, input: SavedSearchInput) -> Result<SavedSearch>;
+
+    fn list_saved_searches(&self, workspace_id: Option<Uuid>) -> Result<Vec<SavedSearch>>;
+
+    fn get_saved_search(&self, id: Uuid) -> Result<Option<SavedSearch>>;
+
+    fn mark_saved_search_viewed(&self, id: Uuid) -> Result<SavedSearch>;
+
+    fn delete_saved_search(&self, id: Uuid) -> Result<()>;
+
+    fn save_tab_set(&self, input: TabSetInput) -> Result<TabSet>;
+
+    fn replace_tabs(&self, tab_set_id: Uuid, tabs: Vec<TabInput>) -> Result<TabSet>;
+
+    fn list_tab_sets(&self, workspace_id: Option<Uuid>) -> Result<Vec<TabSet>>;
+
+    fn get_tab_set(&self, id: Uuid) -> Result<Option<TabSet>>;
+
+    fn get_tab(&self, id: Uuid) -> Result<Option<Tab>>;
+
+    fn delete_tab_set(&self, id: Uuid) -> Result<()>;
+
+    // Linking the same pair with the same kind again replaces the notes.
+    fn link_bookmarks(&self, input: BookmarkLinkInput) -> Result<BookmarkLink>;
+
+    fn list_bookmark_links(&self, bookmark_id: Uuid) -> Result<Vec<BookmarkLink>>;
+
+    fn list_workspace_links(&self, workspace_id: Uuid) -> Result<Vec<BookmarkLink>>;
+
+    fn delete_bookmark_link(&self, id: Uuid) -> Result<()>;
+
+    fn create_tag_rule(&self, input: TagRuleInput) -> Result<TagRule>;
+
+    fn list_tag_rules(&self) -> Result<Vec<TagRule>>;
+
+    fn update_tag_rule(&self, id: Uuid, input: TagRuleInput) -> Result<TagRule>;
+
+    fn delete_tag_rule(&self, id: Uuid) -> Result<()>;
+
+    fn list_tags(&self) -> Result<Vec<Tag>>;
+
+    fn list_tags_for_bookmark(&self, bookmark_id: Uuid) -> Result<Vec<BookmarkTagDetail>>;
+
+    fn get_tag_cloud(&self, limit: usize) -> Result<Vec<TagCloudEntry>>;
+
+    // Each suggestion replaces its source's evidence for the tag, and the
+    // tag's confidence is fused again from all of it.
+    fn upsert_tags_for_bookmark(
+        &self,
+        bookmark_id: Uuid,
+        suggestions: &[TagSuggestion],
+    ) -> Result<Vec<Tag>>;
+
+    fn enqueue_tag_job(&self, bookmark_id: Uuid) -> Result<TagJob> {
+        self.enqueue_job(bookmark_id, JobKind::Tag)
+    }
+
+    fn enqueue_job(&self, bookmark_id: Uuid, kind: JobKind) -> Result<TagJob>;
+
+    fn fetch_pending_tag_jobs(&self, limit: usize) -> Result<Vec<TagJob>>;
+
+    fn update_tag_job_status(&self, id: Uuid, status: TagJobStatus) -> Result<()>;
+
+    fn record_link_check(&self, bookmark_id: Uuid, input: LinkCheckInput) -> Result<LinkCheck>;
+
+    fn list_link_checks(&self, bookmark_id: Uuid, limit: usize) -> Result<Vec<LinkCheck>>;
+
+    fn upsert_history_entry(&self, input: HistoryEntryInput) -> Result<HistoryEntry>;
+
+    fn list_history(&self, workspace_id: Option<Uuid>, limit: usize) -> Result<Vec<HistoryEntry>>;
+
+    fn stats(&self) -> Result<LibraryStats>;
+}
+
+pub(crate) fn new_workspace(id: Uuid, name: &str, now: i64) -> Result<Workspace> {
+    if name.trim().is_empty() {
+        return Err(CoreError::InvalidInput("workspace name is empty".to_string()));
+    }
+    Ok(Workspace {
+        id,
+        name: name.trim().to_string(),
+        created_at: now,
+    })
+}
+
+pub(crate) fn new_bookmark(
+    id: Uuid,
+    now: i64,
+    input: BookmarkInput,
+    created_at: Option<i64>,
+    updated_at: Option<i64>,
+) -> Result<Bookmark> {
+    if input.url.trim().is_empty() || input.title.trim().is_empty() {
+        return Err(CoreError::InvalidInput("bookmark url or title is empty".to_string()));
+    }
+    let created_at = created_at.unwrap_or(now);
+    Ok(Bookmark {
+        id,
+        workspace_id: input.workspace_id,
+        url: input.url.trim().to_string(),
+        title: input.title.trim().to_string(),
+        notes: input.notes,
+        created_at,
+        updated_at: updated_at.unwrap_or(created_at).max(created_at),
+        added_at: now,
+        citation: Citation::default(),
+        metadata: input.metadata.normalized(),
+        reading: Reading::default(),
+        review: None,
+        link_status: None,
+    })
+}
+
+pub(crate) fn apply_patch(bookmark: &mut Bookmark, patch: BookmarkPatch, now: i64) -> Result<()> {
+    if let Some(url) = patch.url {
+        if url.trim().is_empty() {
+            return Err(CoreError::InvalidInput("bookmark url is empty".to_string()));
+        }
+        bookmark.url = url.trim().to_string();
+    }
+    if let Some(title) = patch.title {
+        if title.trim().is_empty() {
+            return Err(CoreError::InvalidInput("bookmark title is empty".to_string()));
+        }
+        bookmark.title = title.trim().to_string();
+    }
+    if let Some(notes) = patch.notes {
+        bookmark.notes = Some(notes).filter(|notes| !notes.trim().is_empty());
+    }
+    if let Some(citation) = patch.citation {
+        bookmark.citation = citation.normalized();
+    }
+    if let Some(metadata) = patch.metadata {
+        bookmark.metadata = metadata.normalized();
+    }
+    bookmark.updated_at = now.max(bookmark.updated_at);
+    Ok(())
+}
+
+fn language_matches(language: Option<&str>, wanted: &str) -> bool {
+    let wanted = wanted.trim().to_lowercase();
+    language.is_some_and(|language| {
+        language == wanted || language.starts_with(&format!("{}-", wanted))
+    })
+}
+
+pub(crate) fn metadata_matches(metadata: &BookmarkMetadata, filter: &BookmarkFilter) -> bool {
+    let same = |value: &Option<String>, wanted: &Option<String>| {
+        wanted.as_deref().is_none_or(|wanted| {
+            value
+                .as_deref()
+                .is_some_and(|value| value.eq_ignore_ascii_case(wanted.trim()))
+        })
+    };
+    filter
+        .language
+        .as_deref()
+        .is_none_or(|wanted| language_matches(metadata.language.as_deref(), wanted))
+        && same(&metadata.site_name, &filter.site_name)
+        && same(&metadata.author, &filter.author)
+        && filter.max_reading_time.is_none_or(|max| {
+            metadata
+                .reading_time_minutes
+                .is_some_and(|minutes| minutes <= max)
+        })
+}
+
+// Tag names are normalized on every write. Suggestions from one source that
+// end up with the same name collapse into the most confident one; empty names
+// are dropped.
+pub(crate) fn normalize_suggestions(suggestions: &[TagSuggestion]) -> Vec<TagSuggestion> {
+    let mut normalized: Vec<TagSuggestion> = Vec::new();
+    for suggestion in suggestions {
+        let Some(name) = normalize_tag(&suggestion.name) else {
+            continue;
+        };
+        match normalized
+            .iter_mut()
+            .find(|seen| seen.name == name && seen.source == suggestion.source)
+        {
+            Some(seen) => seen.confidence = seen.confidence.max(suggestion.confidence),
+            None => normalized.push(TagSuggestion {
+                name,
+                ..suggestion.clone()
+            }),
+        }
+    }
+    normalized
+}
+
+// The text a bookmark `query` looks at.
+pub(crate) fn search_fields(bookmark: &Bookmark) -> Vec<&str> {
+    let metadata = &bookmark.metadata;
+    [
+        Some(&bookmark.title),
+        Some(&bookmark.url),
+        metadata.description.as_ref(),
+        metadata.site_name.as_ref(),
+        metadata.author.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(String::as_str)
+    .collect()
+}
+
+// Token match as done by the SQLite search index: every query term must
+// start one of the bookmark's terms.
+pub(crate) fn matches_search_terms(query_terms: &[String], fields: &[&str]) -> bool {
+    let terms: Vec<String> = fields.iter().flat_map(|field| search_terms(field)).collect();
+    !query_terms.is_empty()
+        && query_terms
+            .iter()
+            .all(|wanted| terms.iter().any(|term| term.starts_with(wanted.as_str())))
+}
+
+pub(crate) fn new_tab_set(id: Uuid, input: &TabSetInput, now: i64) -> Result<TabSet> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(CoreError::InvalidInput("tab set name is empty".to_string()));
+    }
+    Ok(TabSet {
+        id,
+        workspace_id: input.workspace_id,
+        name: name.to_string(),
+        created_at: now,
+        updated_at: now,
+        tabs: Vec::new(),
+    })
+}
+
+// Positions follow the order the tabs were given in.
+pub(crate) fn new_tabs(
+    tab_set_id: Uuid,
+    tabs: Vec<TabInput>,
+    mut new_id: impl FnMut() -> Uuid,
+) -> Result<Vec<Tab>> {
+    tabs.into_iter()
+        .enumerate()
+        .map(|(position, tab)| {
+            let url = tab.url.trim();
+            if url.is_empty() {
+                return Err(CoreError::InvalidInput("tab url is empty".to_string()));
+            }
+            Ok(Tab {
+                id: new_id(),
+                tab_set_id,
+                url: url.to_string(),
+                title: tab.title.trim().to_string(),
+                position: position as i64,
+                pinned: tab.pinned,
+                last_active_at: tab.last_active_at,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn new_bookmark_link(
+    id: Uuid,
+    input: BookmarkLinkInput,
+    now: i64,
+) -> Result<BookmarkLink> {
+    if input.source_id == input.target_id {
+        return Err(CoreError::InvalidInput("a bookmark cannot link to itself".to_string()));
+    }
+    Ok(BookmarkLink {
+        id,
+        source_id: input.source_id,
+        target_id: input.target_id,
+        kind: input.kind,
+        notes: input
+            .notes
+            .map(|notes| notes.trim().to_string())
+            .filter(|notes| !notes.is_empty()),
+        created_at: now,
+    })
+}
+
+pub(crate) fn new_tag_rule(id: Uuid, mut input: TagRuleInput, now: i64) -> Result<TagRule> {
+    validate_rule(&input)?;
+    input.name = input.name.trim().to_string();
+    Ok(TagRule {
+        id,
+        spec: input,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+pub(crate) fn new_saved_search(
+    id: Uuid,
+    input: SavedSearchInput,
+    now: i64,
+) -> Result<SavedSearch> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(CoreError::InvalidInput("saved search name is empty".to_string()));
+    }
+    let query = input.query.filter(|query| !query.trim().is_empty());
+    let filter = match (&query, input.filter) {
+        (Some(_), Some(_)) => {
+            return Err(CoreError::InvalidInput(
+                "give either a query or a filter, not both".to_string(),
+            ))
+        }
+        (Some(query), None) => parse_query(query)?,
+        (None, filter) => filter.unwrap_or_default(),
+    };
+    Ok(SavedSearch {
+        id,
+        name: name.to_string(),
+        workspace_id: input.workspace_id,
+        query,
+        filter,
+        created_at: now,
+        last_viewed_at: None,
+    })
+}
+
+// Checks in a row that must fail before a link that only timed out or got a
+// 5xx is reported Broken.
+pub(crate) const FAILED_CHECKS_BEFORE_BROKEN: usize = 3;
+
+// `recent` is the bookmark's earlier checks, newest first.
+pub(crate) fn new_link_check(
+    id: Uuid,
+    bookmark: &Bookmark,
+    input: LinkCheckInput,
+    recent: &[LinkCheck],
+    now: i64,
+) -> LinkCheck {
+    let mut status = LinkStatus::classify(&bookmark.url, &input);
+    let earlier = FAILED_CHECKS_BEFORE_BROKEN - 1;
+    if status == LinkStatus::Unreachable
+        && recent.len() >= earlier
+        && recent[..earlier].iter().all(|check| check.status.is_failure())
+    {
+        status = LinkStatus::Broken;
+    }
+    LinkCheck {
+        id,
+        bookmark_id: bookmark.id,
+        status,
+        status_code: input.status_code,
+        final_url: input.final_url,
+        error: input.error,
+        checked_at: now,
+    }
+}
+
+pub(crate) fn check_progress(progress: Option<f32>) -> Result<()> {
+    match progress {
+        Some(progress) if !(0.0..=1.0).contains(&progress) => Err(CoreError::InvalidInput(
+            "reading progress must be between 0 and 1".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Bookmarks arrive ordered by `updated_at` descending; the stable sort keeps
+// that as the tie-breaker.
+pub(crate) fn sort_bookmarks(bookmarks: &mut [Bookmark], sort: BookmarkSort, now: i64) {
+    match sort {
+        BookmarkSort::Updated => {}
+        BookmarkSort::Frecency => {
+            bookmarks.sort_by(|a, b| b.reading.frecency(now).total_cmp(&a.reading.frecency(now)))
+        }
+        BookmarkSort::LastVisited => {
+            bookmarks.sort_by_key(|bookmark| std::cmp::Reverse(bookmark.reading.last_visited_at))
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::models::Bookmark;
+use crate::review::ReviewSchedule;
+
+pub const REVIEW_FIELD: &str = "review";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncEnvelope {
     pub device_id: Uuid,
@@ -18,6 +23,29 @@ pub struct SyncOp {
     pub device_id: Uuid,
 }
 
+impl SyncOp {
+    // The whole schedule travels as one field so a snooze and a review outcome
+    // from different devices resolve to one consistent schedule.
+    pub fn bookmark_review(bookmark: &Bookmark, id: Uuid, device_id: Uuid, timestamp: i64) -> Self {
+        Self {
+            id,
+            entity: "bookmark".to_string(),
+            entity_id: bookmark.id,
+            field: REVIEW_FIELD.to_string(),
+            value: serde_json::to_value(&bookmark.review).unwrap_or_default(),
+            timestamp,
+            device_id,
+        }
+    }
+
+    pub fn review_schedule(&self) -> Option<Option<ReviewSchedule>> {
+        if self.entity != "bookmark" || self.field != REVIEW_FIELD {
+            return None;
+        }
+        serde_json::from_value(self.value.clone()).ok()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MergeResult {
     pub merged_ops: Vec<SyncOp>,
@@ -66,3 +94,45 @@ pub fn merge_logs(left: &[SyncOp], right: &[SyncOp]) -> MergeResult {
         conflicts,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+    use crate::models::{BookmarkInput, BookmarkMetadata};
+    use crate::review::ReviewOutcome;
+    use crate::store::Store;
+
+    #[test]
+    fn review_schedules_merge_as_one_field() {
+        let store = MemoryStore::new();
+        let workspace = store.create_workspace("Inbox").unwrap();
+        let bookmark = store
+            .create_bookmark(BookmarkInput {
+                workspace_id: workspace.id,
+                url: "https://example.com/".to_string(),
+                title: "Example".to_string(),
+                notes: None,
+                metadata: BookmarkMetadata::default(),
+            })
+            .unwrap();
+        let (laptop, phone) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let snoozed = store.schedule_review(bookmark.id, 1_700_000_000_000).unwrap();
+        let snooze = SyncOp::bookmark_review(&snoozed, Uuid::new_v4(), laptop, 1_000);
+        let reviewed = store.record_review(bookmark.id, ReviewOutcome::Good).unwrap();
+        let review = SyncOp::bookmark_review(&reviewed, Uuid::new_v4(), phone, 2_000);
+        let done = store.record_review(bookmark.id, ReviewOutcome::Done).unwrap();
+        let finish = SyncOp::bookmark_review(&done, Uuid::new_v4(), laptop, 3_000);
+
+        let merged = merge_logs(std::slice::from_ref(&snooze), std::slice::from_ref(&review));
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.merged_ops.len(), 1);
+        assert_eq!(merged.merged_ops[0].review_schedule(), Some(reviewed.review));
+
+        let merged = merge_logs(&[snooze, finish], &[review]);
+        assert_eq!(merged.merged_ops.len(), 1);
+        assert_eq!(merged.merged_ops[0].value, serde_json::Value::Null);
+        assert_eq!(merged.merged_ops[0].review_schedule(), Some(None));
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{CoreError, Result};
+use crate::models::{Bookmark, BookmarkFilter, BookmarkInput, TagJob};
+use crate::store::Store;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotedTab {
+    pub bookmark: Bookmark,
+    pub job: Option<TagJob>,
+    pub created: bool,
+}
+
+// Turns an open tab into a bookmark in its tab set's workspace. A bookmark
+// with the same URL there is returned as is, like the importers' dedupe.
+pub fn promote_tab<S: Store>(store: &S, tab_id: Uuid) -> Result<PromotedTab> {
+    let tab = store.get_tab(tab_id)?.ok_or(CoreError::NotFound)?;
+    let tab_set = store.get_tab_set(tab.tab_set_id)?.ok_or(CoreError::NotFound)?;
+    let existing = store
+        .list_bookmarks(BookmarkFilter {
+            workspace_id: Some(tab_set.workspace_id),
+            ..BookmarkFilter::default()
+        })?
+        .into_iter()
+        .find(|bookmark| bookmark.url == tab.url);
+    if let Some(bookmark) = existing {
+        return Ok(PromotedTab {
+            bookmark,
+            job: None,
+            created: false,
+        });
+    }
+
+    let title = if tab.title.is_empty() { tab.url.clone() } else { tab.title };
+    let bookmark = store.create_bookmark(BookmarkInput {
+        workspace_id: tab_set.workspace_id,
+        url: tab.url,
+        title,
+        notes: None,
+        metadata: Default::default(),
+    })?;
+    let job = store.enqueue_tag_job(bookmark.id)?;
+    Ok(PromotedTab {
+        bookmark,
+        job: Some(job),
+        created: true,
+    })
+}
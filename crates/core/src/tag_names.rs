@@ -0,0 +1,125 @@
+use unicode_normalization::UnicodeNormalization;
+
+pub const MAX_TAG_CHARS: usize = 64;
+
+// Singulars ending in `s`, plurals that are also the singular, and plurals
+// the suffix rules below would mangle.
+const UNCHANGED_WORDS: &[&str] = &[
+    "analytics", "apparatus", "atlas", "bonus", "cactus", "campus", "canvas", "census",
+    "consensus", "cookies", "corpus", "dataops", "devops", "economics", "ethics", "focus",
+    "genius", "gitops", "graphics", "https", "jenkins", "kubernetes", "linguistics",
+    "mathematics", "mlops", "movies", "news", "nexus", "octopus", "pandas", "physics",
+    "politics", "postgres", "radius", "rails", "redis", "robotics", "secops", "series",
+    "species", "statistics", "status", "stimulus", "syllabus", "virus", "windows",
+];
+
+// Plurals of words ending in a silent `e` that the `-ies`/`-ches` rules would
+// cut too short (`caches` is not `cach`).
+const SILENT_E_WORDS: &[&str] = &[
+    "avalanches", "caches", "headaches", "moustaches", "mustaches", "niches", "psyches",
+    "quiches",
+];
+
+// Plurals whose `s` follows a letter that usually marks a singular (`macos`,
+// `nodejs`, `analysis`), so they need to be listed to be singularized.
+const PLURAL_WORDS: &[&str] = &[
+    "apis", "areas", "cpus", "demos", "emojis", "gpus", "ideas", "kpis", "logos", "menus",
+    "photos", "repos", "schemas", "todos", "tpus", "videos",
+];
+
+// The canonical form of a tag name: NFKC, lower case, whitespace runs turned
+// into single hyphens, the last hyphen-separated word singularized (English,
+// ASCII only) and at most `MAX_TAG_CHARS` characters. `None` when nothing is
+// left.
+pub fn normalize_tag(name: &str) -> Option<String> {
+    let folded: String = name.nfkc().collect::<String>().to_lowercase().nfkc().collect();
+    let mut slug = String::with_capacity(folded.len());
+    for c in folded.chars() {
+        let c = if c.is_whitespace() { '-' } else { c };
+        if c == '-' && (slug.is_empty() || slug.ends_with('-')) {
+            continue;
+        }
+        slug.push(c);
+    }
+    let slug = slug.trim_end_matches('-');
+
+    let (head, last) = match slug.rsplit_once('-') {
+        Some((head, last)) => (Some(head), last),
+        None => (None, slug),
+    };
+    let last = singularize(last);
+    let mut normalized = match head {
+        Some(head) => format!("{}-{}", head, last),
+        None => last,
+    };
+    if normalized.chars().count() > MAX_TAG_CHARS {
+        normalized = normalized.chars().take(MAX_TAG_CHARS).collect();
+        normalized.truncate(normalized.trim_end_matches('-').len());
+    }
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+fn singularize(word: &str) -> String {
+    if word.len() <= 3
+        || !word.bytes().all(|b| b.is_ascii_lowercase())
+        || UNCHANGED_WORDS.contains(&word)
+    {
+        return word.to_string();
+    }
+    if SILENT_E_WORDS.contains(&word) {
+        return word[..word.len() - 1].to_string();
+    }
+    // A one-letter stem means the `ie` belongs to the word: `ties`, `pies`.
+    if let Some(stem) = word.strip_suffix("ies").filter(|stem| stem.len() > 1) {
+        return format!("{}y", stem);
+    }
+    for suffix in ["sses", "xes", "ches", "shes", "zzes"] {
+        if word.ends_with(suffix) {
+            return word[..word.len() - 2].to_string();
+        }
+    }
+    let Some(stem) = word.strip_suffix('s') else {
+        return word.to_string();
+    };
+    if PLURAL_WORDS.contains(&word) || !stem.ends_with(['a', 'i', 'j', 'o', 's', 'u']) {
+        return stem.to_string();
+    }
+    word.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_tag_names() {
+        let normalized = |name: &str| normalize_tag(name);
+        assert_eq!(normalized(" Rust ").as_deref(), Some("rust"));
+        assert_eq!(normalized("ＲＵＳＴ").as_deref(), Some("rust"));
+        assert_eq!(normalized("Large  Language\tModels").as_deref(), Some("large-language-model"));
+        assert_eq!(normalized("rust--lang").as_deref(), Some("rust-lang"));
+        assert_eq!(normalized("Libraries").as_deref(), Some("library"));
+        assert_eq!(normalized("Patches").as_deref(), Some("patch"));
+        assert_eq!(normalized("caches").as_deref(), Some("cache"));
+        assert_eq!(normalized("niches").as_deref(), Some("niche"));
+        assert_eq!(normalized("ties").as_deref(), Some("tie"));
+        assert_eq!(normalized("GPUs").as_deref(), Some("gpu"));
+        assert_eq!(normalized("analysis").as_deref(), Some("analysis"));
+        assert_eq!(normalized("Status").as_deref(), Some("status"));
+        assert_eq!(normalized("news").as_deref(), Some("news"));
+        assert_eq!(normalized("aws").as_deref(), Some("aws"));
+        for word in ["devops", "macos", "nodejs", "rails", "https", "canvas", "atlas"] {
+            assert_eq!(normalized(word).as_deref(), Some(word));
+        }
+        assert_eq!(normalized("Ruby on Rails").as_deref(), Some("ruby-on-rails"));
+        assert_eq!(normalized("APIs").as_deref(), Some("api"));
+        assert_eq!(normalized("videos").as_deref(), Some("video"));
+        assert_eq!(normalized("tools").as_deref(), Some("tool"));
+        assert_eq!(normalized("ycombinator.com").as_deref(), Some("ycombinator.com"));
+        assert_eq!(normalized("Straße").as_deref(), Some("straße"));
+        assert_eq!(normalized("机器学习").as_deref(), Some("机器学习"));
+        assert_eq!(normalized(" - ").as_deref(), None);
+        let long = normalized(&format!("{} tail", "a".repeat(70))).unwrap();
+        assert_eq!(long.chars().count(), MAX_TAG_CHARS);
+    }
+}
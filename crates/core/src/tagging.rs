@@ -1,25 +1,74 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
+use crate::domains::parse_host;
+use crate::keywords::{KeywordExtractor, TextSource};
 use crate::models::{TagSource, TagSuggestion};
 
+// Which domain tags a URL gets: `news.ycombinator.com` yields `ycombinator.com`
+// (registrable), `news.ycombinator.com` (host) or both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainTags {
+    #[default]
+    Registrable,
+    Host,
+    Both,
+}
+
+const LANGUAGE_SAMPLE_CHARS: usize = 2_000;
+
 pub struct TaggingRules {
-    stopwords: HashSet<String>,
+    keywords: KeywordExtractor,
+    domain_tags: DomainTags,
+}
+
+impl Default for TaggingRules {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TaggingRules {
     pub fn new() -> Self {
-        let words = [
-            "the", "and", "for", "with", "that", "this", "from", "into", "your", "you", "are",
-            "was", "were", "have", "has", "about", "http", "https",
-        ];
-        let stopwords = words.iter().map(|word| word.to_string()).collect();
-        Self { stopwords }
+        Self {
+            keywords: KeywordExtractor::new(),
+            domain_tags: DomainTags::default(),
+        }
+    }
+
+    pub fn with_domain_tags(mut self, domain_tags: DomainTags) -> Self {
+        self.domain_tags = domain_tags;
+        self
+    }
+
+    pub fn with_acronyms<I, T>(mut self, acronyms: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.keywords = self.keywords.with_acronyms(acronyms);
+        self
     }
 
     pub fn suggest(&self, url: &str, title: &str) -> Vec<TagSuggestion> {
+        self.suggest_for(url, title, None, &[])
+    }
+
+    // `language` is the page's declared language, if any. A keyword that
+    // stems like one of `known_tags` reuses that tag's name, so `models`
+    // lands on an existing `model` tag.
+    pub fn suggest_for(
+        &self,
+        url: &str,
+        title: &str,
+        language: Option<&str>,
+        known_tags: &[String],
+    ) -> Vec<TagSuggestion> {
         let mut suggestions = Vec::new();
 
-        if let Some(domain) = extract_domain(url) {
+        for domain in self.domain_names(url) {
             suggestions.push(TagSuggestion {
                 name: domain,
                 confidence: 0.72,
@@ -27,9 +76,18 @@ impl TaggingRules {
             });
         }
 
-        for keyword in extract_keywords(title, &self.stopwords) {
+        let language = self.keywords.detect_language(title, language);
+        let known = self.known_tags(known_tags, language);
+        for keyword in self.keywords.extract(title, language) {
+            let name = match known.get(&keyword.stem) {
+                Some(tag) => tag.to_string(),
+                None => keyword.term,
+            };
+            if suggestions.iter().any(|suggestion| suggestion.name == name) {
+                continue;
+            }
             suggestions.push(TagSuggestion {
-                name: keyword,
+                name,
                 confidence: 0.6,
                 source: TagSource::Rules,
             });
@@ -39,38 +97,62 @@ impl TaggingRules {
     }
 }
 
-fn extract_domain(url: &str) -> Option<String> {
-    let trimmed = url.trim();
-    let without_scheme = trimmed
-        .trim_start_matches("https://")
-        .trim_start_matches("http://");
-    let domain = without_scheme.split('/').next()?;
-    let domain = domain.trim_start_matches("www.");
-    if domain.is_empty() {
-        None
-    } else {
-        Some(domain.to_string())
+impl TaggingRules {
+    // Multi-word tags from the title, notes and page text. Keyphrase scores
+    // map onto 0.5..0.9: one mention early in the title gives about 0.75,
+    // repeated mentions across sources approach 0.9.
+    pub fn suggest_phrases(
+        &self,
+        sources: &[(TextSource, &str)],
+        language: Option<&str>,
+        known_tags: &[String],
+    ) -> Vec<TagSuggestion> {
+        let sample: String = sources
+            .iter()
+            .map(|(_, text)| *text)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .chars()
+            .take(LANGUAGE_SAMPLE_CHARS)
+            .collect();
+        let language = self.keywords.detect_language(&sample, language);
+        let known = self.known_tags(known_tags, language);
+        self.keywords
+            .keyphrases(sources, language)
+            .into_iter()
+            .map(|keyphrase| {
+                let key = self.keywords.phrase_key(&keyphrase.phrase, language);
+                TagSuggestion {
+                    name: known.get(&key).map_or(keyphrase.phrase, |tag| tag.to_string()),
+                    confidence: 0.5 + 0.4 * (1.0 - (-keyphrase.score).exp()),
+                    source: TagSource::Rules,
+                }
+            })
+            .collect()
     }
-}
 
-fn extract_keywords(text: &str, stopwords: &HashSet<String>) -> Vec<String> {
-    let mut seen = HashSet::new();
-    text.split(|c: char| !c.is_alphanumeric())
-        .filter_map(|raw| {
-            let candidate = raw.trim().to_lowercase();
-            if candidate.len() < 4 {
-                return None;
-            }
-            if stopwords.contains(&candidate) {
-                return None;
-            }
-            if seen.insert(candidate.clone()) {
-                Some(candidate)
-            } else {
-                None
-            }
-        })
-        .collect()
+    fn known_tags<'a>(&self, tags: &'a [String], language: &str) -> HashMap<String, &'a String> {
+        let mut known = HashMap::new();
+        for tag in tags {
+            known.entry(self.keywords.phrase_key(tag, language)).or_insert(tag);
+        }
+        known
+    }
+
+    // Hosts without a registrable domain (IPs, `localhost`) fall back to the
+    // host in registrable mode.
+    fn domain_names(&self, url: &str) -> Vec<String> {
+        let Some(parsed) = parse_host(url) else {
+            return Vec::new();
+        };
+        let registrable = parsed.registrable_domain.unwrap_or_else(|| parsed.host.clone());
+        match self.domain_tags {
+            DomainTags::Registrable => vec![registrable],
+            DomainTags::Host => vec![parsed.host],
+            DomainTags::Both if parsed.host == registrable => vec![registrable],
+            DomainTags::Both => vec![registrable, parsed.host],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -86,9 +168,44 @@ mod tests {
         );
 
         let names: Vec<_> = suggestions.iter().map(|s| s.name.as_str()).collect();
-        assert!(names.contains(&"news.ycombinator.com"));
+        assert!(names.contains(&"ycombinator.com"));
+        assert!(!names.contains(&"news.ycombinator.com"));
         assert!(names.contains(&"openai"));
         assert!(names.contains(&"research"));
         assert!(names.contains(&"model"));
+
+        let known = vec!["Research".to_string(), "machine learning".to_string()];
+        let names: Vec<_> = rules
+            .suggest_for("https://example.com/", "Researching LLM models", Some("en"), &known)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["example.com", "Research", "llm", "models"]);
+
+        let known = vec!["Deep Learning".to_string()];
+        let phrases = rules.suggest_phrases(
+            &[
+                (TextSource::Title, "Introduction to deep learning with Rust"),
+                (TextSource::Page, "Rust type system: basics.\nThe Rust type system, again."),
+            ],
+            None,
+            &known,
+        );
+        let names: Vec<_> = phrases.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Deep Learning", "rust type system"]);
+        assert!(phrases[0].confidence > 0.7 && phrases[0].confidence < 0.9);
+
+        let domains = |mode: DomainTags, url: &str| {
+            TaggingRules::new().with_domain_tags(mode).domain_names(url)
+        };
+        let url = "https://news.ycombinator.com/item?id=1";
+        assert_eq!(domains(DomainTags::Host, url), vec!["news.ycombinator.com"]);
+        assert_eq!(
+            domains(DomainTags::Both, url),
+            vec!["ycombinator.com", "news.ycombinator.com"]
+        );
+        assert_eq!(domains(DomainTags::Both, "https://example.com/"), vec!["example.com"]);
+        assert_eq!(domains(DomainTags::Registrable, "http://localhost:3000/"), vec!["localhost"]);
+        assert!(domains(DomainTags::Both, "mailto:").is_empty());
     }
 }
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use jieba_rs::Jieba;
+use unicode_segmentation::UnicodeSegmentation;
+
+static JIEBA: OnceLock<Jieba> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Han,
+    Hiragana,
+    Katakana,
+    Hangul,
+    Other,
+}
+
+impl Script {
+    fn of(c: char) -> Self {
+        match c {
+            '\u{3005}' | '\u{3007}' | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{3134F}' => Script::Han,
+            '\u{3040}'..='\u{309F}' => Script::Hiragana,
+            '\u{30A0}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => {
+                Script::Katakana
+            }
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                Script::Hangul
+            }
+            _ => Script::Other,
+        }
+    }
+
+    // Han and kana share runs in the search index since Japanese mixes them
+    // within a word; Hangul is kept apart.
+    fn search_class(self) -> u8 {
+        match self {
+            Script::Han | Script::Hiragana | Script::Katakana => 1,
+            Script::Hangul => 2,
+            Script::Other => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub text: String,
+    // Written without spaces between words (Chinese, Japanese, Korean).
+    pub cjk: bool,
+}
+
+pub(crate) fn is_cjk(word: &str) -> bool {
+    word.chars().any(|c| Script::of(c) != Script::Other)
+}
+
+// Words for tagging, in their original case. Han runs go through the jieba
+// dictionary, with consecutive single characters it could not place (often
+// Japanese forms like `入門`) joined back up. Katakana and Hangul runs stay
+// whole, and hiragana (mostly particles and inflections) is dropped.
+pub(crate) fn keyword_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for (script, start, end) in segments(text, |script| script) {
+        let piece = &text[start..end];
+        match script {
+            Script::Han => {
+                let jieba = JIEBA.get_or_init(Jieba::new);
+                let mut words: Vec<String> = Vec::new();
+                let mut single = false;
+                for word in jieba.cut(piece, false) {
+                    let is_single = word.chars().count() == 1;
+                    match words.last_mut() {
+                        Some(last) if single && is_single => last.push_str(word),
+                        _ => words.push(word.to_string()),
+                    }
+                    single = is_single;
+                }
+                tokens.extend(words.into_iter().map(|text| Token { text, cjk: true }));
+            }
+            Script::Hiragana => {}
+            Script::Katakana | Script::Hangul => tokens.push(Token {
+                text: piece.to_string(),
+                cjk: true,
+            }),
+            Script::Other => tokens.push(Token {
+                text: piece.to_string(),
+                cjk: false,
+            }),
+        }
+    }
+    tokens
+}
+
+// Lower-case terms for the search index and for queries against it. CJK
+// runs become overlapping bigrams (a lone character stays a unigram), so a
+// query matches without knowing where the words end.
+pub(crate) fn search_terms(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut terms = Vec::new();
+    for (script, start, end) in segments(text, Script::search_class) {
+        let piece = &text[start..end];
+        let pieces = if script == Script::Other {
+            vec![piece.to_lowercase()]
+        } else {
+            let chars: Vec<char> = piece.chars().collect();
+            if chars.len() == 1 {
+                vec![piece.to_string()]
+            } else {
+                chars.windows(2).map(|pair| pair.iter().collect()).collect()
+            }
+        };
+        for term in pieces {
+            if seen.insert(term.clone()) {
+                terms.push(term);
+            }
+        }
+    }
+    terms
+}
+
+// UAX #29 words, split again at the punctuation a word may contain (`GPU's`,
+// `e.g`) and wherever the script changes. Adjacent pieces that map to the
+// same `group` are joined back up, which turns the single ideographs UAX #29
+// yields into runs.
+fn segments<K: PartialEq>(
+    text: &str,
+    group: impl Fn(Script) -> K,
+) -> Vec<(Script, usize, usize)> {
+    let mut segments: Vec<(Script, usize, usize)> = Vec::new();
+    for (offset, word) in text.unicode_word_indices() {
+        for (index, c) in word.char_indices() {
+            if is_word_punctuation(c) {
+                continue;
+            }
+            let script = Script::of(c);
+            let start = offset + index;
+            let end = start + c.len_utf8();
+            match segments.last_mut() {
+                Some((last, _, last_end))
+                    if *last_end == start
+                        && (*last == script
+                            || (script != Script::Other && group(*last) == group(script))) =>
+                {
+                    *last_end = end;
+                }
+                _ => segments.push((script, start, end)),
+            }
+        }
+    }
+    segments
+}
+
+// The MidLetter, MidNum and connector characters UAX #29 keeps inside words.
+fn is_word_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(
+            c,
+            '\u{00B7}'
+                | '\u{2018}'
+                | '\u{2019}'
+                | '\u{2024}'
+                | '\u{2027}'
+                | '\u{FE13}'
+                | '\u{FE52}'
+                | '\u{FE55}'
+                | '\u{FF07}'
+                | '\u{FF0E}'
+                | '\u{FF1A}'
+                | '\u{066C}'
+                | '\u{060C}'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keywords(text: &str) -> Vec<String> {
+        keyword_tokens(text).into_iter().map(|token| token.text).collect()
+    }
+
+    #[test]
+    fn segments_words_across_scripts() {
+        assert_eq!(
+            keywords("GPU's e.g. naïve-Bayes 3.14"),
+            vec!["GPU", "s", "e", "g", "naïve", "Bayes", "3", "14"]
+        );
+        assert_eq!(keywords("机器学习入门指南"), vec!["机器", "学习", "入门", "指南"]);
+        assert_eq!(keywords("プログラミング入門の本"), vec!["プログラミング", "入門", "本"]);
+        assert_eq!(keywords("Rust로 웹 서버 만들기"), vec!["Rust", "로", "웹", "서버", "만들기"]);
+        assert_eq!(keywords("Нейронные сети: введение"), vec!["Нейронные", "сети", "введение"]);
+        assert_eq!(keywords("التعلم الآلي"), vec!["التعلم", "الآلي"]);
+        assert_eq!(keywords("हिन्दी भाषा"), vec!["हिन्दी", "भाषा"]);
+    }
+
+    #[test]
+    fn indexes_cjk_as_bigrams() {
+        assert_eq!(search_terms("深度学习 Rust"), vec!["深度", "度学", "学习", "rust"]);
+        assert_eq!(search_terms("東京の大学"), vec!["東京", "京の", "の大", "大学"]);
+        assert_eq!(search_terms("서버가 Über"), vec!["서버", "버가", "über"]);
+        assert_eq!(search_terms("学"), vec!["学"]);
+        assert!(search_terms("!!! ...").is_empty());
+    }
+}
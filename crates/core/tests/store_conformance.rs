@@ -0,0 +1,1208 @@
+use std::sync::Arc;
+
+use lester_core::{
+    export_graph, import_bookmarks, list_neighbours, promote_tab, run_saved_search,
+    BookmarkFilter, BookmarkInput, BookmarkLinkInput, BookmarkMetadata, BookmarkPatch,
+    BookmarkSort, Citation, Clock, CoreError, FolderMapping, HistoryEntryInput, ImportOptions,
+    ImportedBookmark, JobKind, LinkCheckInput, LinkDirection, LinkStatus, ManualClock,
+    MemoryStore, ReadState, RelationKind, ReviewOutcome, SavedSearchInput, SequentialIds,
+    SqliteStore, Store, TabInput, TabSetInput, TagJobStatus, TagRuleInput, TagSource,
+    TagSuggestion,
+};
+use uuid::Uuid;
+
+fn input(workspace_id: Uuid, url: &str, title: &str) -> BookmarkInput {
+    BookmarkInput {
+        workspace_id,
+        url: url.to_string(),
+        title: title.to_string(),
+        notes: None,
+        metadata: BookmarkMetadata::default(),
+    }
+}
+
+fn suggestion(name: &str, confidence: f32, source: TagSource) -> TagSuggestion {
+    TagSuggestion {
+        name: name.to_string(),
+        confidence,
+        source,
+    }
+}
+
+fn workspaces<S: Store>(store: &S) {
+    let first = store.create_workspace("  Research ").unwrap();
+    assert_eq!(first.name, "Research");
+    store.create_workspace("Reading").unwrap();
+    assert!(matches!(
+        store.create_workspace("   "),
+        Err(CoreError::InvalidInput(_))
+    ));
+
+    let mut names: Vec<_> = store
+        .list_workspaces()
+        .unwrap()
+        .into_iter()
+        .map(|workspace| workspace.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["Reading", "Research"]);
+}
+
+fn bookmarks<S: Store>(store: &S) {
+    let research = store.create_workspace("Research").unwrap();
+    let reading = store.create_workspace("Reading").unwrap();
+    let rust = store
+        .create_bookmark(input(research.id, " https://www.rust-lang.org/ ", "Rust"))
+        .unwrap();
+    assert_eq!(rust.url, "https://www.rust-lang.org/");
+    let sqlite = store
+        .create_bookmark_at(
+            input(reading.id, "https://sqlite.org/", "SQLite docs"),
+            Some(1_600_000_000_000),
+            Some(1_500_000_000_000),
+        )
+        .unwrap();
+    assert_eq!(sqlite.created_at, 1_600_000_000_000);
+    assert_eq!(sqlite.updated_at, 1_600_000_000_000);
+    assert!(matches!(
+        store.create_bookmark(input(research.id, "", "Empty")),
+        Err(CoreError::InvalidInput(_))
+    ));
+
+    let fetched = store.get_bookmark(rust.id).unwrap().unwrap();
+    assert_eq!(fetched.title, "Rust");
+    assert!(store.get_bookmark(Uuid::new_v4()).unwrap().is_none());
+
+    let all = store.list_bookmarks(BookmarkFilter::default()).unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].id, rust.id, "most recently updated first");
+
+    let in_reading = store
+        .list_bookmarks(BookmarkFilter {
+            workspace_id: Some(reading.id),
+            ..BookmarkFilter::default()
+        })
+        .unwrap();
+    assert_eq!(in_reading.len(), 1);
+    assert_eq!(in_reading[0].id, sqlite.id);
+
+    let by_query = store
+        .list_bookmarks(BookmarkFilter {
+            query: Some("sqlite".to_string()),
+            ..BookmarkFilter::default()
+        })
+        .unwrap();
+    assert_eq!(by_query.len(), 1);
+    assert_eq!(by_query[0].id, sqlite.id);
+
+    let patched = store
+        .update_bookmark(
+            sqlite.id,
+            BookmarkPatch {
+                notes: Some("Read the WAL docs".to_string()),
+                citation: Some(Citation {
+                    authors: vec!["D. Richard Hipp".to_string()],
+                    year: Some(2000),
+                    ..Citation::default()
+                }),
+                ..BookmarkPatch::default()
+            },
+        )
+        .unwrap();
+    assert!(patched.updated_at >= sqlite.updated_at);
+    let stored = store.get_bookmark(sqlite.id).unwrap().unwrap();
+    assert_eq!(stored.notes.as_deref(), Some("Read the WAL docs"));
+    assert_eq!(stored.citation.year, Some(2000));
+    assert!(matches!(
+        store.update_bookmark(
+            sqlite.id,
+            BookmarkPatch {
+                title: Some(" ".to_string()),
+                ..BookmarkPatch::default()
+            }
+        ),
+        Err(CoreError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        store.update_bookmark(Uuid::new_v4(), BookmarkPatch::default()),
+        Err(CoreError::NotFound)
+    ));
+}
+
+fn metadata<S: Store>(store: &S) {
+    let workspace = store.create_workspace("Reading").unwrap();
+    let long_read = store
+        .create_bookmark(BookmarkInput {
+            metadata: BookmarkMetadata {
+                description: Some(" Notes on B-tree pages ".to_string()),
+                site_name: Some("SQLite Blog".to_string()),
+                author: Some("D. Richard Hipp".to_string()),
+                language: Some("en_GB".to_string()),
+                word_count: Some(4_600),
+                ..BookmarkMetadata::default()
+            },
+            ..input(workspace.id, "https://sqlite.org/btree", "File format")
+        })
+        .unwrap();
+    assert_eq!(
+        long_read.metadata.description.as_deref(),
+        Some("Notes on B-tree pages")
+    );
+    assert_eq!(long_read.metadata.language.as_deref(), Some("en-gb"));
+    assert_eq!(long_read.metadata.reading_time_minutes, Some(20));
+    let short_read = store
+        .create_bookmark(BookmarkInput {
+            metadata: BookmarkMetadata {
+                language: Some("de".to_string()),
+                reading_time_minutes: Some(3),
+                ..BookmarkMetadata::default()
+            },
+            ..input(workspace.id, "https://example.de/", "Kurz")
+        })
+        .unwrap();
+
+    let filtered = |filter: BookmarkFilter| -> Vec<Uuid> {
+        store
+            .list_bookmarks(filter)
+            .unwrap()
+            .into_iter()
+            .map(|bookmark| bookmark.id)
+            .collect()
+    };
+    assert_eq!(
+        filtered(BookmarkFilter {
+            query: Some("b-tree".to_string()),
+            ..BookmarkFilter::default()
+        }),
+        vec![long_read.id]
+    );
+    assert_eq!(
+        filtered(BookmarkFilter {
+            language: Some("EN".to_string()),
+            ..BookmarkFilter::default()
+        }),
+        vec![long_read.id]
+    );
+    assert_eq!(
+        filtered(BookmarkFilter {
+            site_name: Some("sqlite blog".to_string()),
+            author: Some("D. Richard Hipp".to_string()),
+            ..BookmarkFilter::default()
+        }),
+        vec![long_read.id]
+    );
+    assert_eq!(
+        filtered(BookmarkFilter {
+            max_reading_time: Some(10),
+            ..BookmarkFilter::default()
+        }),
+        vec![short_read.id]
+    );
+
+    store
+        .update_bookmark(
+            long_read.id,
+            BookmarkPatch {
+                metadata: Some(BookmarkMetadata {
+                    favicon_url: Some("https://sqlite.org/favicon.ico".to_string()),
+                    published_at: Some(1_700_000_000_000),
+                    ..long_read.metadata.clone()
+                }),
+                ..BookmarkPatch::default()
+            },
+        )
+        .unwrap();
+    let stored = store.get_bookmark(long_read.id).unwrap().unwrap();
+    assert_eq!(stored.metadata.published_at, Some(1_700_000_000_000));
+    assert_eq!(stored.metadata.site_name.as_deref(), Some("SQLite Blog"));
+    assert_eq!(
+        stored.metadata.favicon_url.as_deref(),
+        Some("https://sqlite.org/favicon.ico")
+    );
+}
+
+fn search<S: Store>(store: &S) {
+    let workspace = store.create_workspace("Research").unwrap();
+    for (url, title) in [
+        ("https://example.cn/dl", "深度学习框架的比较"),
+        ("https://example.jp/nlp", "東京大学の自然言語処理入門"),
+        ("https://example.kr/rust", "Rust로 웹 서버 만들기"),
+        ("https://example.com/ml", "Machine learning with Rust"),
+    ] {
+        store.create_bookmark(input(workspace.id, url, title)).unwrap();
+    }
+    let titles = |query: &str| {
+        let mut titles: Vec<String> = store
+            .list_bookmarks(BookmarkFilter {
+                query: Some(query.to_string()),
+                ..BookmarkFilter::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|bookmark| bookmark.title)
+            .collect();
+        titles.sort();
+        titles
+    };
+
+    assert_eq!(titles("学习 深度"), vec!["深度学习框架的比较"]);
+    assert_eq!(titles("自然 入門"), vec!["東京大学の自然言語処理入門"]);
+    assert_eq!(titles("서버"), vec!["Rust로 웹 서버 만들기"]);
+    assert_eq!(titles("rust learn"), vec!["Machine learning with Rust"]);
+    assert_eq!(titles("RUST"), vec!["Machine learning with Rust", "Rust로 웹 서버 만들기"]);
+    assert!(titles("学习 rust").is_empty());
+
+    let ml = store
+        .list_bookmarks(BookmarkFilter::default())
+        .unwrap()
+        .into_iter()
+        .find(|bookmark| bookmark.url == "https://example.com/ml")
+        .unwrap();
+    store
+        .update_bookmark(
+            ml.id,
+            BookmarkPatch {
+                title: Some("Ownership in Rust".to_string()),
+                ..BookmarkPatch::default()
+            },
+        )
+        .unwrap();
+    assert!(titles("machine").is_empty());
+    assert_eq!(titles("ownership rust"), vec!["Ownership in Rust"]);
+}
+
+fn tags<S: Store>(store: &S) {
+    let workspace = store.create_workspace("Research").unwrap();
+    let first = store
+        .create_bookmark(input(workspace.id, "https://a.example/", "A"))
+        .unwrap();
+    let second = store
+        .create_bookmark(input(workspace.id, "https://b.example/", "B"))
+        .unwrap();
+
+    store
+        .upsert_tags_for_bookmark(
+            first.id,
+            &[
+                suggestion("rust", 0.6, TagSource::Rules),
+                suggestion("Databases", 0.8, TagSource::Rules),
+            ],
+        )
+        .unwrap();
+    let tags = store
+        .upsert_tags_for_bookmark(second.id, &[suggestion("ＲＵＳＴ ", 0.9, TagSource::Llm)])
+        .unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].name, "rust");
+    store
+        .upsert_tags_for_bookmark(
+            first.id,
+            &[
+                suggestion("Rust", 0.7, TagSource::User),
+                suggestion("rust", 0.5, TagSource::Llm),
+                suggestion(" - ", 0.9, TagSource::Llm),
+            ],
+        )
+        .unwrap();
+
+    let names: Vec<_> = store
+        .list_tags()
+        .unwrap()
+        .into_iter()
+        .map(|tag| tag.name)
+        .collect();
+    assert_eq!(names, vec!["database", "rust"]);
+
+    let details = store.list_tags_for_bookmark(first.id).unwrap();
+    assert_eq!(details.len(), 2);
+    assert_eq!(details[0].name, "rust");
+    assert_eq!(details[1].name, "database");
+    assert!(matches!(details[0].source, TagSource::User));
+    assert!((details[0].confidence - 0.94).abs() < 1e-6);
+    assert_eq!(details[0].evidence.len(), 3);
+
+    let tagged = store
+        .list_bookmarks(BookmarkFilter {
+            tag: Some("Rust".to_string()),
+            ..BookmarkFilter::default()
+        })
+        .unwrap();
+    assert_eq!(tagged.len(), 2);
+
+    let cloud = store.get_tag_cloud(1).unwrap();
+    assert_eq!(cloud.len(), 1);
+    assert_eq!(cloud[0].name, "rust");
+    assert!((cloud[0].weight - 1.84).abs() < 1e-4);
+}
+
+fn tag_evidence<S: Store>(store: &S) {
+    let workspace = store.create_workspace("Research").unwrap();
+    let bookmark = store
+        .create_bookmark(input(workspace.id, "https://a.example/", "A"))
+        .unwrap();
+
+    store
+        .upsert_tags_for_bookmark(bookmark.id, &[suggestion("rust", 0.6, TagSource::Rules)])
+        .unwrap();
+    store
+        .upsert_tags_for_bookmark(bookmark.id, &[suggestion("Rust", 0.5, TagSource::Llm)])
+        .unwrap();
+    let details = store.list_tags_for_bookmark(bookmark.id).unwrap();
+    assert_eq!(details.len(), 1);
+    assert!((details[0].confidence - 0.8).abs() < 1e-6);
+    assert_eq!(details[0].source, TagSource::Rules);
+    let sources: Vec<_> = details[0]
+        .evidence
+        .iter()
+        .map(|evidence| (evidence.source.clone(), evidence.confidence))
+        .collect();
+    assert_eq!(sources, vec![(TagSource::Llm, 0.5), (TagSource::Rules, 0.6)]);
+
+    // A source's new suggestion replaces its earlier evidence.
+    store
+        .upsert_tags_for_bookmark(bookmark.id, &[suggestion("rust", 0.2, TagSource::Rules)])
+        .unwrap();
+    let details = store.list_tags_for_bookmark(bookmark.id).unwrap();
+    assert!((details[0].confidence - 0.6).abs() < 1e-6);
+    assert_eq!(details[0].source, TagSource::Llm);
+    assert_eq!(details[0].evidence.len(), 2);
+}
+
+fn tag_jobs<S: Store>(store: &S) {
+    let workspace = store.create_workspace("Research").unwrap();
+    let bookmark = store
+        .create_bookmark(input(workspace.id, "https://a.example/", "A"))
+        .unwrap();
+    let first = store.enqueue_tag_job(bookmark.id).unwrap();
+    let second = store.enqueue_tag_job(bookmark.id).unwrap();
+    assert_eq!(first.status, TagJobStatus::Pending);
+
+    assert_eq!(store.fetch_pending_tag_jobs(1).unwrap().len(), 1);
+    store
+        .update_tag_job_status(first.id, TagJobStatus::Running)
+        .unwrap();
+    let pending = store.fetch_pending_tag_jobs(10).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, second.id);
+
+    store
+        .update_tag_job_status(second.id, TagJobStatus::Failed)
+        .unwrap();
+    assert!(store.fetch_pending_tag_jobs(10).unwrap().is_empty());
+}
+
+fn history<S: Store>(store: &S) {
+    let workspace = store.create_workspace("Research").unwrap();
+    let entry = |title: &str, visit_count, last_visited_at| HistoryEntryInput {
+        workspace_id: workspace.id,
+        url: "https://docs.rs/".to_string(),
+        title: title.to_string(),
+        visit_count,
+        last_visited_at,
+    };
+    store.upsert_history_entry(entry("Docs", 5, 200)).unwrap();
+    let merged = store.upsert_history_entry(entry("", 3, 300)).unwrap();
+    assert_eq!(merged.title, "Docs");
+    assert_eq!(merged.visit_count, 5);
+    assert_eq!(merged.last_visited_at, 300);
+
+    store
+        .upsert_history_entry(HistoryEntryInput {
+            url: "https://crates.io/".to_string(),
+            ..entry("Crates", 1, 100)
+        })
+        .unwrap();
+    let entries = store.list_history(Some(workspace.id), 10).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].url, "https://docs.rs/");
+    assert_eq!(store.list_history(None, 1).unwrap().len(), 1);
+    assert!(store.list_history(Some(Uuid::new_v4()), 10).unwrap().is_empty());
+}
+
+fn stats<S: Store>(store: &S) {
+    let empty = store.create_workspace("Empty").unwrap();
+    let research = store.create_workspace("Research").unwrap();
+    let first = store
+        .create_bookmark(input(research.id, "https://a.example/", "A"))
+        .unwrap();
+    store
+        .create_bookmark(input(research.id, "https://b.example/", "B"))
+        .unwrap();
+    store
+        .upsert_tags_for_bookmark(
+            first.id,
+            &[
+                suggestion("rust", 0.6, TagSource::Rules),
+                suggestion("systems", 0.8, TagSource::Llm),
+                suggestion("databases", 0.8, TagSource::Llm),
+            ],
+        )
+        .unwrap();
+    let done = store.enqueue_tag_job(first.id).unwrap();
+    store.enqueue_tag_job(first.id).unwrap();
+    store
+        .update_tag_job_status(done.id, TagJobStatus::Done)
+        .unwrap();
+
+    let stats = store.stats().unwrap();
+    assert_eq!(stats.workspaces, 2);
+    assert_eq!(stats.bookmarks, 2);
+    assert_eq!(stats.tags, 3);
+    let counts: Vec<_> = stats
+        .bookmarks_per_workspace
+        .iter()
+        .map(|entry| (entry.workspace_id, entry.bookmarks))
+        .collect();
+    assert_eq!(counts, vec![(empty.id, 0), (research.id, 2)]);
+    assert_eq!(stats.tag_sources.rules, 1);
+    assert_eq!(stats.tag_sources.llm, 2);
+    assert_eq!(stats.tag_sources.user, 0);
+    assert_eq!(stats.jobs.pending, 1);
+    assert_eq!(stats.jobs.done, 1);
+    assert!(stats.jobs.oldest_pending_age_secs.is_some());
+}
+
+fn clock_and_ids<S: Store>(store: &S, clock: &ManualClock) {
+    let workspace = store.create_workspace("Research").unwrap();
+    assert_eq!(workspace.id, Uuid::from_u128(1));
+    assert_eq!(workspace.created_at, 1_700_000_000_000);
+
+    let first = store
+        .create_bookmark(input(workspace.id, "https://a.example/", "A"))
+        .unwrap();
+    clock.advance(5);
+    let second = store
+        .create_bookmark(input(workspace.id, "https://b.example/", "B"))
+        .unwrap();
+    assert_eq!(first.id, Uuid::from_u128(2));
+    assert_eq!(second.created_at - first.created_at, 5);
+
+    clock.advance(5);
+    let updated = store
+        .update_bookmark(
+            first.id,
+            BookmarkPatch {
+                notes: Some("later".to_string()),
+                ..BookmarkPatch::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(updated.updated_at, 1_700_000_000_010);
+    let order: Vec<_> = store
+        .list_bookmarks(BookmarkFilter::default())
+        .unwrap()
+        .into_iter()
+        .map(|bookmark| bookmark.id)
+        .collect();
+    assert_eq!(order, vec![first.id, second.id]);
+
+    store.enqueue_tag_job(first.id).unwrap();
+    clock.advance(90_000);
+    assert_eq!(store.stats().unwrap().jobs.oldest_pending_age_secs, Some(90));
+}
+
+fn reading<S: Store>(store: &S, clock: &ManualClock) {
+    let workspace = store.create_workspace("Research").unwrap();
+    let old = store
+        .create_bookmark(input(workspace.id, "https://old.example/", "Old"))
+        .unwrap();
+    let recent = store
+        .create_bookmark(input(workspace.id, "https://recent.example/", "Recent"))
+        .unwrap();
+    let never = store
+        .create_bookmark(input(workspace.id, "https://never.example/", "Never"))
+        .unwrap();
+
+    for _ in 0..3 {
+        store.record_visit(old.id, None).unwrap();
+    }
+    clock.advance(40 * 86_400_000);
+    let visited = store.record_visit(recent.id, Some(0.4)).unwrap();
+    assert_eq!(visited.reading.state, ReadState::Reading);
+    assert_eq!(visited.reading.visit_count, 1);
+    assert_eq!(visited.reading.last_visited_at, Some(clock.now_ms()));
+    assert_eq!(visited.updated_at, recent.updated_at);
+    let finished = store.record_visit(recent.id, Some(1.0)).unwrap();
+    assert_eq!(finished.reading.state, ReadState::Read);
+    assert_eq!(finished.reading.visit_count, 2);
+
+    let archived = store
+        .set_read_state(old.id, ReadState::Archived, None)
+        .unwrap();
+    assert_eq!(archived.reading.visit_count, 3);
+    assert_eq!(
+        store.get_bookmark(old.id).unwrap().unwrap().reading,
+        archived.reading
+    );
+    assert!(matches!(
+        store.set_read_state(recent.id, ReadState::Reading, Some(1.5)),
+        Err(CoreError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        store.record_visit(Uuid::new_v4(), None),
+        Err(CoreError::NotFound)
+    ));
+
+    let unread = store
+        .list_bookmarks(BookmarkFilter {
+            read_state: Some(ReadState::Unread),
+            ..BookmarkFilter::default()
+        })
+        .unwrap();
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0].id, never.id);
+
+    let order = |sort| -> Vec<Uuid> {
+        store
+            .list_bookmarks(BookmarkFilter {
+                sort,
+                ..BookmarkFilter::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|bookmark| bookmark.id)
+            .collect()
+    };
+    // 2 visits today outweigh 3 visits 40 days ago (200 vs 90).
+    assert_eq!(order(BookmarkSort::Frecency), vec![recent.id, old.id, never.id]);
+    assert_eq!(order(BookmarkSort::LastVisited), vec![recent.id, old.id, never.id]);
+}
+
+fn review<S: Store>(store: &S, clock: &ManualClock) {
+    const DAY: i64 = 86_400_000;
+    let workspace = store.create_workspace("Research").unwrap();
+    let other = store.create_workspace("Other").unwrap();
+    let paper = store
+        .create_bookmark(input(workspace.id, "https://paper.example/", "Paper"))
+        .unwrap();
+    let later = store
+        .create_bookmark(input(workspace.id, "https://later.example/", "Later"))
+        .unwrap();
+    let elsewhere = store
+        .create_bookmark(input(other.id, "https://elsewhere.example/", "Elsewhere"))
+        .unwrap();
+    assert!(store.list_due_reviews(None, 0, 10).unwrap().is_empty());
+
+    let now = clock.now_ms();
+    store.schedule_review(paper.id, now - DAY).unwrap();
+    store.schedule_review(later.id, now + 3 * DAY).unwrap();
+    store.schedule_review(elsewhere.id, now).unwrap();
+    let due: Vec<_> = store
+        .list_due_reviews(None, 0, 10)
+        .unwrap()
+        .into_iter()
+        .map(|bookmark| bookmark.id)
+        .collect();
+    assert_eq!(due, vec![paper.id, elsewhere.id]);
+    assert_eq!(store.list_due_reviews(Some(other.id), 0, 10).unwrap().len(), 1);
+    assert_eq!(store.list_due_reviews(None, 0, 1).unwrap().len(), 1);
+
+    // It is 22:13 UTC: four hours from now is tomorrow in UTC, still today in Tokyo.
+    store.schedule_review(later.id, now + DAY / 6).unwrap();
+    assert_eq!(store.list_due_reviews(None, 0, 10).unwrap().len(), 2);
+    assert_eq!(store.list_due_reviews(None, 540, 10).unwrap().len(), 3);
+    assert!(matches!(
+        store.list_due_reviews(None, 15 * 60, 10),
+        Err(CoreError::InvalidInput(_))
+    ));
+    store.schedule_review(later.id, now + 3 * DAY).unwrap();
+
+    let reviewed = store.record_review(paper.id, ReviewOutcome::Good).unwrap();
+    let schedule = reviewed.review.unwrap();
+    assert_eq!(schedule.due_at, now + DAY);
+    assert_eq!(schedule.repetitions, 1);
+    assert_eq!(schedule.last_reviewed_at, Some(now));
+    assert_eq!(
+        store.get_bookmark(paper.id).unwrap().unwrap().review,
+        Some(schedule)
+    );
+
+    store
+        .set_read_state(elsewhere.id, ReadState::Archived, None)
+        .unwrap();
+    assert!(store.list_due_reviews(None, 0, 10).unwrap().is_empty());
+
+    clock.advance(3 * DAY);
+    assert_eq!(store.list_due_reviews(None, 0, 10).unwrap().len(), 2);
+    let done = store.record_review(later.id, ReviewOutcome::Done).unwrap();
+    assert_eq!(done.review, None);
+    assert_eq!(store.get_bookmark(later.id).unwrap().unwrap().review, None);
+    assert!(matches!(
+        store.record_review(Uuid::new_v4(), ReviewOutcome::Good),
+        Err(CoreError::NotFound)
+    ));
+}
+
+// Read-modify-write calls from several threads must not lose updates.
+fn concurrent_updates<S: Store + Sync>(store: &S) {
+    let workspace = store.create_workspace("Inbox").unwrap();
+    let bookmark = store
+        .create_bookmark(input(workspace.id, "https://example.com/", "Example"))
+        .unwrap();
+    store.schedule_review(bookmark.id, 1_700_000_000_000).unwrap();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..5 {
+                    store.record_visit(bookmark.id, None).unwrap();
+                    store.record_review(bookmark.id, ReviewOutcome::Good).unwrap();
+                }
+            });
+        }
+    });
+    let bookmark = store.get_bookmark(bookmark.id).unwrap().unwrap();
+    assert_eq!(bookmark.reading.visit_count, 20);
+    assert_eq!(bookmark.review.unwrap().repetitions, 20);
+}
+
+fn link_checks<S: Store>(store: &S) {
+    let workspace = store.create_workspace("Research").unwrap();
+    let bookmark = store
+        .create_bookmark(input(workspace.id, "https://old.example/paper", "Paper"))
+        .unwrap();
+    let other = store
+        .create_bookmark(input(workspace.id, "https://fine.example/", "Fine"))
+        .unwrap();
+    assert_eq!(bookmark.link_status, None);
+
+    let job = store.enqueue_job(bookmark.id, JobKind::LinkCheck).unwrap();
+    let pending = store.fetch_pending_tag_jobs(10).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, job.id);
+    assert_eq!(pending[0].kind, JobKind::LinkCheck);
+    assert_eq!(store.enqueue_tag_job(other.id).unwrap().kind, JobKind::Tag);
+
+    let moved = store
+        .record_link_check(
+            bookmark.id,
+            LinkCheckInput {
+                status_code: Some(200),
+                final_url: Some("https://new.example/paper".to_string()),
+                error: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(moved.status, LinkStatus::Moved);
+    let timed_out = || LinkCheckInput {
+        error: Some("timed out".to_string()),
+        ..LinkCheckInput::default()
+    };
+    let unreachable = store.record_link_check(bookmark.id, timed_out()).unwrap();
+    assert_eq!(unreachable.status, LinkStatus::Unreachable);
+    let stored = store.get_bookmark(bookmark.id).unwrap().unwrap();
+    assert_eq!(stored.link_status, Some(LinkStatus::Unreachable));
+    let server_error = LinkCheckInput {
+        status_code: Some(503),
+        ..LinkCheckInput::default()
+    };
+    let still_unreachable = store.record_link_check(bookmark.id, server_error).unwrap();
+    assert_eq!(still_unreachable.status, LinkStatus::Unreachable);
+    let broken = store.record_link_check(bookmark.id, timed_out()).unwrap();
+    assert_eq!(broken.status, LinkStatus::Broken);
+    store
+        .record_link_check(
+            other.id,
+            LinkCheckInput {
+                status_code: Some(200),
+                final_url: Some("https://fine.example".to_string()),
+                error: None,
+            },
+        )
+        .unwrap();
+
+    let stored = store.get_bookmark(bookmark.id).unwrap().unwrap();
+    assert_eq!(stored.link_status, Some(LinkStatus::Broken));
+    assert_eq!(stored.updated_at, bookmark.updated_at);
+    let history = store.list_link_checks(bookmark.id, 10).unwrap();
+    assert_eq!(history, vec![broken, still_unreachable, unreachable, moved]);
+    assert_eq!(store.list_link_checks(bookmark.id, 1).unwrap().len(), 1);
+
+    let broken_bookmarks = store
+        .list_bookmarks(BookmarkFilter {
+            link_status: Some(LinkStatus::Broken),
+            ..BookmarkFilter::default()
+        })
+        .unwrap();
+    assert_eq!(broken_bookmarks.len(), 1);
+    assert_eq!(broken_bookmarks[0].id, bookmark.id);
+    assert!(matches!(
+        store.record_link_check(Uuid::new_v4(), LinkCheckInput::default()),
+        Err(CoreError::NotFound)
+    ));
+}
+
+fn saved_searches<S: Store>(store: &S, clock: &ManualClock) {
+    let research = store.create_workspace("Research").unwrap();
+    let other = store.create_workspace("Other").unwrap();
+    let paper = store
+        .create_bookmark(input(research.id, "https://arxiv.org/abs/1", "Attention paper"))
+        .unwrap();
+    store
+        .create_bookmark(input(other.id, "https://blog.example/attention", "Attention post"))
+        .unwrap();
+
+    let pinned = store
+        .create_saved_search(SavedSearchInput {
+            name: " Attention ".to_string(),
+            workspace_id: Some(research.id),
+            query: Some("attention read:unread".to_string()),
+            filter: None,
+        })
+        .unwrap();
+    assert_eq!(pinned.name, "Attention");
+    assert_eq!(pinned.filter.query.as_deref(), Some("attention"));
+    let global = store
+        .create_saved_search(SavedSearchInput {
+            name: "Everything".to_string(),
+            ..SavedSearchInput::default()
+        })
+        .unwrap();
+    assert!(matches!(
+        store.create_saved_search(SavedSearchInput {
+            name: "Bad".to_string(),
+            query: Some("read:someday".to_string()),
+            ..SavedSearchInput::default()
+        }),
+        Err(CoreError::InvalidInput(_))
+    ));
+    assert_eq!(store.get_saved_search(pinned.id).unwrap(), Some(pinned.clone()));
+    assert_eq!(store.list_saved_searches(None).unwrap().len(), 2);
+    assert_eq!(store.list_saved_searches(Some(other.id)).unwrap(), vec![global.clone()]);
+
+    let first = run_saved_search(store, pinned.id).unwrap();
+    assert_eq!(first.total, 1);
+    assert_eq!(first.new_bookmark_ids, vec![paper.id]);
+    assert_eq!(first.search.last_viewed_at, Some(clock.now_ms()));
+
+    clock.advance(1_000);
+    let added = store
+        .create_bookmark(input(research.id, "https://arxiv.org/abs/2", "More attention"))
+        .unwrap();
+    let second = run_saved_search(store, pinned.id).unwrap();
+    assert_eq!(second.total, 2);
+    assert_eq!(second.new_count, 1);
+    assert_eq!(second.new_bookmark_ids, vec![added.id]);
+
+    // Imports keep the source's `created_at` but are still new here.
+    clock.advance(1_000);
+    import_bookmarks(
+        store,
+        vec![ImportedBookmark {
+            url: "https://arxiv.org/abs/3".to_string(),
+            title: "Old attention paper".to_string(),
+            created_at: Some(1_500_000_000_000),
+            ..ImportedBookmark::default()
+        }],
+        &ImportOptions {
+            workspace_id: research.id,
+            folder_mapping: FolderMapping::Workspaces,
+            dry_run: false,
+        },
+    )
+    .unwrap();
+    let third = run_saved_search(store, pinned.id).unwrap();
+    assert_eq!(third.total, 3);
+    assert_eq!(third.new_count, 1);
+    let imported = store.get_bookmark(third.new_bookmark_ids[0]).unwrap().unwrap();
+    assert_eq!(imported.created_at, 1_500_000_000_000);
+    assert_eq!(imported.added_at, clock.now_ms());
+    assert_eq!(run_saved_search(store, global.id).unwrap().total, 4);
+
+    store.delete_saved_search(global.id).unwrap();
+    assert!(matches!(
+        store.delete_saved_search(global.id),
+        Err(CoreError::NotFound)
+    ));
+    assert!(matches!(
+        run_saved_search(store, global.id),
+        Err(CoreError::NotFound)
+    ));
+}
+
+fn tab(url: &str, title: &str) -> TabInput {
+    TabInput {
+        url: url.to_string(),
+        title: title.to_string(),
+        ..TabInput::default()
+    }
+}
+
+fn tabs<S: Store>(store: &S, clock: &ManualClock) {
+    let workspace = store.create_workspace("Research").unwrap();
+    let existing = store
+        .create_bookmark(input(workspace.id, "https://arxiv.org/abs/1", "Attention paper"))
+        .unwrap();
+
+    let session = store
+        .save_tab_set(TabSetInput {
+            workspace_id: workspace.id,
+            name: " Morning ".to_string(),
+            tabs: vec![
+                TabInput {
+                    pinned: true,
+                    last_active_at: Some(1_699_999_000_000),
+                    ..tab("https://mail.example", "Mail")
+                },
+                tab("https://arxiv.org/abs/1", "arXiv"),
+                tab(" https://blog.example/post ", ""),
+            ],
+        })
+        .unwrap();
+    assert_eq!(session.name, "Morning");
+    assert_eq!(session.tabs.len(), 3);
+    assert_eq!(session.tabs[2].url, "https://blog.example/post");
+    assert_eq!(
+        session.tabs.iter().map(|tab| tab.position).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    assert!(session.tabs[0].pinned);
+    assert_eq!(store.get_tab_set(session.id).unwrap(), Some(session.clone()));
+    assert_eq!(store.get_tab(session.tabs[1].id).unwrap(), Some(session.tabs[1].clone()));
+    assert!(matches!(
+        store.save_tab_set(TabSetInput {
+            workspace_id: Uuid::new_v4(),
+            name: "Nowhere".to_string(),
+            tabs: Vec::new(),
+        }),
+        Err(CoreError::NotFound)
+    ));
+    assert!(matches!(
+        store.save_tab_set(TabSetInput {
+            workspace_id: workspace.id,
+            name: "Blank".to_string(),
+            tabs: vec![tab(" ", "Blank")],
+        }),
+        Err(CoreError::InvalidInput(_))
+    ));
+
+    let duplicate = promote_tab(store, session.tabs[1].id).unwrap();
+    assert!(!duplicate.created);
+    assert_eq!(duplicate.bookmark.id, existing.id);
+    assert!(duplicate.job.is_none());
+    let promoted = promote_tab(store, session.tabs[2].id).unwrap();
+    assert!(promoted.created);
+    assert_eq!(promoted.bookmark.workspace_id, workspace.id);
+    assert_eq!(promoted.bookmark.title, "https://blog.example/post");
+    assert_eq!(promoted.job.unwrap().bookmark_id, promoted.bookmark.id);
+
+    clock.advance(1_000);
+    let other = store
+        .save_tab_set(TabSetInput {
+            workspace_id: workspace.id,
+            name: "Evening".to_string(),
+            tabs: Vec::new(),
+        })
+        .unwrap();
+    clock.advance(1_000);
+    let replaced = store
+        .replace_tabs(session.id, vec![tab("https://news.example", "News")])
+        .unwrap();
+    assert_eq!(replaced.updated_at, clock.now_ms());
+    assert_eq!(replaced.tabs.len(), 1);
+    assert_eq!(store.get_tab(session.tabs[0].id).unwrap(), None);
+    let listed = store.list_tab_sets(Some(workspace.id)).unwrap();
+    assert_eq!(listed, vec![replaced, other.clone()]);
+    assert!(store.list_tab_sets(Some(Uuid::new_v4())).unwrap().is_empty());
+
+    store.delete_tab_set(session.id).unwrap();
+    assert_eq!(store.list_tab_sets(None).unwrap(), vec![other]);
+    assert!(matches!(store.delete_tab_set(session.id), Err(CoreError::NotFound)));
+    assert!(matches!(
+        store.replace_tabs(session.id, Vec::new()),
+        Err(CoreError::NotFound)
+    ));
+}
+
+fn link(source: Uuid, target: Uuid, kind: RelationKind) -> BookmarkLinkInput {
+    BookmarkLinkInput {
+        source_id: source,
+        target_id: target,
+        kind,
+        notes: None,
+    }
+}
+
+fn bookmark_links<S: Store>(store: &S, clock: &ManualClock) {
+    let research = store.create_workspace("Research").unwrap();
+    let other = store.create_workspace("Other").unwrap();
+    let survey = store
+        .create_bookmark(input(research.id, "https://arxiv.org/abs/1", "Survey"))
+        .unwrap();
+    let paper = store
+        .create_bookmark(input(research.id, "https://arxiv.org/abs/2", "Paper"))
+        .unwrap();
+    let rebuttal = store
+        .create_bookmark(input(research.id, "https://arxiv.org/abs/3", "Rebuttal"))
+        .unwrap();
+    let outside = store
+        .create_bookmark(input(other.id, "https://blog.example/post", "Post"))
+        .unwrap();
+
+    let cites = store
+        .link_bookmarks(BookmarkLinkInput {
+            notes: Some(" section 2 ".to_string()),
+            ..link(survey.id, paper.id, RelationKind::Cites)
+        })
+        .unwrap();
+    assert_eq!(cites.notes.as_deref(), Some("section 2"));
+    clock.advance(1_000);
+    let contradicts = store
+        .link_bookmarks(link(rebuttal.id, paper.id, RelationKind::Contradicts))
+        .unwrap();
+    store
+        .link_bookmarks(link(paper.id, outside.id, RelationKind::Related))
+        .unwrap();
+
+    let relinked = store
+        .link_bookmarks(BookmarkLinkInput {
+            notes: Some("table 3".to_string()),
+            ..link(survey.id, paper.id, RelationKind::Cites)
+        })
+        .unwrap();
+    assert_eq!(relinked.id, cites.id);
+    assert_eq!(relinked.created_at, cites.created_at);
+    assert_eq!(relinked.notes.as_deref(), Some("table 3"));
+    assert!(matches!(
+        store.link_bookmarks(link(paper.id, paper.id, RelationKind::Related)),
+        Err(CoreError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        store.link_bookmarks(link(paper.id, Uuid::new_v4(), RelationKind::Cites)),
+        Err(CoreError::NotFound)
+    ));
+
+    let neighbours = list_neighbours(store, paper.id).unwrap();
+    assert_eq!(
+        neighbours
+            .iter()
+            .map(|neighbour| (neighbour.bookmark.id, neighbour.direction))
+            .collect::<Vec<_>>(),
+        vec![
+            (survey.id, LinkDirection::Incoming),
+            (rebuttal.id, LinkDirection::Incoming),
+            (outside.id, LinkDirection::Outgoing),
+        ]
+    );
+    assert_eq!(neighbours[0].link, relinked);
+    assert!(matches!(
+        list_neighbours(store, Uuid::new_v4()),
+        Err(CoreError::NotFound)
+    ));
+
+    let graph = export_graph(store, research.id).unwrap();
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(
+        graph.edges.iter().map(|edge| edge.id).collect::<Vec<_>>(),
+        vec![cites.id, contradicts.id]
+    );
+    let degree = |id| graph.nodes.iter().find(|node| node.id == id).unwrap().degree;
+    assert_eq!(degree(paper.id), 2);
+    assert_eq!(degree(survey.id), 1);
+
+    store.delete_bookmark_link(contradicts.id).unwrap();
+    assert_eq!(store.list_bookmark_links(rebuttal.id).unwrap(), Vec::new());
+    assert!(matches!(
+        store.delete_bookmark_link(contradicts.id),
+        Err(CoreError::NotFound)
+    ));
+}
+
+fn tag_rules<S: Store>(store: &S, clock: &ManualClock) {
+    let arxiv = TagRuleInput {
+        name: " arXiv ".to_string(),
+        url_pattern: None,
+        domain: Some("arxiv.org".to_string()),
+        title_regex: None,
+        has_tags: Vec::new(),
+        tags: vec!["paper".to_string()],
+        confidence: 0.9,
+        enabled: true,
+    };
+    let created = store.create_tag_rule(arxiv.clone()).unwrap();
+    assert_eq!(created.spec.name, "arXiv");
+    let surveys = store
+        .create_tag_rule(TagRuleInput {
+            name: "surveys".to_string(),
+            domain: None,
+            title_regex: Some("(?i)survey".to_string()),
+            has_tags: vec!["ml".to_string()],
+            tags: vec!["survey".to_string()],
+            ..arxiv.clone()
+        })
+        .unwrap();
+    assert!(matches!(
+        store.create_tag_rule(TagRuleInput {
+            title_regex: Some("(".to_string()),
+            ..arxiv.clone()
+        }),
+        Err(CoreError::InvalidInput(_))
+    ));
+    assert_eq!(store.list_tag_rules().unwrap(), vec![created.clone(), surveys.clone()]);
+
+    clock.advance(1_000);
+    let updated = store
+        .update_tag_rule(
+            created.id,
+            TagRuleInput {
+                confidence: 0.75,
+                enabled: false,
+                ..arxiv.clone()
+            },
+        )
+        .unwrap();
+    assert_eq!(updated.created_at, created.created_at);
+    assert_eq!(updated.updated_at, clock.now_ms());
+    assert!(!updated.spec.enabled);
+    assert_eq!(updated.spec.confidence, 0.75);
+    assert_eq!(store.list_tag_rules().unwrap()[0], updated);
+    assert!(matches!(
+        store.update_tag_rule(Uuid::new_v4(), arxiv.clone()),
+        Err(CoreError::NotFound)
+    ));
+
+    store.delete_tag_rule(surveys.id).unwrap();
+    assert_eq!(store.list_tag_rules().unwrap(), vec![updated]);
+    assert!(matches!(store.delete_tag_rule(surveys.id), Err(CoreError::NotFound)));
+}
+
+macro_rules! conformance_suite {
+    ($backend:ident, $setup:expr) => {
+        mod $backend {
+            use super::*;
+
+            #[test]
+            fn workspaces() {
+                let (_guard, store) = $setup;
+                super::workspaces(&store);
+            }
+
+            #[test]
+            fn bookmarks() {
+                let (_guard, store) = $setup;
+                super::bookmarks(&store);
+            }
+
+            #[test]
+            fn metadata() {
+                let (_guard, store) = $setup;
+                super::metadata(&store);
+            }
+
+            #[test]
+            fn search() {
+                let (_guard, store) = $setup;
+                super::search(&store);
+            }
+
+            #[test]
+            fn tags() {
+                let (_guard, store) = $setup;
+                super::tags(&store);
+            }
+
+            #[test]
+            fn tag_evidence() {
+                let (_guard, store) = $setup;
+                super::tag_evidence(&store);
+            }
+
+            #[test]
+            fn tag_jobs() {
+                let (_guard, store) = $setup;
+                super::tag_jobs(&store);
+            }
+
+            #[test]
+            fn link_checks() {
+                let (_guard, store) = $setup;
+                super::link_checks(&store);
+            }
+
+            #[test]
+            fn history() {
+                let (_guard, store) = $setup;
+                super::history(&store);
+            }
+
+            #[test]
+            fn stats() {
+                let (_guard, store) = $setup;
+                super::stats(&store);
+            }
+
+            #[test]
+            fn clock_and_ids() {
+                let (_guard, store) = $setup;
+                let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+                let store = store
+                    .with_clock(clock.clone())
+                    .with_id_generator(SequentialIds::new());
+                super::clock_and_ids(&store, &clock);
+            }
+
+            #[test]
+            fn reading() {
+                let (_guard, store) = $setup;
+                let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+                let store = store.with_clock(clock.clone());
+                super::reading(&store, &clock);
+            }
+
+            #[test]
+            fn saved_searches() {
+                let (_guard, store) = $setup;
+                let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+                let store = store.with_clock(clock.clone());
+                super::saved_searches(&store, &clock);
+            }
+
+            #[test]
+            fn review() {
+                let (_guard, store) = $setup;
+                let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+                let store = store.with_clock(clock.clone());
+                super::review(&store, &clock);
+            }
+
+            #[test]
+            fn tabs() {
+                let (_guard, store) = $setup;
+                let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+                let store = store.with_clock(clock.clone());
+                super::tabs(&store, &clock);
+            }
+
+            #[test]
+            fn bookmark_links() {
+                let (_guard, store) = $setup;
+                let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+                let store = store.with_clock(clock.clone());
+                super::bookmark_links(&store, &clock);
+            }
+
+            #[test]
+            fn concurrent_updates() {
+                let (_guard, store) = $setup;
+                super::concurrent_updates(&store);
+            }
+
+            #[test]
+            fn tag_rules() {
+                let (_guard, store) = $setup;
+                let clock = Arc::new(ManualClock::new(1_700_000_000_000));
+                let store = store.with_clock(clock.clone());
+                super::tag_rules(&store, &clock);
+            }
+        }
+    };
+}
+
+fn sqlite_store() -> (tempfile::TempDir, SqliteStore) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SqliteStore::new(dir.path().join("lester.db").to_string_lossy().to_string());
+    store.migrate().unwrap();
+    (dir, store)
+}
+
+conformance_suite!(memory, ((), MemoryStore::new()));
+conformance_suite!(sqlite, sqlite_store());
@@ -0,0 +1,127 @@
+use lester_core::LinkCheckInput;
+
+// HEAD first to keep checks cheap. Plenty of servers refuse or mishandle it with
+// all sorts of error statuses (or drop the connection), so anything short of a
+// successful HEAD gets a second try with GET.
+pub fn check_link(agent: &ureq::Agent, url: &str) -> LinkCheckInput {
+    match request(agent, "HEAD", url) {
+        check if check.status_code.is_some_and(|code| code < 400) => check,
+        _ => request(agent, "GET", url),
+    }
+}
+
+fn request(agent: &ureq::Agent, method: &str, url: &str) -> LinkCheckInput {
+    match agent.request(method, url).call() {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => LinkCheckInput {
+            status_code: Some(i64::from(response.status())),
+            final_url: Some(response.get_url().to_string()),
+            error: None,
+        },
+        Err(err) => LinkCheckInput {
+            status_code: None,
+            final_url: None,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::time::Duration;
+
+    use http_body_util::Full;
+    use hyper::body::{Bytes, Incoming};
+    use hyper::{Method, Request, Response};
+    use hyper_util::rt::TokioIo;
+    use lester_core::LinkStatus;
+
+    use super::*;
+
+    async fn respond(request: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+        let (status, location) = match (request.method(), request.uri().path()) {
+            (_, "/ok") => (200, None),
+            (_, "/old") => (301, Some("/ok")),
+            (&Method::HEAD, "/no-head") => (405, None),
+            (_, "/no-head") => (200, None),
+            (&Method::HEAD, "/head-missing") => (404, None),
+            (_, "/head-missing") => (200, None),
+            (_, "/down") => (503, None),
+            (_, "/slow") => {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                (200, None)
+            }
+            _ => (404, None),
+        };
+        let mut response = Response::builder().status(status);
+        if let Some(location) = location {
+            response = response.header("location", location);
+        }
+        Ok(response.body(Full::new(Bytes::new())).unwrap())
+    }
+
+    fn serve() -> String {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                tx.send(listener.local_addr().unwrap()).unwrap();
+                loop {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    tokio::spawn(
+                        hyper::server::conn::http1::Builder::new().serve_connection(
+                            TokioIo::new(stream),
+                            hyper::service::service_fn(respond),
+                        ),
+                    );
+                }
+            });
+        });
+        format!("http://{}", rx.recv().unwrap())
+    }
+
+    #[test]
+    fn classifies_live_moved_and_dead_links() {
+        let base = serve();
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_millis(300))
+            .build();
+        let status = |path: &str| {
+            let url = format!("{}{}", base, path);
+            let check = check_link(&agent, &url);
+            (LinkStatus::classify(&url, &check), check)
+        };
+
+        let (ok, check) = status("/ok");
+        assert_eq!(ok, LinkStatus::Ok);
+        assert_eq!(check.status_code, Some(200));
+
+        let (moved, check) = status("/old");
+        assert_eq!(moved, LinkStatus::Moved);
+        assert_eq!(check.final_url, Some(format!("{}/ok", base)));
+
+        let (no_head, check) = status("/no-head");
+        assert_eq!(no_head, LinkStatus::Ok);
+        assert_eq!(check.status_code, Some(200));
+
+        let (head_missing, check) = status("/head-missing");
+        assert_eq!(head_missing, LinkStatus::Ok);
+        assert_eq!(check.status_code, Some(200));
+
+        let (gone, check) = status("/gone");
+        assert_eq!(gone, LinkStatus::Broken);
+        assert_eq!(check.status_code, Some(404));
+
+        let (down, check) = status("/down");
+        assert_eq!(down, LinkStatus::Unreachable);
+        assert_eq!(check.status_code, Some(503));
+
+        let (slow, check) = status("/slow");
+        assert_eq!(slow, LinkStatus::Unreachable);
+        assert!(check.error.is_some());
+    }
+}
@@ -1,66 +1,367 @@
+mod links;
+
 use clap::Parser;
-use lester_core::{SqliteStore, TagJobStatus, TaggingRules, TagSuggestion, TagSource};
+use std::io::Read;
+use std::time::Duration;
+
+use lester_core::{
+    parse_calibration, Bookmark, BookmarkPatch, DomainTags, FusionMethod, JobKind, ProfileRegistry,
+    RuleSet, SqliteStore, Store, TagFusion, TagJobStatus, TagRuleInput, TaggingRules,
+    TagSuggestion, TagSource, TextSource,
+};
 use tracing::{info, warn};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Args {
-    #[arg(long, default_value = "lester.db")]
-    db_path: String,
+    /// Serve a single database instead of profiles under `--data-dir`.
+    #[arg(long, conflicts_with_all = ["profile", "all_profiles"])]
+    db_path: Option<String>,
+    #[arg(long, default_value = "lester-data")]
+    data_dir: String,
+    /// Profile to serve; defaults to the active profile.
+    #[arg(long, conflicts_with = "all_profiles")]
+    profile: Option<String>,
+    /// Serve every profile, picking up new ones as they are created.
+    #[arg(long, default_value_t = false)]
+    all_profiles: bool,
     #[arg(long, default_value_t = 3000)]
     poll_interval_ms: u64,
     #[arg(long, default_value_t = 8)]
     batch_size: usize,
     #[arg(long, default_value_t = false)]
     once: bool,
+    /// Skip fetching bookmarked pages to fill in description, author and other metadata.
+    #[arg(long, default_value_t = false)]
+    no_fetch: bool,
+    /// Tag bookmarks by registrable domain (`ycombinator.com`), full host
+    /// (`news.ycombinator.com`) or both.
+    #[arg(long, default_value = "registrable", value_parser = parse_domain_tags)]
+    domain_tags: DomainTags,
+    /// Extra short terms kept as keyword tags, comma separated (GPU, LLM and other common
+    /// acronyms are built in).
+    #[arg(long, value_delimiter = ',')]
+    acronyms: Vec<String>,
+    /// TOML file with extra tagging rules, applied alongside the rules stored in each database.
+    #[arg(long)]
+    rules: Option<String>,
+    /// How evidence from several sources for one tag is combined: `noisy-or` or `weighted`.
+    #[arg(long, default_value = "noisy-or", value_parser = parse_fusion_method)]
+    fusion: FusionMethod,
+    /// Per-source calibration weights between 0 and 1, comma separated (`rules=0.8,llm=0.9`).
+    #[arg(long, value_delimiter = ',', value_parser = parse_calibration_arg)]
+    calibration: Vec<(TagSource, f32)>,
+    /// Timeout for page fetches and link checks.
+    #[arg(long, default_value_t = 10_000)]
+    fetch_timeout_ms: u64,
+}
+
+fn parse_domain_tags(value: &str) -> Result<DomainTags, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| "expected one of: registrable, host, both".to_string())
+}
+
+fn parse_fusion_method(value: &str) -> Result<FusionMethod, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| "expected one of: noisy-or, weighted".to_string())
 }
 
+fn parse_calibration_arg(value: &str) -> Result<(TagSource, f32), String> {
+    parse_calibration(value).map_err(|err| err.to_string())
+}
+
+fn tag_fusion(args: &Args) -> TagFusion {
+    args.calibration
+        .iter()
+        .fold(TagFusion::default().with_method(args.fusion), |fusion, (source, weight)| {
+            fusion.with_weight(source.clone(), *weight)
+        })
+}
+
+const MAX_PAGE_BYTES: u64 = 2 * 1024 * 1024;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
     let args = Args::parse();
 
-    let store = SqliteStore::new(args.db_path);
-    store.migrate()?;
+    // Parsed up front so a broken rules file stops the worker before it takes any jobs.
+    let file_rules = match &args.rules {
+        Some(path) => RuleSet::parse_toml(&std::fs::read_to_string(path)?)?,
+        None => Vec::new(),
+    };
+
+    let heuristics = TaggingRules::new()
+        .with_domain_tags(args.domain_tags)
+        .with_acronyms(&args.acronyms);
+
+    let mut stores = Vec::new();
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_millis(args.fetch_timeout_ms))
+        .build();
+    let fetcher = (!args.no_fetch).then_some(&agent);
 
     info!("llm-worker started");
     loop {
-        let jobs = store.fetch_pending_tag_jobs(args.batch_size)?;
-        if jobs.is_empty() {
-            if args.once {
-                break;
+        refresh_stores(&args, &mut stores)?;
+        let mut processed = 0;
+        for (name, store) in &stores {
+            let count = process_batch(
+                store,
+                args.batch_size,
+                &agent,
+                fetcher,
+                &heuristics,
+                &file_rules,
+            )?;
+            if count > 0 {
+                info!("processed {} jobs for {}", count, name);
             }
+            processed += count;
+        }
+        if args.once {
+            break;
+        }
+        if processed == 0 {
             tokio::time::sleep(std::time::Duration::from_millis(args.poll_interval_ms)).await;
-            continue;
         }
+    }
+
+    Ok(())
+}
 
-        for job in jobs {
-            store.update_tag_job_status(job.id, TagJobStatus::Running)?;
-            let Some(bookmark) = store.get_bookmark(job.bookmark_id)? else {
-                warn!("missing bookmark for tag job {}", job.id);
-                store.update_tag_job_status(job.id, TagJobStatus::Failed)?;
-                continue;
-            };
-
-            let rules = TaggingRules::new();
-            let suggestions = rules
-                .suggest(&bookmark.url, &bookmark.title)
-                .into_iter()
-                .map(|s| TagSuggestion {
-                    name: s.name,
-                    confidence: (s.confidence * 0.9).min(0.95),
-                    source: TagSource::Llm,
-                })
-                .collect::<Vec<_>>();
-
-            store.upsert_tags_for_bookmark(bookmark.id, &suggestions)?;
-            store.update_tag_job_status(job.id, TagJobStatus::Done)?;
+// Opens the stores to poll. The registry is re-read on every poll: without
+// `--profile` the worker follows the active profile across switches, and with
+// `--all-profiles` profiles created while the worker runs get served too. A
+// profile whose database cannot be opened is skipped there rather than
+// stopping the others.
+fn refresh_stores(
+    args: &Args,
+    stores: &mut Vec<(String, SqliteStore)>,
+) -> lester_core::Result<()> {
+    if let Some(db_path) = &args.db_path {
+        if stores.is_empty() {
+            let store = SqliteStore::new(db_path.clone()).with_tag_fusion(tag_fusion(args));
+            store.migrate()?;
+            stores.push((db_path.clone(), store));
         }
+        return Ok(());
+    }
+    let registry = ProfileRegistry::open(&args.data_dir)?.with_tag_fusion(tag_fusion(args));
+    let names: Vec<String> = if args.all_profiles {
+        registry
+            .list()?
+            .into_iter()
+            .map(|profile| profile.name)
+            .collect()
+    } else {
+        match &args.profile {
+            Some(name) => vec![name.clone()],
+            None => vec![registry.active()?.name],
+        }
+    };
+    stores.retain(|(served, _)| names.contains(served));
+    for name in names {
+        if stores.iter().any(|(served, _)| *served == name) {
+            continue;
+        }
+        match registry.store(&name) {
+            Ok(store) => {
+                stores.push((name.clone(), store));
+                info!("serving profile {}", name);
+            }
+            Err(err) if args.all_profiles => warn!("skipping profile {}: {}", name, err),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
 
-        if args.once {
-            break;
+fn process_batch<S: Store>(
+    store: &S,
+    batch_size: usize,
+    agent: &ureq::Agent,
+    fetcher: Option<&ureq::Agent>,
+    heuristics: &TaggingRules,
+    file_rules: &[TagRuleInput],
+) -> lester_core::Result<usize> {
+    let jobs = store.fetch_pending_tag_jobs(batch_size)?;
+    let processed = jobs.len();
+    if jobs.is_empty() {
+        return Ok(0);
+    }
+    let stored_rules = store.list_tag_rules()?;
+    let rules = RuleSet::new(
+        stored_rules
+            .iter()
+            .map(|rule| &rule.spec)
+            .chain(file_rules),
+    )?;
+
+    for job in jobs {
+        store.update_tag_job_status(job.id, TagJobStatus::Running)?;
+        let Some(bookmark) = store.get_bookmark(job.bookmark_id)? else {
+            warn!("missing bookmark for tag job {}", job.id);
+            store.update_tag_job_status(job.id, TagJobStatus::Failed)?;
+            continue;
+        };
+        match job.kind {
+            JobKind::Tag => tag_bookmark(store, fetcher, heuristics, &rules, bookmark)?,
+            JobKind::LinkCheck => {
+                let check = links::check_link(agent, &bookmark.url);
+                let check = store.record_link_check(bookmark.id, check)?;
+                info!("link check {}: {}", bookmark.url, check.status.as_str());
+            }
         }
+        store.update_tag_job_status(job.id, TagJobStatus::Done)?;
     }
 
+    Ok(processed)
+}
+
+fn tag_bookmark<S: Store>(
+    store: &S,
+    fetcher: Option<&ureq::Agent>,
+    heuristics: &TaggingRules,
+    rules: &RuleSet,
+    bookmark: Bookmark,
+) -> lester_core::Result<()> {
+    let (bookmark, page_text) = match fetcher {
+        Some(agent) => enrich_metadata(store, agent, bookmark)?,
+        None => (bookmark, None),
+    };
+
+    // User rules see the tags already on the bookmark. When a rule and the
+    // built-in heuristics agree on a tag, each records its own evidence and
+    // the store fuses them.
+    let existing: Vec<String> = store
+        .list_tags_for_bookmark(bookmark.id)?
+        .into_iter()
+        .map(|tag| tag.name)
+        .collect();
+    let mut suggestions = rules.apply(&bookmark.url, &bookmark.title, &existing);
+    let known_tags: Vec<String> = store.list_tags()?.into_iter().map(|tag| tag.name).collect();
+    let language = bookmark.metadata.language.as_deref();
+    let mut sources = vec![(TextSource::Title, bookmark.title.as_str())];
+    sources.extend(bookmark.notes.as_deref().map(|notes| (TextSource::Notes, notes)));
+    sources.extend(page_text.as_deref().map(|text| (TextSource::Page, text)));
+    let mut candidates =
+        heuristics.suggest_for(&bookmark.url, &bookmark.title, language, &known_tags);
+    candidates.extend(heuristics.suggest_phrases(&sources, language, &known_tags));
+    let heuristics = candidates
+        .into_iter()
+        .map(|s| TagSuggestion {
+            name: s.name,
+            confidence: (s.confidence * 0.9).min(0.95),
+            source: TagSource::Llm,
+        })
+        .collect::<Vec<_>>();
+    suggestions.extend(heuristics);
+
+    store.upsert_tags_for_bookmark(bookmark.id, &suggestions)?;
     Ok(())
 }
+
+// Also hands back the page's visible text for keyphrase extraction.
+fn enrich_metadata<S: Store>(
+    store: &S,
+    agent: &ureq::Agent,
+    bookmark: Bookmark,
+) -> lester_core::Result<(Bookmark, Option<String>)> {
+    let html = match fetch_page(agent, &bookmark.url) {
+        Ok(html) => html,
+        Err(err) => {
+            warn!("could not fetch {} for metadata: {}", bookmark.url, err);
+            return Ok((bookmark, None));
+        }
+    };
+    let text = Some(lester_core::extract_text(&html));
+    let extracted = lester_core::extract_metadata(&html, &bookmark.url);
+    let metadata = bookmark.metadata.clone().fill_missing(extracted);
+    if metadata == bookmark.metadata {
+        return Ok((bookmark, text));
+    }
+    let bookmark = store.update_bookmark(
+        bookmark.id,
+        BookmarkPatch {
+            metadata: Some(metadata),
+            ..BookmarkPatch::default()
+        },
+    )?;
+    Ok((bookmark, text))
+}
+
+fn fetch_page(agent: &ureq::Agent, url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let response = agent.get(url).call()?;
+    let content_type = response.content_type().to_ascii_lowercase();
+    if !content_type.contains("html") {
+        return Err(format!("not an HTML page ({})", content_type).into());
+    }
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_PAGE_BYTES)
+        .read_to_end(&mut body)?;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use lester_core::{BookmarkInput, BookmarkMetadata, MemoryStore};
+
+    use super::*;
+
+    #[test]
+    fn fuses_rule_and_heuristic_evidence_for_the_same_tag() {
+        let store = MemoryStore::new();
+        let workspace = store.create_workspace("Inbox").unwrap();
+        let bookmark = store
+            .create_bookmark(BookmarkInput {
+                workspace_id: workspace.id,
+                url: "https://example.com/ownership".to_string(),
+                title: "Rust ownership and borrowing explained".to_string(),
+                notes: None,
+                metadata: BookmarkMetadata::default(),
+            })
+            .unwrap();
+        let file_rules = RuleSet::parse_toml(
+            r#"
+            [[rule]]
+            name = "rust"
+            title_regex = "(?i)\\brust\\b"
+            tags = ["rust"]
+            confidence = 0.6
+            "#,
+        )
+        .unwrap();
+        let rules = RuleSet::new(file_rules.iter()).unwrap();
+
+        tag_bookmark(&store, None, &TaggingRules::new(), &rules, bookmark.clone()).unwrap();
+
+        let tags = store.list_tags_for_bookmark(bookmark.id).unwrap();
+        let rust = tags.iter().find(|tag| tag.name == "rust").unwrap();
+        let sources: Vec<&TagSource> = rust.evidence.iter().map(|e| &e.source).collect();
+        assert!(sources.contains(&&TagSource::Rules));
+        assert!(sources.contains(&&TagSource::Llm));
+        assert!(rust.confidence > 0.6);
+    }
+
+    #[test]
+    fn follows_the_active_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = Args::parse_from(["llm-worker", "--data-dir", dir.path().to_str().unwrap()]);
+        let mut stores = Vec::new();
+        let served = |stores: &Vec<(String, SqliteStore)>| -> Vec<String> {
+            stores.iter().map(|(name, _)| name.clone()).collect()
+        };
+
+        refresh_stores(&args, &mut stores).unwrap();
+        assert_eq!(served(&stores), vec!["default"]);
+
+        let registry = ProfileRegistry::open(dir.path()).unwrap();
+        registry.create("work").unwrap();
+        registry.switch("work").unwrap();
+        refresh_stores(&args, &mut stores).unwrap();
+        assert_eq!(served(&stores), vec!["work"]);
+    }
+}